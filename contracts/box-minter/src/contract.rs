@@ -1,24 +1,36 @@
 use crate::msg::{
-    ExecuteMsg, GetBoxResponse, GetStateResponse, InstantiateMsg, Metadata, MintBoxCrystal,
-    QueryMsg, ReceiveMsg,
+    DropTableEntry, DropTableResponse, ExecuteMsg, GetBoxResponse, GetStateResponse,
+    InstantiateMsg, Metadata, MigrateMsg, MintBoxCrystal, QueryMsg, ReceiveMsg,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::WasmMsg::Execute;
 use cosmwasm_std::{
     from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, SubMsg, Uint128,
+    Order, Response, StdError, StdResult, SubMsg, Uint128,
 };
 use std::ops::Add;
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use stake_dragons_common::migrate::{check_migration, Version};
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
+use stake_dragons_common::random::{
+    fulfil_randomness, request_randomness, validate_source, RandomnessRequest, RandomnessSource,
+};
+use stake_dragons_common::templates::{
+    query_metadata_templates, save_metadata_templates, MetadataTemplate, METADATA_TEMPLATES,
+};
 
 pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Metadata>;
 
 use crate::error::ContractError;
 use crate::helper::{generate_box_mint_msg, generate_crystal_mint_msg};
-use crate::state::{ContractAddressList, State, BOX_COUNT, CONTRACTS, OPENED_BOX_COUNT, STATE};
+use crate::state::{
+    default_drop_table, default_metadata_templates, ContractAddressList, DropWeight, PendingOpen,
+    State, BOX_COUNT, CONTRACTS, CRYSTAL_KINDS, DROP_TABLE, OPENED_BOX_COUNT, OPENED_PER_KIND,
+    PENDING_OPENS, RANDOMNESS_SOURCE, STATE,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:box-minter";
@@ -35,7 +47,6 @@ pub fn instantiate(
         owner: String::from(info.sender.clone()),
         base_price: msg.base_price,
         open_price: msg.open_price,
-        random_key: msg.random_key,
         allowed_cw20: msg.allowed_cw20,
     };
 
@@ -46,8 +57,15 @@ pub fn instantiate(
         juno_recipient: msg.juno_recipient,
     };
 
+    let drop_table = msg.drop_table.unwrap_or_else(default_drop_table);
+    validate_drop_table(&drop_table)?;
+    validate_source(deps.api, &msg.randomness_source)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    DROP_TABLE.save(deps.storage, &drop_table)?;
+    RANDOMNESS_SOURCE.save(deps.storage, &msg.randomness_source)?;
+    save_metadata_templates(deps.storage, &default_metadata_templates())?;
     BOX_COUNT.save(deps.storage, &Uint128::new(0))?;
     OPENED_BOX_COUNT.save(deps.storage, &Uint128::new(0))?;
     CONTRACTS.save(deps.storage, &contracts)?;
@@ -66,21 +84,22 @@ pub fn execute(
         ExecuteMsg::MintBox {} => execute_box_mint(deps, info),
         ExecuteMsg::GenesisMint {} => execute_genesis_box_mint(deps, info),
         ExecuteMsg::OpenBox(msg) => execute_free_hatch(deps, info, msg),
+        ExecuteMsg::FulfilOpen { box_id, secret } => {
+            execute_fulfil_open(deps, _env, box_id, secret)
+        }
         ExecuteMsg::EditState {
-            new_owner,
             base_price,
-            random_key,
             open_price,
             allowed_cw20,
-        } => execute_edit_state(
-            deps,
-            info,
-            new_owner,
-            base_price,
-            random_key,
-            allowed_cw20,
-            open_price,
-        ),
+        } => execute_edit_state(deps, info, base_price, allowed_cw20, open_price),
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, _env, info, msg),
+        ExecuteMsg::EditDropTable { table } => execute_edit_drop_table(deps, info, table),
+        ExecuteMsg::EditMetadataTemplate { template } => {
+            execute_edit_metadata_template(deps, info, template)
+        }
+        ExecuteMsg::EditRandomnessSource { source } => {
+            execute_edit_randomness_source(deps, info, source)
+        }
         ExecuteMsg::EditContracts {
             dragon_box,
             crystal,
@@ -103,19 +122,21 @@ pub fn execute_receive(
     let amount = wrapper.amount;
     let sender = wrapper.sender;
     match msg {
-        ReceiveMsg::Hatch { id, box_id } => {
-            execute_open_cw20(deps, _env, info, sender, id, box_id, amount)
-        }
+        ReceiveMsg::Hatch {
+            id,
+            box_id,
+            commitment,
+        } => execute_open_cw20(deps, info, sender, id, box_id, commitment, amount),
     }
 }
 
 pub fn execute_open_cw20(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
     sender: String,
     id: String,
     box_id: String,
+    commitment: Option<Binary>,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let cfg = STATE.load(deps.storage)?;
@@ -139,58 +160,25 @@ pub fn execute_open_cw20(
         .call(cw20_execute_msg_fp)
         .map_err(ContractError::Std)?;
 
-    //OPEN DRAGON BOX AND MINT CRYSTAL
-    let crystal = contracts.crystal;
-    let dragon_box = contracts.dragon_box;
-
-    //id
-    let id_type = id.clone();
-
-    //crystal type
-    let key1 = cfg.random_key / 10000;
-    let key2 = cfg.random_key % 100;
-    let secret = key1 + key2;
-    let type_id = id_type.chars().nth(secret as usize).unwrap();
-
-    let type_name;
-    match type_id {
-        'G' => type_name = "fire",
-        '2' => type_name = "fire",
-        'N' => type_name = "storm",
-        '5' => type_name = "storm",
-        'V' => type_name = "udin",
-        'S' => type_name = "udin",
-        '7' => type_name = "divine",
-        '1' => type_name = "divine",
-        _ => type_name = "ice",
-    };
-
-    let crystal_mint =
-        generate_crystal_mint_msg(&*box_id.clone(), type_name.to_string(), sender.to_string())?;
-
-    let crystal_mint_msg = CosmosMsg::Wasm(Execute {
-        contract_addr: crystal,
-        msg: to_binary(&crystal_mint)?,
-        funds: vec![],
-    });
+    //OPEN DRAGON BOX
+    let owner = deps.api.addr_validate(&sender)?;
+    let request = request_open(deps, &box_id, owner, commitment)?;
 
     let burn_box = Cw721ExecuteMsg::Burn {
         token_id: box_id.clone(),
     };
 
     let burn_box_msg = CosmosMsg::Wasm(Execute {
-        contract_addr: dragon_box,
+        contract_addr: contracts.dragon_box,
         msg: to_binary(&burn_box)?,
         funds: vec![],
     });
 
-    OPENED_BOX_COUNT.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint128::new(1))))?;
-
-    let res = Response::new().add_submessages(vec![
-        SubMsg::new(fee_payout_msg),
-        SubMsg::new(crystal_mint_msg),
-        SubMsg::new(burn_box_msg),
-    ]);
+    let res = Response::new()
+        .add_submessages(vec![SubMsg::new(fee_payout_msg), SubMsg::new(burn_box_msg)])
+        .add_attribute("id", id)
+        .add_attribute("box_id", box_id)
+        .add_attributes(request.attributes());
 
     Ok(res)
 }
@@ -208,56 +196,105 @@ pub fn execute_free_hatch(
             msg: "free hatch is no longer available".to_string(),
         });
     }
-    let crystal = contracts.crystal;
-    let dragon_box = contracts.dragon_box;
-
-    //id
-    let id_type = msg.clone().id;
-
-    //crystal type
-    let key1 = state.random_key / 10000;
-    let key2 = state.random_key % 100;
-    let secret = key1 + key2;
-    let type_id = id_type.chars().nth(secret as usize).unwrap();
-
-    let type_name;
-    match type_id {
-        'G' => type_name = "fire",
-        '2' => type_name = "fire",
-        'N' => type_name = "storm",
-        '5' => type_name = "storm",
-        'V' => type_name = "udin",
-        'S' => type_name = "udin",
-        '7' => type_name = "divine",
-        '1' => type_name = "divine",
-        _ => type_name = "ice",
-    };
-
-    let crystal_mint = generate_crystal_mint_msg(
-        &*msg.clone().box_id.to_string(),
-        type_name.to_string(),
-        info.sender.to_string(),
-    )?;
+    let request = request_open(deps, &msg.box_id, info.sender.clone(), msg.commitment)?;
 
     let transfer_box = Cw721ExecuteMsg::Burn {
-        token_id: String::from(msg.clone().box_id),
+        token_id: msg.box_id,
     };
 
-    OPENED_BOX_COUNT.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint128::new(1))))?;
-
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(Execute {
-            contract_addr: dragon_box,
+            contract_addr: contracts.dragon_box,
             msg: to_binary(&transfer_box)?,
             funds: vec![],
         }))
+        .add_attribute("box burn", msg.id)
+        .add_attribute("open for", info.sender)
+        .add_attributes(request.attributes()))
+}
+
+/// Commits the opening of `box_id` to a draw that nobody can know yet. The
+/// box is burned right away, so an opening can't be taken back once its
+/// outcome is known.
+fn request_open(
+    deps: DepsMut,
+    box_id: &str,
+    owner: Addr,
+    commitment: Option<Binary>,
+) -> Result<RandomnessRequest, ContractError> {
+    if PENDING_OPENS.has(deps.storage, box_id) {
+        return Err(ContractError::OpenPending {
+            box_id: box_id.to_string(),
+        });
+    }
+    let source = RANDOMNESS_SOURCE.load(deps.storage)?;
+    let request = request_randomness(deps.as_ref(), &source, box_id.as_bytes(), commitment)?;
+    let open = PendingOpen {
+        owner,
+        table: DROP_TABLE.load(deps.storage)?,
+        request: request.clone(),
+    };
+    PENDING_OPENS.save(deps.storage, box_id, &open)?;
+    OPENED_BOX_COUNT.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint128::new(1))))?;
+    Ok(request)
+}
+
+/// Draws the kind of crystal dropped by `box_id` from the drop table of its
+/// request, records it in the per-kind open counts and mints it to the owner
+/// of the opening.
+pub fn execute_fulfil_open(
+    deps: DepsMut,
+    env: Env,
+    box_id: String,
+    secret: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let open = PENDING_OPENS.load(deps.storage, &box_id)?;
+    let randomness = fulfil_randomness(deps.as_ref(), &env, &open.request, secret)?;
+    let (roll, picked) = randomness
+        .pick(&open.table, |w| w.weight)
+        .ok_or(ContractError::InvalidDropTable { total: 0 })?;
+    let kind = picked.kind.clone();
+    OPENED_PER_KIND.update::<_, StdError>(deps.storage, &kind, |opened| {
+        Ok(opened.unwrap_or_default().add(Uint128::new(1)))
+    })?;
+    PENDING_OPENS.remove(deps.storage, &box_id);
+
+    let contracts = CONTRACTS.load(deps.storage)?;
+    let crystal_mint =
+        generate_crystal_mint_msg(deps.storage, &box_id, kind.clone(), open.owner.to_string())?;
+
+    Ok(Response::new()
         .add_message(CosmosMsg::Wasm(Execute {
-            contract_addr: crystal,
+            contract_addr: contracts.crystal,
             msg: to_binary(&crystal_mint)?,
             funds: vec![],
         }))
-        .add_attribute("box burn", msg.id)
-        .add_attribute("mint crystal for", info.sender))
+        .add_attribute("action", "fulfil_open")
+        .add_attribute("box_id", box_id)
+        .add_attribute("mint crystal for", open.owner)
+        .add_attribute("kind", kind)
+        .add_attribute("roll", roll.to_string())
+        .add_attributes(randomness.attributes()))
+}
+
+fn validate_drop_table(table: &[DropWeight]) -> Result<(), ContractError> {
+    for (i, entry) in table.iter().enumerate() {
+        if !CRYSTAL_KINDS.contains(&&*entry.kind) {
+            return Err(ContractError::UnknownKind {
+                kind: entry.kind.clone(),
+            });
+        }
+        if table[..i].iter().any(|w| w.kind == entry.kind) {
+            return Err(ContractError::DuplicateKind {
+                kind: entry.kind.clone(),
+            });
+        }
+    }
+    let total: u64 = table.iter().map(|w| w.weight).sum();
+    if total != 10000 {
+        return Err(ContractError::InvalidDropTable { total });
+    }
+    Ok(())
 }
 
 /// box mint
@@ -337,9 +374,7 @@ pub fn execute_genesis_box_mint(
 pub fn execute_edit_state(
     deps: DepsMut,
     info: MessageInfo,
-    new_owner: String,
     base_price: Uint128,
-    random_key: i32,
     allowed_cw20: Addr,
     open_price: Uint128,
 ) -> Result<Response, ContractError> {
@@ -350,9 +385,8 @@ pub fn execute_edit_state(
         });
     }
     let new = State {
-        owner: new_owner,
+        owner: state.owner,
         base_price,
-        random_key,
         allowed_cw20,
         open_price,
     };
@@ -360,6 +394,84 @@ pub fn execute_edit_state(
     Ok(Response::new())
 }
 
+pub fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
+    STATE.save(deps.storage, &state)?;
+    Ok(res)
+}
+
+pub fn execute_edit_drop_table(
+    deps: DepsMut,
+    info: MessageInfo,
+    table: Vec<DropWeight>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            msg: "drop table can only be edited by the owner".to_string(),
+        });
+    }
+    validate_drop_table(&table)?;
+    DROP_TABLE.save(deps.storage, &table)?;
+
+    let mut res = Response::new().add_attribute("action", "edit_drop_table");
+    for entry in table {
+        res = res.add_attribute(entry.kind, entry.weight.to_string());
+    }
+    Ok(res)
+}
+
+pub fn execute_edit_randomness_source(
+    deps: DepsMut,
+    info: MessageInfo,
+    source: RandomnessSource,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            msg: "randomness source can only be edited by the owner".to_string(),
+        });
+    }
+    validate_source(deps.api, &source)?;
+    RANDOMNESS_SOURCE.save(deps.storage, &source)?;
+    Ok(Response::new().add_attribute("action", "edit_randomness_source"))
+}
+
+pub fn execute_edit_metadata_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    template: MetadataTemplate,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            msg: "metadata templates can only be edited by the owner".to_string(),
+        });
+    }
+    let kind = template.kind.to_lowercase();
+    if !CRYSTAL_KINDS.contains(&&*kind) {
+        return Err(ContractError::UnknownKind { kind });
+    }
+    save_metadata_templates(deps.storage, &[template])?;
+    Ok(Response::new()
+        .add_attribute("action", "edit_metadata_template")
+        .add_attribute("kind", kind))
+}
+
 pub fn execute_edit_contracts(
     deps: DepsMut,
     info: MessageInfo,
@@ -385,11 +497,57 @@ pub fn execute_edit_contracts(
     Ok(Response::new())
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let from = check_migration(
+        &stored.contract,
+        &stored.version,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+    if from < Version::new(0, 2, 0) {
+        migrate_v0_2(deps.branch(), msg.randomness_source)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// 0.2.0 dropped `random_key` from `State` and draws crystals from a stored
+/// drop table and a randomness beacon, minting them from stored metadata
+/// templates.
+fn migrate_v0_2(deps: DepsMut, source: Option<RandomnessSource>) -> Result<(), ContractError> {
+    let storage = deps.storage;
+    let state = STATE.load(storage)?;
+    STATE.save(storage, &state)?;
+    if DROP_TABLE.may_load(storage)?.is_none() {
+        DROP_TABLE.save(storage, &default_drop_table())?;
+    }
+    if let Some(source) = source {
+        validate_source(deps.api, &source)?;
+        RANDOMNESS_SOURCE.save(storage, &source)?;
+    } else if RANDOMNESS_SOURCE.may_load(storage)?.is_none() {
+        return Err(ContractError::MissingRandomnessSource {});
+    }
+    let templates = METADATA_TEMPLATES.keys(storage, None, None, Order::Ascending);
+    if templates.count() == 0 {
+        save_metadata_templates(storage, &default_metadata_templates())?;
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetState {} => to_binary(&query_state(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
         QueryMsg::GetBoxListInfo {} => to_binary(&query_box_list(deps)?),
+        QueryMsg::DropTable {} => to_binary(&query_drop_table(deps)?),
+        QueryMsg::MetadataTemplates {} => to_binary(&query_metadata_templates(deps.storage)?),
+        QueryMsg::PendingOpen { box_id } => to_binary(&PENDING_OPENS.load(deps.storage, &box_id)?),
     }
 }
 
@@ -400,7 +558,6 @@ fn query_state(deps: Deps) -> StdResult<GetStateResponse> {
         owner: state.owner,
         base_price: state.base_price,
         open_price: state.open_price,
-        random_key: state.random_key,
         allowed_cw20: state.allowed_cw20,
         dragon_box: contract.dragon_box,
         crystal: contract.crystal,
@@ -421,3 +578,31 @@ fn query_box_list(deps: Deps) -> StdResult<GetBoxResponse> {
         open_price: state.open_price,
     })
 }
+
+fn query_drop_table(deps: Deps) -> StdResult<DropTableResponse> {
+    let table = DROP_TABLE.load(deps.storage)?;
+    let entries = CRYSTAL_KINDS
+        .iter()
+        .map(|kind| {
+            let weight = table
+                .iter()
+                .find(|entry| entry.kind == *kind)
+                .map(|entry| entry.weight)
+                .unwrap_or_default();
+            let opened = OPENED_PER_KIND
+                .may_load(deps.storage, kind)?
+                .unwrap_or_default();
+            Ok(DropTableEntry {
+                kind: kind.to_string(),
+                weight,
+                opened,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(DropTableResponse {
+        entries,
+        total_opened: OPENED_BOX_COUNT.load(deps.storage)?,
+        source: RANDOMNESS_SOURCE.load(deps.storage)?,
+    })
+}
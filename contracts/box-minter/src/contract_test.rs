@@ -0,0 +1,380 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{execute, instantiate, migrate, query};
+    use crate::msg::{
+        DropTableResponse, ExecuteMsg, GetStateResponse, InstantiateMsg, MigrateMsg,
+        MintBoxCrystal, QueryMsg,
+    };
+    use crate::state::{
+        default_drop_table, DropWeight, PendingOpen, DROP_TABLE, RANDOMNESS_SOURCE, STATE,
+    };
+    use crate::ContractError;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+    use cosmwasm_std::{
+        from_binary, Addr, DepsMut, Env, MemoryStorage, MessageInfo, OwnedDeps, Response, Uint128,
+    };
+    use cw2::set_contract_version;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
+    use serde::{Deserialize, Serialize};
+    use stake_dragons_common::random::mock::mock_beacon;
+    use stake_dragons_common::random::{RandomnessError, RandomnessSource};
+
+    fn instantiate_msg(table: Option<Vec<DropWeight>>) -> InstantiateMsg {
+        InstantiateMsg {
+            owner: "owner".to_string(),
+            base_price: Uint128::new(1),
+            open_price: Uint128::new(1),
+            allowed_cw20: Addr::unchecked("drgn"),
+            multisig: "multisig".to_string(),
+            juno_recipient: "recipient".to_string(),
+            drop_table: table,
+            randomness_source: beacon(),
+        }
+    }
+
+    fn beacon() -> RandomnessSource {
+        RandomnessSource::Beacon {
+            contract: "beacon".to_string(),
+        }
+    }
+
+    fn setup(table: Option<Vec<DropWeight>>) -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier
+            .update_wasm(mock_beacon("beacon", 1, vec![3u8; 32]));
+        let msg = instantiate_msg(table);
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let msg = ExecuteMsg::EditContracts {
+            dragon_box: "box".to_string(),
+            crystal: "crystal".to_string(),
+            multisig: "multisig".to_string(),
+            juno_recipient: "recipient".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        deps
+    }
+
+    fn open(id: &str, box_id: &str) -> ExecuteMsg {
+        ExecuteMsg::OpenBox(MintBoxCrystal {
+            id: id.to_string(),
+            box_id: box_id.to_string(),
+            commitment: None,
+        })
+    }
+
+    /// Publishes the round the opening of `box_id` waits for and fulfils it.
+    fn fulfil(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, box_id: &str) -> Response {
+        let msg = QueryMsg::PendingOpen {
+            box_id: box_id.to_string(),
+        };
+        let open: PendingOpen =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        deps.querier
+            .update_wasm(mock_beacon("beacon", open.request.round, vec![3u8; 32]));
+        let msg = ExecuteMsg::FulfilOpen {
+            box_id: box_id.to_string(),
+            secret: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap()
+    }
+
+    fn open_and_fulfil(
+        deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        box_id: &str,
+    ) -> Response {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            open("any", box_id),
+        )
+        .unwrap();
+        fulfil(deps, box_id)
+    }
+
+    fn dropped_kind(res: &Response) -> String {
+        res.attributes
+            .iter()
+            .find(|a| a.key == "kind")
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    fn only(kind: &str) -> Vec<DropWeight> {
+        vec![DropWeight {
+            kind: kind.to_string(),
+            weight: 10000,
+        }]
+    }
+
+    #[test]
+    fn open_ignores_caller_id() {
+        let mut deps = setup(Some(only("udin")));
+        for id in ["GGGGGGGGGG", "NNNNNNNNNN", "0000000000"] {
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("owner", &[]),
+                open(id, "1"),
+            )
+            .unwrap();
+            assert_eq!(res.messages.len(), 1);
+            let res = fulfil(&mut deps, "1");
+            assert_eq!(dropped_kind(&res), "udin");
+            assert_eq!(res.messages.len(), 1);
+        }
+    }
+
+    #[test]
+    fn open_keeps_the_drop_table_it_was_requested_with() {
+        let mut deps = setup(Some(only("udin")));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            open("any", "1"),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::EditDropTable {
+            table: only("fire"),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert_eq!(dropped_kind(&fulfil(&mut deps, "1")), "udin");
+        assert_eq!(dropped_kind(&open_and_fulfil(&mut deps, "2")), "fire");
+    }
+
+    #[test]
+    fn drop_table_is_validated() {
+        let mut deps = setup(None);
+        let msg = ExecuteMsg::EditDropTable {
+            table: only("storm"),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        let bad = ExecuteMsg::EditDropTable {
+            table: vec![DropWeight {
+                kind: "storm".to_string(),
+                weight: 10001,
+            }],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), bad).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InvalidDropTable { total: 10001 }
+        ));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::EditDropTable {
+                table: only("earth"),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UnknownKind { .. }));
+
+        let duplicate = ExecuteMsg::EditDropTable {
+            table: vec![
+                DropWeight {
+                    kind: "udin".to_string(),
+                    weight: 1,
+                },
+                DropWeight {
+                    kind: "ice".to_string(),
+                    weight: 9998,
+                },
+                DropWeight {
+                    kind: "udin".to_string(),
+                    weight: 1,
+                },
+            ],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            duplicate,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateKind { kind } if kind == "udin"));
+
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let res = open_and_fulfil(&mut deps, "2");
+        assert_eq!(dropped_kind(&res), "storm");
+    }
+
+    #[test]
+    fn drop_table_counts_opened_boxes() {
+        let mut deps = setup(Some(only("fire")));
+        for box_id in ["1", "2", "3"] {
+            open_and_fulfil(&mut deps, box_id);
+        }
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::DropTable {}).unwrap();
+        let res: DropTableResponse = from_binary(&res).unwrap();
+        assert_eq!(res.total_opened, Uint128::new(3));
+        assert_eq!(res.source, beacon());
+        for entry in res.entries {
+            if entry.kind == "fire" {
+                assert_eq!(entry.weight, 10000);
+                assert_eq!(entry.opened, Uint128::new(3));
+            } else {
+                assert_eq!(entry.weight, 0);
+                assert_eq!(entry.opened, Uint128::zero());
+            }
+        }
+    }
+
+    #[test]
+    fn open_waits_for_the_next_round() {
+        let mut deps = setup(None);
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            open("any", "4"),
+        )
+        .unwrap();
+        let round = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "randomness_round")
+            .unwrap();
+        assert_eq!(round.value, "2");
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            open("any", "4"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OpenPending { .. }));
+
+        let msg = ExecuteMsg::FulfilOpen {
+            box_id: "4".to_string(),
+            secret: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Randomness(RandomnessError::RoundNotPublished { round: 2 })
+        ));
+        let res = fulfil(&mut deps, "4");
+        let round = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "randomness_round")
+            .unwrap();
+        assert_eq!(round.value, "2");
+    }
+
+    /// `State` as stored by 0.1.0, which still had the open `random_key`.
+    #[derive(Serialize, Deserialize)]
+    struct LegacyState {
+        owner: String,
+        base_price: Uint128,
+        open_price: Uint128,
+        random_key: i32,
+        allowed_cw20: Addr,
+    }
+
+    fn instantiate_v0_1(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> Result<Response, ContractError> {
+        let res = instantiate(deps.branch(), env, info, msg)?;
+        let state = STATE.load(deps.storage)?;
+        let legacy = LegacyState {
+            owner: state.owner,
+            base_price: state.base_price,
+            open_price: state.open_price,
+            random_key: 7,
+            allowed_cw20: state.allowed_cw20,
+        };
+        Item::new("state").save(deps.storage, &legacy)?;
+        DROP_TABLE.remove(deps.storage);
+        RANDOMNESS_SOURCE.remove(deps.storage);
+        set_contract_version(deps.storage, "crates.io:box-minter", "0.1.0")?;
+        Ok(res)
+    }
+
+    #[test]
+    fn migrate_upgrades_v0_1_layout() {
+        let mut app = App::default();
+        let legacy = app.store_code(Box::new(ContractWrapper::new(
+            execute,
+            instantiate_v0_1,
+            query,
+        )));
+        let current = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+        ));
+        let admin = Addr::unchecked("admin");
+        let addr = app
+            .instantiate_contract(
+                legacy,
+                admin.clone(),
+                &instantiate_msg(None),
+                &[],
+                "box-minter",
+                Some(admin.to_string()),
+            )
+            .unwrap();
+        let err = app
+            .migrate_contract(
+                admin.clone(),
+                addr.clone(),
+                &MigrateMsg {
+                    randomness_source: None,
+                },
+                current,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap().to_string(),
+            ContractError::MissingRandomnessSource {}.to_string()
+        );
+        let msg = MigrateMsg {
+            randomness_source: Some(beacon()),
+        };
+        app.migrate_contract(admin, addr.clone(), &msg, current)
+            .unwrap();
+
+        let state: GetStateResponse = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::GetState {})
+            .unwrap();
+        assert_eq!(state.owner, "admin");
+        let table: DropTableResponse = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::DropTable {})
+            .unwrap();
+        let weights: Vec<_> = table.entries.iter().map(|e| e.weight).collect();
+        let defaults: Vec<_> = default_drop_table().iter().map(|w| w.weight).collect();
+        assert_eq!(weights, defaults);
+        assert_eq!(table.source, beacon());
+    }
+
+    #[test]
+    fn migrate_refuses_downgrade() {
+        let mut deps = setup(None);
+        set_contract_version(deps.as_mut().storage, "crates.io:box-minter", "1.0.0").unwrap();
+        let msg = MigrateMsg {
+            randomness_source: None,
+        };
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Migrate(_)));
+    }
+}
@@ -1,68 +1,37 @@
-use cosmwasm_std::{Addr, Uint128};
+use crate::state::DropWeight;
+use cosmwasm_std::{Addr, Binary, Uint128};
 use cw20::Cw20ReceiveMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::ownership::OwnershipMsg;
+use stake_dragons_common::random::RandomnessSource;
+use stake_dragons_common::templates::MetadataTemplate;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, MintExecuteMsg, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub owner: String,
     pub base_price: Uint128,
     pub open_price: Uint128,
-    pub random_key: i32,
     pub allowed_cw20: Addr,
     pub multisig: String,
     pub juno_recipient: String,
-}
-
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-//Change some to mandatory some to optional
-// see: https://docs.opensea.io/docs/metadata-standards
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub external_url: Option<String>,
-    pub attributes: Vec<Trait>,
-    pub image_data: Option<String>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct BaseMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct NftMintMsg {
-    pub mint: BaseMintMsg,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct ExtensionMintMsg {
-    pub mint: ExtMintMsg,
+    pub drop_table: Option<Vec<DropWeight>>,
+    pub randomness_source: RandomnessSource,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct ExtMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub extension: Vec<Trait>,
+pub struct MigrateMsg {
+    /// Required when migrating from a version that stored no source.
+    pub randomness_source: Option<RandomnessSource>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintBoxCrystal {
     pub id: String,
     pub box_id: String,
+    /// sha256 of a secret to reveal in `FulfilOpen`.
+    pub commitment: Option<Binary>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -77,13 +46,28 @@ pub enum ExecuteMsg {
     MintBox {},
     GenesisMint {},
     OpenBox(MintBoxCrystal),
+    /// Mints the crystal of an opened box once the beacon round it waits for
+    /// is published. Anyone can fulfil an opening made without a commitment.
+    FulfilOpen {
+        box_id: String,
+        secret: Option<Binary>,
+    },
     EditState {
-        new_owner: String,
         base_price: Uint128,
-        random_key: i32,
         open_price: Uint128,
         allowed_cw20: Addr,
     },
+    UpdateOwnership(OwnershipMsg),
+    EditDropTable {
+        table: Vec<DropWeight>,
+    },
+    EditRandomnessSource {
+        source: RandomnessSource,
+    },
+    /// Adds the template of a kind or replaces the one it had.
+    EditMetadataTemplate {
+        template: MetadataTemplate,
+    },
     EditContracts {
         dragon_box: String,
         crystal: String,
@@ -96,7 +80,11 @@ pub enum ExecuteMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReceiveMsg {
-    Hatch { id: String, box_id: String },
+    Hatch {
+        id: String,
+        box_id: String,
+        commitment: Option<Binary>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -104,7 +92,11 @@ pub enum ReceiveMsg {
 pub enum QueryMsg {
     // GetCount returns the current count as a json-encoded number
     GetState {},
+    PendingOwner {},
     GetBoxListInfo {},
+    DropTable {},
+    MetadataTemplates {},
+    PendingOpen { box_id: String },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -112,7 +104,6 @@ pub struct GetStateResponse {
     pub owner: String,
     pub base_price: Uint128,
     pub open_price: Uint128,
-    pub random_key: i32,
     pub allowed_cw20: Addr,
     pub dragon_box: String,
     pub crystal: String,
@@ -127,3 +118,18 @@ pub struct GetBoxResponse {
     pub base_price: Uint128,
     pub open_price: Uint128,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DropTableEntry {
+    pub kind: String,
+    /// basis points of 10000
+    pub weight: u64,
+    pub opened: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DropTableResponse {
+    pub entries: Vec<DropTableEntry>,
+    pub total_opened: Uint128,
+    pub source: RandomnessSource,
+}
@@ -2,14 +2,15 @@ use cosmwasm_std::{Addr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
+use stake_dragons_common::random::{RandomnessRequest, RandomnessSource};
+use stake_dragons_common::templates::MetadataTemplate;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub owner: String,
     pub base_price: Uint128,
     pub open_price: Uint128,
-    pub random_key: i32,
     pub allowed_cw20: Addr,
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -24,3 +25,61 @@ pub const STATE: Item<State> = Item::new("state");
 pub const CONTRACTS: Item<ContractAddressList> = Item::new("contract_list");
 pub const BOX_COUNT: Item<Uint128> = Item::new("box_count");
 pub const OPENED_BOX_COUNT: Item<Uint128> = Item::new("opened_box_count");
+
+/// Chance of a box dropping a given crystal kind, in basis points of 10000.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DropWeight {
+    pub kind: String,
+    pub weight: u64,
+}
+
+pub const DROP_TABLE: Item<Vec<DropWeight>> = Item::new("drop_table");
+pub const OPENED_PER_KIND: Map<&str, Uint128> = Map::new("opened_per_kind");
+pub const RANDOMNESS_SOURCE: Item<RandomnessSource> = Item::new("randomness_source");
+
+/// Box burned for an opening whose crystal is minted once its draw is
+/// fulfilled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOpen {
+    pub owner: Addr,
+    /// drop table at the time of the request
+    pub table: Vec<DropWeight>,
+    pub request: RandomnessRequest,
+}
+
+pub const PENDING_OPENS: Map<&str, PendingOpen> = Map::new("pending_opens");
+
+pub const CRYSTAL_KINDS: [&str; 5] = ["fire", "ice", "storm", "divine", "udin"];
+
+/// Same odds as the legacy character table: 28 of 36 characters dropped an
+/// ice crystal and each other kind had 2 characters.
+pub fn default_drop_table() -> Vec<DropWeight> {
+    [
+        ("fire", 555),
+        ("ice", 7778),
+        ("storm", 555),
+        ("divine", 556),
+        ("udin", 556),
+    ]
+    .iter()
+    .map(|(kind, weight)| DropWeight {
+        kind: kind.to_string(),
+        weight: *weight,
+    })
+    .collect()
+}
+
+/// The metadata the minter gave each crystal before templates were stored.
+pub fn default_metadata_templates() -> Vec<MetadataTemplate> {
+    CRYSTAL_KINDS
+        .iter()
+        .map(|kind| MetadataTemplate {
+            kind: kind.to_string(),
+            name: format!("{}_crystal", kind),
+            description: String::new(),
+            image_base_uri: String::new(),
+            image: None,
+            traits: vec![],
+        })
+        .collect()
+}
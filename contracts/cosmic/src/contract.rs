@@ -1,25 +1,51 @@
 use crate::error::ContractError;
 use crate::msg::{
-    CollectionInfoResponse, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, QueryMsg, ClaimMessage, Claim, StateResponse
+    CollectionInfoResponse, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, MigrateMsg, QueryMsg, ClaimMessage, Claim, StateResponse,
+    Trait,
 };
 use crate::state::{
-    CollectionInfo, State, Cosmic, CosmicListResponse, CosmicResponse, COLLECTION_INFO, COSMIC_INFO,
-    COSMIC_INFO_SEQ, STATE, MIN_STAKE_TIME,
+    CollectionInfo, State, Cosmic, CosmicInfoListResponse, CosmicListResponse, CosmicResponse,
+    CosmicRewardResponse, UserCosmicResponse, BURNED_COSMICS, COLLECTION_INFO, cosmic_info,
+    COSMIC_INFO_SEQ, COSMIC_KIND, STATE, MIN_STAKE_TIME,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::WasmMsg::Execute;
-use cosmwasm_std::{to_binary, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Uint64, Uint128, SubMsg};
-use cw2::set_contract_version;
+use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint64, Uint128, SubMsg};
+use cw2::{get_contract_version, set_contract_version};
+use cw721::{AllNftInfoResponse, Cw721Query, NftInfoResponse};
+use std::collections::BTreeMap;
 use std::ops::Add;
+use std::str::FromStr;
 
 pub type Cw721Contract<'a> = cw721_base::Cw721Contract<'a, Extension, Empty>;
 pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
 use cw_storage_plus::Bound;
 use cw_utils::Expiration;
+use stake_dragons_common::batch::check_batch;
+use stake_dragons_common::burns::{query_burned_tokens, BurnReason, BurnedToken};
+use stake_dragons_common::claims::{
+    can_claim, query_claim_settings, reward_recipient, update_claim_settings,
+};
+use stake_dragons_common::migrate::{
+    check_renamed_migration, ensure_migrated, save_migration_cursor, Version,
+    DEFAULT_MIGRATION_LIMIT, MIGRATION_CURSOR,
+};
+use stake_dragons_common::nft::{update_metadata, with_live_traits, MetadataUpdate};
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
+use stake_dragons_common::supply::{check_supply, query_supply, update_supply, SupplyMsg};
+use stake_dragons_common::reward::{
+    accrued_reward, current_daily_income, query_emission_rates, query_rate_history,
+    save_emission_rates, EmissionRate, TOKEN_UNIT,
+};
+use stake_dragons_common::stats::{
+    query_staking_stats, record_burn, record_claim, record_mint, record_status_change, StakeStatus,
+};
 
 // version info for migration info
-const CONTRACT_NAME: &str = "crates.io:dragon-mint";
+const CONTRACT_NAME: &str = "crates.io:cosmic";
+// 0.1.0 was deployed under the dragon contract's cw2 name
+const LEGACY_CONTRACT_NAME: &str = "crates.io:dragon-mint";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -63,20 +89,54 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    ensure_migrated(deps.storage)?;
     match msg {
-        ExecuteMsg::UpdateOwner {new_owner} => execute_update_owner(deps, info, new_owner),
-        ExecuteMsg::UpdateDailyIncome {new_daily_income} => execute_update_daily_income(deps, info, new_daily_income),
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, env, info, msg),
+        ExecuteMsg::UpdateSupply(msg) => execute_update_supply(deps, info, msg),
+        ExecuteMsg::UpdateClaimSettings(msg) => {
+            Ok(update_claim_settings(deps.storage, deps.api, &info.sender, msg)?)
+        }
+        ExecuteMsg::UpdateDailyIncome { new_daily_income } => {
+            execute_update_daily_income(deps, env, info, new_daily_income)
+        }
         ExecuteMsg::UpdateRewardContractAddress {new_address} => execute_update_reward_contract_address(deps, info, new_address),
         ExecuteMsg::UpdateMinStakeTime {time} => execute_update_min_stake_time(deps, info, time),
+        ExecuteMsg::UpdateEmissionRates { rates } => {
+            execute_update_emission_rates(deps, env, info, rates)
+        }
         ExecuteMsg::Mint(msg) => execute_mint(deps, env, info, msg),
         ExecuteMsg::StakeCosmic { token_id } => execute_stake_cosmic(deps, info, env, token_id),
         ExecuteMsg::StartUnstakingProcess { token_id } => {
             execute_start_unstake_process(deps, info, env, token_id)
         }
         ExecuteMsg::UnstakeCosmic { token_id } => execute_unstake_cosmic(deps, info, env, token_id),
-        ExecuteMsg::ClaimReward { token_id } => execute_claim_reward(deps, info, env, token_id),
-        ExecuteMsg::Claim { token_id } => execute_claim(deps, info, env, token_id),
-        ExecuteMsg::Burn { token_id } => execute_burn(deps, env, info, token_id),
+        ExecuteMsg::CancelUnstaking { token_id } => {
+            execute_cancel_unstaking(deps, info, env, token_id)
+        }
+        ExecuteMsg::ClaimReward { token_id } | ExecuteMsg::Claim { token_id } => {
+            execute_claim_reward(deps, info, env, token_id)
+        }
+        ExecuteMsg::BatchStakeCosmic { token_ids } => {
+            execute_batch(deps, info, env, token_ids, "batch_stake_cosmic", stake_cosmic)
+        }
+        ExecuteMsg::BatchStartUnstakingProcess { token_ids } => execute_batch(
+            deps,
+            info,
+            env,
+            token_ids,
+            "batch_start_unstaking_process",
+            start_unstake_process,
+        ),
+        ExecuteMsg::BatchUnstakeCosmic { token_ids } => {
+            execute_batch(deps, info, env, token_ids, "batch_unstake_cosmic", unstake_cosmic)
+        }
+        ExecuteMsg::BatchClaimReward { token_ids } => {
+            execute_batch_claim_reward(deps, info, env, token_ids)
+        }
+        ExecuteMsg::Burn { token_id, reason } => execute_burn(deps, env, info, token_id, reason),
+        ExecuteMsg::UpdateMetadata { token_id, metadata } => {
+            execute_update_metadata(deps, info, token_id, metadata)
+        }
         ExecuteMsg::TransferNft {
             recipient,
             token_id,
@@ -101,22 +161,48 @@ pub fn execute(
     }
 }
 
-fn execute_update_owner(
+fn execute_update_supply(
     deps: DepsMut,
     info: MessageInfo,
-    new_owner: String,
+    msg: SupplyMsg,
 ) -> Result<Response, ContractError> {
-    let mut state = STATE.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
     if info.sender.to_string() != state.owner {
         return Err(ContractError::Unauthorized {});
     }
-    state.owner = new_owner;
+    let mut collection_info = COLLECTION_INFO.load(deps.storage)?;
+    collection_info.size = update_supply(deps.storage, collection_info.size, msg)?;
+    COLLECTION_INFO.save(deps.storage, &collection_info)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_supply")
+        .add_attribute("size", collection_info.size))
+}
+
+fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
     STATE.save(deps.storage, &state)?;
-    Ok(Response::default().add_attribute("new_owner", state.owner))
+    Ok(res)
 }
 
+/// Sets the income of new cosmics and, from now on, the rate every cosmic
+/// earns. Rewards earned before keep their old rate.
 fn execute_update_daily_income(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     new_daily_income: Uint64,
 ) -> Result<Response, ContractError> {
@@ -126,9 +212,32 @@ fn execute_update_daily_income(
     }
     state.daily_income = new_daily_income;
     STATE.save(deps.storage, &state)?;
+    let rate = EmissionRate {
+        kind: COSMIC_KIND.to_string(),
+        per_day: Uint128::from(new_daily_income).checked_mul(Uint128::new(TOKEN_UNIT))?,
+    };
+    save_emission_rates(deps.storage, &[rate], env.block.time.seconds())?;
     Ok(Response::default().add_attribute("new_owner", state.owner))
 }
 
+fn execute_update_emission_rates(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rates: Vec<EmissionRate>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender.to_string() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    save_emission_rates(deps.storage, &rates, env.block.time.seconds())?;
+    let mut res = Response::default().add_attribute("action", "update_emission_rates");
+    for rate in rates {
+        res = res.add_attribute(rate.kind, rate.per_day);
+    }
+    Ok(res)
+}
+
 fn execute_update_reward_contract_address(
     deps: DepsMut,
     info: MessageInfo,
@@ -157,18 +266,55 @@ fn execute_update_min_stake_time(
     Ok(Response::default().add_attribute("min_stake_time", time))
 }
 
+fn execute_update_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    metadata: MetadataUpdate,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender.to_string() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    update_metadata(deps.storage, &token_id, metadata)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_metadata")
+        .add_attribute("token_id", token_id))
+}
+
 //Add burning for your local list here
 fn execute_burn(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     token_id: String,
+    reason: Option<BurnReason>,
 ) -> Result<Response, ContractError> {
+    let id = token_id
+        .parse::<u64>()
+        .map_err(|err| StdError::parse_err("u64", err))?;
+    let cosmic = cosmic_info().load(deps.storage, id)?;
+    if cosmic.is_staked {
+        return Err(ContractError::StakedCosmicCantBeBurned {});
+    }
+    let reason = reason.unwrap_or(BurnReason::User);
+    if reason != BurnReason::User
+        && info.sender != Cw721Contract::default().minter.load(deps.storage)?
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    let burned_at = Uint64::new(env.block.time.seconds());
     let msg = Cw721ExecuteMsg::Burn { token_id };
-    Cw721Contract::default()
-        .execute(deps, env, info, msg)
-        .unwrap();
-    Ok(Response::new())
+    let res = Cw721Contract::default().execute(deps.branch(), env, info, msg)?;
+    cosmic_info().remove(deps.storage, id)?;
+    let burned = BurnedToken {
+        token: cosmic,
+        reason,
+        burned_at,
+    };
+    BURNED_COSMICS.save(deps.storage, id, &burned)?;
+    record_burn(deps.storage)?;
+    Ok(res)
 }
 
 fn execute_mint(
@@ -179,6 +325,8 @@ fn execute_mint(
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
     let daily_income: String = state.daily_income.to_string();
+    let cap = COLLECTION_INFO.load(deps.storage)?.size;
+    check_supply(COSMIC_INFO_SEQ.load(deps.storage)?, cap)?;
     let id =
         COSMIC_INFO_SEQ.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint64::new(1))))?;
     let cosmic = Cosmic {
@@ -191,8 +339,10 @@ fn execute_mint(
         unstaking_start_time: Uint64::zero(),
         unstaking_process: false,
         reward_end_time: Uint64::zero(),
+        carried_reward: Uint128::zero(),
     };
-    COSMIC_INFO.save(deps.storage, id.u64(), &cosmic)?;
+    cosmic_info().save(deps.storage, id.u64(), &cosmic)?;
+    record_mint(deps.storage)?;
     msg.base.token_id = id.to_string();
     let mint_msg = Cw721ExecuteMsg::Mint(msg.base.clone());
     Cw721Contract::default()
@@ -218,23 +368,36 @@ fn execute_transfer_nft(
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
     let msg = Cw721ExecuteMsg::TransferNft {
-        recipient: recipient.clone(),
+        recipient,
         token_id: token_id.to_string(),
     };
-    let mut cosmic = COSMIC_INFO.load(deps.storage, token_id.u64())?;
-    let is_staked = cosmic.clone().is_staked;
-    if is_staked {
+    move_cosmic(deps, env, info, token_id.u64(), msg)
+}
+
+/// Runs a cw721 transfer or send, which checks that the sender owns the
+/// token or is approved for it, then copies the new cw721 owner into the
+/// cosmic record. Staked cosmics can't be moved.
+fn move_cosmic(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    msg: Cw721ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let mut cosmic = cosmic_info().load(deps.storage, id)?;
+    if cosmic.is_staked {
         return Err(ContractError::StakedCosmicCantBeTransferred {});
     }
-    let valid_recipient = deps.api.addr_validate(&*recipient)?;
-    cosmic.owner = valid_recipient.to_string();
-    COSMIC_INFO.save(deps.storage, token_id.u64(), &cosmic)?;
-    Cw721Contract::default()
-        .execute(deps, env, info.clone(), msg)
-        .unwrap();
-
-    Ok(Response::default()
-        .add_attribute("old owner", info.sender.to_string())
+    let old_owner = cosmic.owner.clone();
+    let res = Cw721Contract::default().execute(deps.branch(), env, info, msg)?;
+    cosmic.owner = Cw721Contract::default()
+        .tokens
+        .load(deps.storage, &cosmic.token_id)?
+        .owner
+        .into_string();
+    cosmic_info().save(deps.storage, id, &cosmic)?;
+    Ok(res
+        .add_attribute("old owner", old_owner)
         .add_attribute("new owner", cosmic.owner))
 }
 
@@ -244,21 +407,32 @@ pub fn execute_stake_cosmic(
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut cosmic = COSMIC_INFO.load(deps.storage, token_id.u64())?;
+    let mut cosmic = cosmic_info().load(deps.storage, token_id.u64())?;
+    let was = cosmic.stake_status();
+    stake_cosmic(deps.storage, &mut cosmic, &info.sender, &env)?;
+    save_cosmic(deps.storage, token_id.u64(), was, &cosmic)?;
+    Ok(Response::default()
+        .add_attribute("token_id", cosmic.token_id.to_string())
+        .add_attribute("is_staked", cosmic.is_staked.to_string())
+        .add_attribute("start_time", cosmic.stake_start_time)
+        .add_attribute("reward_start_time", cosmic.reward_start_time))
+}
+
+fn stake_cosmic(
+    _storage: &dyn Storage,
+    cosmic: &mut Cosmic,
+    sender: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
     if cosmic.is_staked {
         return Err(ContractError::CosmicAlreadyStaked {});
     }
-    let _is_owner = cosmic.clone().is_owner(info.sender.to_string())?;
+    let _is_owner = cosmic.clone().is_owner(sender.to_string())?;
     cosmic.is_staked = true;
     let now = Uint64::new(env.block.time.seconds());
     cosmic.stake_start_time = now;
     cosmic.reward_start_time = now;
-    COSMIC_INFO.save(deps.storage, token_id.u64(), &cosmic)?;
-    Ok(Response::default()
-        .add_attribute("token_id", cosmic.token_id.to_string())
-        .add_attribute("is_staked", cosmic.is_staked.to_string())
-        .add_attribute("start_time", cosmic.stake_start_time)
-        .add_attribute("reward_start_time", cosmic.reward_start_time))
+    Ok(())
 }
 
 fn execute_start_unstake_process(
@@ -267,55 +441,87 @@ fn execute_start_unstake_process(
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut cosmic = COSMIC_INFO.load(deps.storage, token_id.u64())?;
+    let mut cosmic = cosmic_info().load(deps.storage, token_id.u64())?;
+    let was = cosmic.stake_status();
+    start_unstake_process(deps.storage, &mut cosmic, &info.sender, &env)?;
+    save_cosmic(deps.storage, token_id.u64(), was, &cosmic)?;
+    Ok(Response::default()
+        .add_attribute("token_id", cosmic.clone().token_id.to_string())
+        .add_attribute("unstaking_start_time", cosmic.unstaking_start_time)
+        .add_attribute("reward_end_time", cosmic.reward_end_time))
+}
+
+fn start_unstake_process(
+    _storage: &dyn Storage,
+    cosmic: &mut Cosmic,
+    sender: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
     if cosmic.unstaking_process {
         return Err(ContractError::OngoingUnstakingProcess {});
     }
     if !cosmic.is_staked {
         return Err(ContractError::CosmicNotStaked {});
     }
-    if cosmic.owner != info.sender.to_string() {
+    if cosmic.owner != sender.to_string() {
         return Err(ContractError::Unauthorized {});
     }
     cosmic.unstaking_process = true;
     cosmic.unstaking_start_time = Uint64::new(env.block.time.seconds());
     cosmic.reward_end_time = Uint64::new(env.block.time.seconds());
-    COSMIC_INFO.save(deps.storage, token_id.u64(), &cosmic)?;
-    Ok(Response::default()
-        .add_attribute("token_id", cosmic.clone().token_id.to_string())
-        .add_attribute("unstaking_start_time", cosmic.unstaking_start_time)
-        .add_attribute("reward_end_time", cosmic.reward_end_time))
+    Ok(())
 }
 
-fn execute_unstake_cosmic(
+fn execute_cancel_unstaking(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut cosmic = COSMIC_INFO.load(deps.storage, token_id.u64())?;
-    if !cosmic.is_staked {
-        return Err(ContractError::CosmicNotStaked {});
-    }
-    if cosmic.owner != info.sender.to_string() {
+    let mut cosmic = cosmic_info().load(deps.storage, token_id.u64())?;
+    let was = cosmic.stake_status();
+    cancel_unstaking(deps.storage, &mut cosmic, &info.sender, &env)?;
+    save_cosmic(deps.storage, token_id.u64(), was, &cosmic)?;
+    Ok(Response::default()
+        .add_attribute("action", "cancel_unstaking")
+        .add_attribute("token_id", cosmic.token_id.clone())
+        .add_attribute("unstaking_process", cosmic.unstaking_process.to_string())
+        .add_attribute("reward_start_time", cosmic.reward_start_time))
+}
+
+/// Clears the unstaking process of `cosmic`. What it earned so far is carried over
+/// and accrual restarts now, so later rate changes can't touch it.
+fn cancel_unstaking(
+    storage: &dyn Storage,
+    cosmic: &mut Cosmic,
+    sender: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
+    if cosmic.owner != sender.to_string() {
         return Err(ContractError::Unauthorized {});
     }
     if !cosmic.unstaking_process {
         return Err(ContractError::UnstakingProcessIsNotStarted {});
     }
-    let now = Uint64::new(env.block.time.seconds());
-    let min_stake_time = MIN_STAKE_TIME.load(deps.storage)?;
-    if now.checked_sub(cosmic.unstaking_start_time)? < min_stake_time
-        || cosmic.unstaking_start_time.is_zero()
-    {
-        return Err(ContractError::MinUnstakingTimeRequired {});
-    }
-    cosmic.is_staked = false;
-    cosmic.stake_start_time = Uint64::zero();
-    cosmic.reward_start_time = Uint64::zero();
+    let now = env.block.time.seconds();
+    cosmic.carried_reward = cosmic_reward(storage, cosmic, now)?;
+    cosmic.reward_start_time = Uint64::new(now);
     cosmic.unstaking_process = false;
     cosmic.unstaking_start_time = Uint64::zero();
-    COSMIC_INFO.save(deps.storage, token_id.u64(), &cosmic)?;
+    cosmic.reward_end_time = Uint64::zero();
+    Ok(())
+}
+
+fn execute_unstake_cosmic(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    token_id: Uint64,
+) -> Result<Response, ContractError> {
+    let mut cosmic = cosmic_info().load(deps.storage, token_id.u64())?;
+    let was = cosmic.stake_status();
+    unstake_cosmic(deps.storage, &mut cosmic, &info.sender, &env)?;
+    save_cosmic(deps.storage, token_id.u64(), was, &cosmic)?;
     Ok(Response::default()
         .add_attribute("token_id", cosmic.clone().token_id.to_string())
         .add_attribute("is_staked", cosmic.clone().is_staked.to_string())
@@ -337,80 +543,172 @@ fn execute_unstake_cosmic(
         ))
 }
 
+fn unstake_cosmic(
+    storage: &dyn Storage,
+    cosmic: &mut Cosmic,
+    sender: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
+    if !cosmic.is_staked {
+        return Err(ContractError::CosmicNotStaked {});
+    }
+    if cosmic.owner != sender.to_string() {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !cosmic.unstaking_process {
+        return Err(ContractError::UnstakingProcessIsNotStarted {});
+    }
+    let now = Uint64::new(env.block.time.seconds());
+    let min_stake_time = MIN_STAKE_TIME.load(storage)?;
+    if now.checked_sub(cosmic.unstaking_start_time)? < min_stake_time
+        || cosmic.unstaking_start_time.is_zero()
+    {
+        return Err(ContractError::MinUnstakingTimeRequired {});
+    }
+    cosmic.is_staked = false;
+    cosmic.stake_start_time = Uint64::zero();
+    cosmic.reward_start_time = Uint64::zero();
+    cosmic.carried_reward = Uint128::zero();
+    cosmic.unstaking_process = false;
+    cosmic.unstaking_start_time = Uint64::zero();
+    Ok(())
+}
+
 fn execute_claim_reward(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut cosmic = COSMIC_INFO.load(deps.storage, token_id.u64())?;
+    let mut cosmic = cosmic_info().load(deps.storage, token_id.u64())?;
+    let reward = claim_cosmic_reward(deps.storage, &mut cosmic, &info.sender, &env)?;
+    cosmic_info().save(deps.storage, token_id.u64(), &cosmic)?;
+    record_claim(deps.storage, reward)?;
+    let recipient = reward_recipient(deps.storage, &cosmic.owner)?;
+    let claim_reward_msg = claim_message(deps.storage, &recipient, reward)?;
+    Ok(Response::new().add_submessages(vec![
+        SubMsg::new(claim_reward_msg),
+    ]))
+}
+
+/// Settles the reward of one cosmic and restarts its reward window. The owner
+/// or their claim delegate may do this.
+fn claim_cosmic_reward(
+    storage: &dyn Storage,
+    cosmic: &mut Cosmic,
+    sender: &Addr,
+    env: &Env,
+) -> Result<Uint128, ContractError> {
     if !cosmic.is_staked {
         return Err(ContractError::CosmicNotStaked {});
     }
-    if cosmic.owner != info.sender.to_string() {
+    if !can_claim(storage, &cosmic.owner, sender)? {
         return Err(ContractError::Unauthorized {});
     }
-    //Reward calculation
-    let now = env.block.time.seconds();
-    let reward: Uint128;
+    let reward = cosmic_reward(storage, cosmic, env.block.time.seconds())?;
+    cosmic.carried_reward = Uint128::zero();
     if cosmic.unstaking_process {
-            let second_difference = Uint128::new(cosmic.reward_end_time.u64() as u128).checked_sub(Uint128::new(cosmic.reward_start_time.u64() as u128))?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint128::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint128::new(cosmic.daily_income.parse::<u128>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint128::new(86400)).unwrap_or(Uint128::zero());
+        cosmic.reward_start_time = Uint64::zero();
+        cosmic.reward_end_time = Uint64::zero();
     } else {
-            let second_difference = Uint128::new(now as u128).checked_sub(Uint128::new(cosmic.reward_start_time.u64() as u128))?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint128::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint128::new(cosmic.daily_income.parse::<u128>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint128::new(86400)).unwrap_or(Uint128::zero());
+        cosmic.reward_start_time = Uint64::new(env.block.time.seconds());
     }
-    let state = STATE.load(deps.storage)?;
+    Ok(reward)
+}
+
+/// Asks the reward contract to pay `amount` to `recipient`.
+fn claim_message(
+    storage: &dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    let state = STATE.load(storage)?;
     let msg = ClaimMessage {
         claim: Claim {
-            recipient: info.sender.to_string(),
-            amount: reward,
+            recipient: recipient.to_string(),
+            amount,
         }
     };
-
-    let claim_reward_msg = CosmosMsg::Wasm(Execute {
+    Ok(CosmosMsg::Wasm(Execute {
         contract_addr: state.reward_contract_address,
         msg: to_binary(&msg)?,
         funds: vec![],
-    });
-    if cosmic.unstaking_process {
-        cosmic.reward_start_time = Uint64::zero();
-        cosmic.reward_end_time = Uint64::zero();
-    } else {
-        cosmic.reward_start_time = Uint64::new(env.block.time.seconds());
-    }
-    COSMIC_INFO.save(deps.storage, token_id.u64(), &cosmic)?;
-    Ok(Response::new().add_submessages(vec![
-        SubMsg::new(claim_reward_msg),
-    ]))
+    }))
+}
+
+/// Saves `cosmic` and moves the staking counters along from its status `was`.
+fn save_cosmic(storage: &mut dyn Storage, id: u64, was: StakeStatus, cosmic: &Cosmic) -> StdResult<()> {
+    record_status_change(storage, COSMIC_KIND, was, cosmic.stake_status())?;
+    cosmic_info().save(storage, id, cosmic)
+}
+
+/// Loads every cosmic of a batch. Duplicates and unknown ids are rejected
+/// before anything is changed.
+fn load_batch(storage: &dyn Storage, token_ids: &[Uint64]) -> Result<Vec<Cosmic>, ContractError> {
+    check_batch(token_ids)?;
+    let cosmics = token_ids
+        .iter()
+        .map(|token_id| cosmic_info().load(storage, token_id.u64()))
+        .collect::<StdResult<_>>()?;
+    Ok(cosmics)
 }
 
-fn execute_claim(
+/// Applies `update` to every cosmic of a batch. All of them are checked before
+/// the first one is saved, so either all change or none do.
+fn execute_batch(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
-    token_id: Uint64,
+    token_ids: Vec<Uint64>,
+    action: &str,
+    update: fn(&dyn Storage, &mut Cosmic, &Addr, &Env) -> Result<(), ContractError>,
 ) -> Result<Response, ContractError> {
-    let mut cosmic = COSMIC_INFO.load(deps.storage, token_id.u64())?;
-    if !cosmic.is_staked {
-        return Err(ContractError::CosmicNotStaked {});
+    let mut cosmics = load_batch(deps.storage, &token_ids)?;
+    let was: Vec<_> = cosmics.iter().map(Cosmic::stake_status).collect();
+    for cosmic in cosmics.iter_mut() {
+        update(deps.storage, cosmic, &info.sender, &env)?;
     }
-    if cosmic.owner != info.sender.to_string() {
-        return Err(ContractError::Unauthorized {});
+    let mut res = Response::new().add_attribute("action", action);
+    for ((token_id, cosmic), was) in token_ids.iter().zip(&cosmics).zip(was) {
+        save_cosmic(deps.storage, token_id.u64(), was, cosmic)?;
+        res = res.add_attribute("token_id", *token_id);
     }
-    if cosmic.unstaking_process {
-        cosmic.reward_start_time = Uint64::zero();
-        cosmic.reward_end_time = Uint64::zero();
-    } else {
-        cosmic.reward_start_time = Uint64::new(env.block.time.seconds());
+    Ok(res)
+}
+
+/// Claims for every cosmic of a batch and pays each recipient its total with one
+/// message.
+fn execute_batch_claim_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    token_ids: Vec<Uint64>,
+) -> Result<Response, ContractError> {
+    let mut cosmics = load_batch(deps.storage, &token_ids)?;
+    let rewards = cosmics
+        .iter_mut()
+        .map(|cosmic| claim_cosmic_reward(deps.storage, cosmic, &info.sender, &env))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut total = Uint128::zero();
+    let mut payouts: BTreeMap<Addr, Uint128> = BTreeMap::new();
+    let mut res = Response::new().add_attribute("action", "batch_claim_reward");
+    for ((token_id, cosmic), reward) in token_ids.iter().zip(&cosmics).zip(rewards) {
+        cosmic_info().save(deps.storage, token_id.u64(), cosmic)?;
+        total = total.checked_add(reward)?;
+        let due = payouts
+            .entry(reward_recipient(deps.storage, &cosmic.owner)?)
+            .or_default();
+        *due = due.checked_add(reward)?;
+        res = res
+            .add_attribute("token_id", *token_id)
+            .add_attribute("reward", reward);
     }
-    COSMIC_INFO.save(deps.storage, token_id.u64(), &cosmic)?;
-    Ok(Response::new().add_attribute("reward_start_time", cosmic.reward_start_time)
-        .add_attribute("reward_end_time", cosmic.reward_end_time))
+    record_claim(deps.storage, total)?;
+    // a delegate can batch tokens of several owners, each paid separately
+    for (recipient, amount) in payouts {
+        res = res.add_submessage(SubMsg::new(claim_message(deps.storage, &recipient, amount)?));
+    }
+    Ok(res.add_attribute("total_reward", total))
 }
 
 fn execute_revoke_all(
@@ -493,25 +791,111 @@ fn execute_send_nft(
     token_id: String,
     msg: Binary,
 ) -> Result<Response, ContractError> {
+    let id = token_id
+        .parse::<u64>()
+        .map_err(|err| StdError::parse_err("u64", err))?;
     let send_msg = Cw721ExecuteMsg::SendNft {
         contract,
         token_id,
         msg,
     };
-    let res = Cw721Contract::default().execute(deps, env, info, send_msg);
-    if res.is_err() {
-        return Err(ContractError::NftContractError {
-            method: "send nft".to_string(),
-        });
+    move_cosmic(deps, env, info, id, send_msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let from = check_renamed_migration(
+        &stored.contract,
+        &stored.version,
+        LEGACY_CONTRACT_NAME,
+        &Version::new(0, 2, 0),
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+    let mut res = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version);
+    if from < Version::new(0, 2, 0) {
+        let (indexed, done) = migrate_v0_2(deps.storage, msg.limit)?;
+        res = res.add_attribute("indexed_cosmics", indexed.to_string());
+        if !done {
+            return Ok(res.add_attribute("migration", "unfinished"));
+        }
     }
-    Ok(Response::new())
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(res.add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// 0.2.0 indexes cosmics by owner, takes the owner from cw721, checkpoints the
+/// emission rates and counts the staking stats from the records; claims made
+/// before it aren't counted. Rewrites up to `limit` cosmics after the stored
+/// cursor so the index entries get created, moving those whose token is gone
+/// to the burned ones; returns how many and whether that was the last of them.
+fn migrate_v0_2(storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<(usize, bool)> {
+    let limit = limit.unwrap_or(DEFAULT_MIGRATION_LIMIT).max(1) as usize;
+    let start = MIGRATION_CURSOR.may_load(storage)?.map(Bound::exclusive);
+    let mut cosmics: Vec<_> = cosmic_info()
+        .range(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<_>>()?;
+    let done = cosmics.len() <= limit;
+    cosmics.truncate(limit);
+    for (id, cosmic) in cosmics.iter_mut() {
+        record_mint(storage)?;
+        match cw721_owner(storage, &cosmic.token_id)? {
+            Some(owner) => {
+                cosmic.owner = owner;
+                let status = cosmic.stake_status();
+                record_status_change(storage, COSMIC_KIND, StakeStatus::default(), status)?;
+                cosmic_info().replace(storage, *id, Some(cosmic), None)?;
+            }
+            None => {
+                record_burn(storage)?;
+                cosmic_info().replace(storage, *id, None, None)?;
+                let burned = BurnedToken {
+                    token: cosmic.clone(),
+                    reason: BurnReason::User,
+                    burned_at: Uint64::zero(),
+                };
+                BURNED_COSMICS.save(storage, *id, &burned)?;
+            }
+        }
+    }
+    if !save_migration_cursor(storage, cosmics.last().map(|(id, _)| *id), done)? {
+        return Ok((cosmics.len(), false));
+    }
+    // the rates so far have applied from the start
+    let rates = query_emission_rates(storage)?.rates;
+    save_emission_rates(storage, &rates, 0)?;
+    Ok((cosmics.len(), true))
+}
+
+/// Owner of `token_id` according to cw721, which is what the records follow.
+fn cw721_owner(storage: &dyn Storage, token_id: &str) -> StdResult<Option<String>> {
+    Ok(Cw721Contract::default()
+        .tokens
+        .may_load(storage, token_id)?
+        .map(|token| token.owner.into_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::CollectionInfo {} => to_binary(&query_config(deps)?),
-        QueryMsg::CosmicInfo { id } => to_binary(&query_cosmic(deps, id)?),
+        QueryMsg::Supply {} => to_binary(&query_supply(
+            deps.storage,
+            COSMIC_INFO_SEQ.load(deps.storage)?,
+            Cw721Contract::default().token_count(deps.storage)?,
+            COLLECTION_INFO.load(deps.storage)?.size,
+        )?),
+        QueryMsg::BurnedTokens { start_after, limit } => to_binary(&query_burned_tokens(
+            deps.storage,
+            &BURNED_COSMICS,
+            start_after,
+            limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize,
+        )?),
+        QueryMsg::CosmicInfo { id } => to_binary(&query_cosmic(deps, env, id)?),
         QueryMsg::RangeCosmics { start_after, limit } => {
             to_binary(&range_cosmics(deps, start_after, limit)?)
         }
@@ -520,38 +904,104 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
             owner,
         } => to_binary(&range_user_cosmics(deps, start_after, limit, owner)?),
+        QueryMsg::CosmicInfoList { start_after, limit } => {
+            to_binary(&query_cosmic_info_list(deps, env, start_after, limit)?)
+        }
+        QueryMsg::QueryUserCosmic {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_user_cosmic(deps, env, owner, start_after, limit)?),
         QueryMsg::CalculateReward { token_id } => {
             to_binary(&query_calculate_reward(deps, env, token_id)?)
         }
+        QueryMsg::RateHistory {} => {
+            to_binary(&query_rate_history(deps.storage, COSMIC_KIND.to_string())?)
+        }
+        QueryMsg::StakingStats {} => to_binary(&query_staking_stats(deps.storage)?),
+        QueryMsg::EmissionRates {} => to_binary(&query_emission_rates(deps.storage)?),
         QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
+        QueryMsg::ClaimSettings { owner } => to_binary(&query_claim_settings(deps.storage, owner)?),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, env, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_binary(&query_all_nft_info(deps, env, token_id, include_expired)?),
         _ => Cw721Contract::default().query(deps, env, msg.into()),
     }
 }
 
-fn query_calculate_reward(deps: Deps, env: Env, token_id: Uint64) -> StdResult<Uint64> {
-    let cosmic = COSMIC_INFO.load(deps.storage, token_id.u64()).unwrap();
-    let now = Uint64::new(env.block.time.seconds());
-    let reward: Uint64;
-    if cosmic.unstaking_process {
-            let second_difference = cosmic.reward_end_time.checked_sub(cosmic.reward_start_time)?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint64::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint64::new(cosmic.daily_income.parse::<u64>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint64::new(86400))?;
-    } else {
-            let second_difference = now.checked_sub(cosmic.reward_start_time)?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint64::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint64::new(cosmic.daily_income.parse::<u64>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint64::new(86400))?;
-    }
-    Ok(reward)
+/// cw721 info of `token_id` with the cosmic's staking state set over the
+/// attributes it was minted with.
+fn query_nft_info(deps: Deps, env: Env, token_id: String) -> StdResult<NftInfoResponse<Extension>> {
+    let live = live_traits(deps.storage, &token_id, env.block.time.seconds())?;
+    let mut info = Cw721Contract::default().nft_info(deps, token_id)?;
+    info.extension = with_live_traits(info.extension, live);
+    Ok(info)
+}
+
+fn query_all_nft_info(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: Option<bool>,
+) -> StdResult<AllNftInfoResponse<Extension>> {
+    let live = live_traits(deps.storage, &token_id, env.block.time.seconds())?;
+    let mut res = Cw721Contract::default().all_nft_info(
+        deps,
+        env,
+        token_id,
+        include_expired.unwrap_or(false),
+    )?;
+    res.info.extension = with_live_traits(res.info.extension, live);
+    Ok(res)
+}
+
+/// What the cosmic record of `token_id` says at `now`; nothing for tokens
+/// without one.
+fn live_traits(storage: &dyn Storage, token_id: &str, now: u64) -> StdResult<Vec<Trait>> {
+    let cosmic = match token_id.parse() {
+        Ok(id) => cosmic_info().may_load(storage, id)?,
+        Err(_) => None,
+    };
+    Ok(match cosmic {
+        Some(cosmic) => vec![
+            Trait::new("staked", cosmic.is_staked),
+            Trait::new("unstaking", cosmic.unstaking_process),
+            Trait::new("daily_income", daily_income(storage, &cosmic, now)?),
+        ],
+        None => vec![],
+    })
+}
+
+/// Daily income `cosmic` earns at `now`, which follows the cosmic emission
+/// rate rather than what it was minted with.
+fn daily_income(storage: &dyn Storage, cosmic: &Cosmic, now: u64) -> StdResult<Decimal> {
+    let minted_with = Decimal::from_str(&cosmic.daily_income)?;
+    current_daily_income(storage, COSMIC_KIND, minted_with, now)
+}
+
+fn query_calculate_reward(deps: Deps, env: Env, token_id: Uint64) -> StdResult<Uint128> {
+    let cosmic = cosmic_info().load(deps.storage, token_id.u64())?;
+    cosmic_reward(deps.storage, &cosmic, env.block.time.seconds())
 }
 
-fn query_cosmic(deps: Deps, id: Uint64) -> StdResult<CosmicResponse> {
-    let cosmic = COSMIC_INFO.load(deps.storage, id.u64())?;
+/// Reward `cosmic` has earned up to `now`, including what it carried over;
+/// both the claim and the query go through here so they always agree.
+fn cosmic_reward(storage: &dyn Storage, cosmic: &Cosmic, now: u64) -> StdResult<Uint128> {
+    let daily_income = Decimal::from_str(&cosmic.daily_income)?;
+    let earned = accrued_reward(storage, COSMIC_KIND, daily_income, &cosmic.accrual(), now)?;
+    Ok(earned.checked_add(cosmic.carried_reward)?)
+}
+
+fn query_cosmic(deps: Deps, env: Env, id: Uint64) -> StdResult<CosmicResponse> {
+    let cosmic = cosmic_info().load(deps.storage, id.u64())?;
+    let daily_income = daily_income(deps.storage, &cosmic, env.block.time.seconds())?;
     Ok(CosmicResponse {
         token_id: cosmic.token_id,
         owner: cosmic.owner,
-        daily_income: cosmic.daily_income,
+        daily_income: daily_income.to_string(),
         is_staked: cosmic.is_staked,
         stake_start_time: cosmic.stake_start_time,
         reward_start_time: cosmic.reward_start_time,
@@ -593,7 +1043,7 @@ fn range_cosmics(
 ) -> StdResult<CosmicListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
-    let cosmics: StdResult<Vec<_>> = COSMIC_INFO
+    let cosmics: StdResult<Vec<_>> = cosmic_info()
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .collect();
@@ -611,9 +1061,11 @@ fn range_user_cosmics(
 ) -> StdResult<CosmicListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
-    let cosmics: StdResult<Vec<_>> = COSMIC_INFO
+    let cosmics: StdResult<Vec<_>> = cosmic_info()
+        .idx
+        .owner
+        .prefix(owner)
         .range(deps.storage, start, None, Order::Ascending)
-        .filter(|r| r.as_ref().unwrap().1.owner == owner)
         .take(limit)
         .collect();
     let res = CosmicListResponse {
@@ -621,3 +1073,71 @@ fn range_user_cosmics(
     };
     Ok(res)
 }
+
+/// `cosmic` with what it can claim at `now`. Only staked cosmics earn.
+fn with_reward(storage: &dyn Storage, cosmic: Cosmic, now: u64) -> StdResult<CosmicRewardResponse> {
+    let pending_reward = if cosmic.is_staked {
+        cosmic_reward(storage, &cosmic, now)?
+    } else {
+        Uint128::zero()
+    };
+    Ok(CosmicRewardResponse {
+        cosmic: cosmic.into(),
+        pending_reward,
+    })
+}
+
+fn query_cosmic_info_list(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<CosmicInfoListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let now = env.block.time.seconds();
+    let cosmics = cosmic_info()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| with_reward(deps.storage, item?.1, now))
+        .collect::<StdResult<_>>()?;
+    Ok(CosmicInfoListResponse { cosmics })
+}
+
+fn query_user_cosmic(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<UserCosmicResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let now = env.block.time.seconds();
+    let mut res = UserCosmicResponse {
+        owner: owner.clone(),
+        total_owned: 0,
+        staked: 0,
+        pending_reward: Uint128::zero(),
+        cosmics: vec![],
+    };
+    // totals cover every cosmic of the owner, the list only the requested page
+    for item in cosmic_info()
+        .idx
+        .owner
+        .prefix(owner)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (id, cosmic) = item?;
+        let entry = with_reward(deps.storage, cosmic, now)?;
+        res.total_owned += 1;
+        if entry.cosmic.is_staked {
+            res.staked += 1;
+        }
+        res.pending_reward = res.pending_reward.checked_add(entry.pending_reward)?;
+        let after_cursor = !matches!(start_after, Some(cursor) if id <= cursor);
+        if after_cursor && res.cosmics.len() < limit {
+            res.cosmics.push(entry);
+        }
+    }
+    Ok(res)
+}
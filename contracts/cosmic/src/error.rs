@@ -1,6 +1,10 @@
 use cosmwasm_std::{OverflowError, StdError};
 use cw721_base::ContractError as Cw721ContractError;
 use cw_utils::PaymentError;
+use stake_dragons_common::batch::BatchError;
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::ownership::OwnershipError;
+use stake_dragons_common::supply::SupplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +12,21 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
+    #[error("{0}")]
+    Supply(#[from] SupplyError),
+
+    #[error("{0}")]
+    Cw721(#[from] Cw721ContractError),
+
+    #[error("{0}")]
+    Batch(#[from] BatchError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -32,6 +51,9 @@ pub enum ContractError {
     #[error("Staked cosmic cannot be transferred")]
     StakedCosmicCantBeTransferred {},
 
+    #[error("Staked cosmic cannot be burned")]
+    StakedCosmicCantBeBurned {},
+
     #[error("Cosmic must be staked")]
     CosmicNotStaked {},
 
@@ -2,5 +2,7 @@ pub mod contract;
 mod error;
 pub mod msg;
 pub mod state;
+#[cfg(test)]
+mod tests;
 
 pub use crate::error::ContractError;
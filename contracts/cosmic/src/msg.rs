@@ -1,10 +1,16 @@
 use cosmwasm_std::{Binary, Uint128, Uint64};
 use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::burns::BurnReason;
+use stake_dragons_common::claims::ClaimSettingsMsg;
+use stake_dragons_common::nft::MetadataUpdate;
+use stake_dragons_common::ownership::OwnershipMsg;
+use stake_dragons_common::reward::EmissionRate;
+use stake_dragons_common::supply::SupplyMsg;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -15,31 +21,11 @@ pub struct InstantiateMsg {
     pub daily_income: Uint64,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub external_url: Option<String>,
-    pub attributes: Vec<Trait>,
-    pub image_data: Option<String>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub extension: Vec<Trait>,
+pub struct MigrateMsg {
+    /// Tokens to rewrite in this call, `DEFAULT_MIGRATION_LIMIT` if unset; a
+    /// collection that doesn't fit is finished by migrating again.
+    pub limit: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -59,15 +45,19 @@ pub enum ExecuteMsg {
     UpdateMinStakeTime {
         time: Uint64,
     },
-    UpdateOwner {
-        new_owner: String,
-    },
+    UpdateOwnership(OwnershipMsg),
+    UpdateClaimSettings(ClaimSettingsMsg),
+    /// Raises or freezes the collection size. Owner only.
+    UpdateSupply(SupplyMsg),
     UpdateDailyIncome {
         new_daily_income: Uint64,
     },
     UpdateRewardContractAddress {
         new_address: String,
     },
+    UpdateEmissionRates {
+        rates: Vec<EmissionRate>,
+    },
     TransferNft {
         recipient: String,
         token_id: Uint64,
@@ -93,8 +83,16 @@ pub enum ExecuteMsg {
     RevokeAll {
         operator: String,
     },
+    /// Burns a token. A `reason` other than `user` is only taken from the
+    /// collection minter.
     Burn {
         token_id: String,
+        reason: Option<BurnReason>,
+    },
+    /// Changes the art or URI of a token. Owner only.
+    UpdateMetadata {
+        token_id: String,
+        metadata: MetadataUpdate,
     },
     Mint(CustomMintMsg),
     StakeCosmic {
@@ -103,12 +101,31 @@ pub enum ExecuteMsg {
     StartUnstakingProcess {
         token_id: Uint64,
     },
+    /// Calls off an unstaking process; the token keeps what it earned and
+    /// starts earning again.
+    CancelUnstaking {
+        token_id: Uint64,
+    },
     UnstakeCosmic {
         token_id: Uint64,
     },
     ClaimReward {
         token_id: Uint64,
     },
+    BatchStakeCosmic {
+        token_ids: Vec<Uint64>,
+    },
+    BatchStartUnstakingProcess {
+        token_ids: Vec<Uint64>,
+    },
+    BatchUnstakeCosmic {
+        token_ids: Vec<Uint64>,
+    },
+    /// Claims for every listed cosmic with a single payout.
+    BatchClaimReward {
+        token_ids: Vec<Uint64>,
+    },
+    /// Older name of `ClaimReward`, paid out the same way.
     Claim {
         token_id: Uint64,
     },
@@ -140,12 +157,22 @@ pub enum QueryMsg {
     },
     Minter {},
     CollectionInfo {},
-    CosmicInfoList {},
+    Supply {},
+    BurnedTokens {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    CosmicInfoList {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     CosmicInfo {
         id: Uint64,
     },
     QueryUserCosmic {
         owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
     RangeCosmics {
         start_after: Option<u64>,
@@ -159,7 +186,14 @@ pub enum QueryMsg {
     CalculateReward {
         token_id: Uint64,
     },
+    EmissionRates {},
+    StakingStats {},
+    RateHistory {},
     State {},
+    PendingOwner {},
+    ClaimSettings {
+        owner: String,
+    },
 }
 
 impl From<QueryMsg> for Cw721QueryMsg {
@@ -1,8 +1,15 @@
 use crate::ContractError;
-use cosmwasm_std::{Response, Uint64};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Response, Uint128, Uint64};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::burns::BurnedToken;
+use stake_dragons_common::reward::Accrual;
+use stake_dragons_common::stats::StakeStatus;
+
+/// Emission rate key shared by every cosmic. Without a configured rate a
+/// cosmic earns the `daily_income` it was minted with.
+pub const COSMIC_KIND: &str = "cosmic";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollectionInfo {
@@ -32,6 +39,9 @@ pub struct Cosmic {
     pub unstaking_start_time: Uint64,
     pub unstaking_process: bool,
     pub reward_end_time: Uint64,
+    /// Reward earned in an earlier window and not claimed yet.
+    #[serde(default)]
+    pub carried_reward: Uint128,
 }
 
 impl Cosmic {
@@ -42,6 +52,21 @@ impl Cosmic {
             Ok(Response::new())
         }
     }
+
+    pub fn stake_status(&self) -> StakeStatus {
+        StakeStatus {
+            staked: self.is_staked,
+            unstaking: self.unstaking_process,
+        }
+    }
+
+    pub fn accrual(&self) -> Accrual {
+        Accrual {
+            reward_start_time: self.reward_start_time,
+            reward_end_time: self.reward_end_time,
+            unstaking_process: self.unstaking_process,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -78,8 +103,54 @@ pub struct CosmicListResponse {
     pub cosmics: Vec<CosmicResponse>,
 }
 
+/// A cosmic together with the reward it can claim right now.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CosmicRewardResponse {
+    pub cosmic: CosmicResponse,
+    pub pending_reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CosmicInfoListResponse {
+    pub cosmics: Vec<CosmicRewardResponse>,
+}
+
+/// Totals over everything `owner` holds, plus one page of their cosmics.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserCosmicResponse {
+    pub owner: String,
+    pub total_owned: u64,
+    pub staked: u64,
+    pub pending_reward: Uint128,
+    pub cosmics: Vec<CosmicRewardResponse>,
+}
+
 pub const STATE: Item<State> = Item::new("state");
 pub const COLLECTION_INFO: Item<CollectionInfo> = Item::new("collection_info");
-pub const COSMIC_INFO: Map<u64, Cosmic> = Map::new("cosmics_list");
 pub const COSMIC_INFO_SEQ: Item<Uint64> = Item::new("cosmic_list_length");
+/// Burned cosmics by id, out of `cosmic_info` for good.
+pub const BURNED_COSMICS: Map<u64, BurnedToken<Cosmic>> = Map::new("burned_cosmics");
 pub const MIN_STAKE_TIME: Item<Uint64> = Item::new("minimum_stake_time");
+
+pub struct CosmicIndexes<'a> {
+    pub owner: MultiIndex<'a, String, Cosmic, u64>,
+}
+
+impl<'a> IndexList<Cosmic> for CosmicIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Cosmic>> + '_> {
+        let v: Vec<&dyn Index<Cosmic>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Cosmics by id, indexed by owner.
+pub fn cosmic_info<'a>() -> IndexedMap<'a, u64, Cosmic, CosmicIndexes<'a>> {
+    let indexes = CosmicIndexes {
+        owner: MultiIndex::new(
+            |d: &Cosmic| d.owner.clone(),
+            "cosmics_list",
+            "cosmics_list__owner",
+        ),
+    };
+    IndexedMap::new("cosmics_list", indexes)
+}
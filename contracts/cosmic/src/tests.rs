@@ -0,0 +1,744 @@
+use crate::contract::{execute, instantiate, migrate, query, Cw721Contract};
+use crate::msg::{
+    ClaimMessage, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, MigrateMsg, QueryMsg,
+};
+use crate::state::{
+    Cosmic, CosmicInfoListResponse, CosmicListResponse, CosmicResponse, UserCosmicResponse,
+};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{
+    from_binary, Addr, Binary, CosmosMsg, DepsMut, Env, MemoryStorage, MessageInfo, OwnedDeps,
+    Response, StdResult, Storage, Timestamp, Uint128, Uint64, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw721::NftInfoResponse;
+use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
+use cw721_base::state::TokenInfo;
+use cw721_base::MintMsg;
+use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_storage_plus::Map;
+use stake_dragons_common::batch::BatchError;
+use stake_dragons_common::burns::{BurnReason, BurnedTokensResponse};
+use stake_dragons_common::claims::{ClaimSettingsMsg, ClaimSettingsResponse};
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::nft::MetadataUpdate;
+use stake_dragons_common::stats::StakingStatsResponse;
+use stake_dragons_common::supply::{SupplyError, SupplyMsg, SupplyResponse};
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        base: Cw721InstantiateMsg {
+            name: "cosmics".to_string(),
+            symbol: "COSMIC".to_string(),
+            minter: "minter".to_string(),
+        },
+        size: Uint64::new(100),
+        base_price: Uint64::new(1),
+        reward_contract_address: "reward".to_string(),
+        daily_income: Uint64::new(10),
+    }
+}
+
+fn setup() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let msg = instantiate_msg();
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    deps
+}
+
+fn try_mint_to(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    owner: &str,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Mint(CustomMintMsg {
+        base: MintMsg {
+            token_id: "".to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        },
+        extension: vec![],
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+}
+
+fn at(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(seconds);
+    env
+}
+
+fn stake(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, id: u64, now: u64) {
+    let msg = ExecuteMsg::StakeCosmic {
+        token_id: Uint64::new(id),
+    };
+    execute(deps.as_mut(), at(now), mock_info("user", &[]), msg).unwrap();
+}
+
+/// A cosmic as 0.1.0 stored it, under an owner cw721 no longer agrees with.
+fn legacy_cosmic(id: u64) -> Cosmic {
+    Cosmic {
+        owner: "stale".to_string(),
+        token_id: id.to_string(),
+        daily_income: "10".to_string(),
+        is_staked: false,
+        stake_start_time: Uint64::zero(),
+        reward_start_time: Uint64::zero(),
+        unstaking_start_time: Uint64::zero(),
+        unstaking_process: false,
+        reward_end_time: Uint64::zero(),
+        carried_reward: Uint128::zero(),
+    }
+}
+
+fn legacy_token(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    let token = TokenInfo {
+        owner: Addr::unchecked("user"),
+        approvals: vec![],
+        token_uri: None,
+        extension: None,
+    };
+    Cw721Contract::default()
+        .tokens
+        .save(storage, &id.to_string(), &token)
+}
+
+/// Instantiates with the 0.1.0 layout: unindexed cosmics, one of them burned,
+/// stored under the dragon contract's cw2 name.
+fn instantiate_v0_1(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    let cosmics: Map<u64, Cosmic> = Map::new("cosmics_list");
+    for id in [1, 2] {
+        cosmics.save(deps.storage, id, &legacy_cosmic(id))?;
+    }
+    legacy_token(deps.storage, 1)?;
+    set_contract_version(deps.storage, "crates.io:dragon-mint", "0.1.0")?;
+    Ok(res)
+}
+
+#[test]
+fn migrate_upgrades_v0_1_layout() {
+    let mut app = App::default();
+    let legacy = app.store_code(Box::new(ContractWrapper::new(
+        execute,
+        instantiate_v0_1,
+        query,
+    )));
+    let current = app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+    ));
+    let admin = Addr::unchecked("admin");
+    let addr = app
+        .instantiate_contract(
+            legacy,
+            admin.clone(),
+            &instantiate_msg(),
+            &[],
+            "cosmics",
+            Some(admin.to_string()),
+        )
+        .unwrap();
+
+    let res = app
+        .migrate_contract(
+            admin.clone(),
+            addr.clone(),
+            &MigrateMsg { limit: None },
+            current,
+        )
+        .unwrap();
+    let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|a| a.key == "indexed_cosmics" && a.value == "2"));
+
+    let msg = QueryMsg::RangeUserCosmics {
+        start_after: None,
+        limit: None,
+        owner: "user".to_string(),
+    };
+    let owned: CosmicListResponse = app.wrap().query_wasm_smart(&addr, &msg).unwrap();
+    assert_eq!(owned.cosmics.len(), 1);
+    assert_eq!(owned.cosmics[0].token_id, "1");
+    let msg = QueryMsg::BurnedTokens {
+        start_after: None,
+        limit: None,
+    };
+    let burned: BurnedTokensResponse<Cosmic> = app.wrap().query_wasm_smart(&addr, &msg).unwrap();
+    assert_eq!(burned.tokens.len(), 1);
+    assert_eq!(burned.tokens[0].token.token_id, "2");
+    assert_eq!(burned.tokens[0].reason, BurnReason::User);
+
+    // stored under its own name now, so it migrates again without the steps
+    let res = app
+        .migrate_contract(admin, addr, &MigrateMsg { limit: None }, current)
+        .unwrap();
+    let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+    assert!(!wasm.attributes.iter().any(|a| a.key == "indexed_cosmics"));
+}
+
+#[test]
+fn migrate_resumes_from_its_cursor() {
+    let mut deps = mock_dependencies();
+    let info = mock_info("owner", &[]);
+    instantiate_v0_1(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(1) }).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "migration" && a.value == "unfinished"));
+    assert_eq!(
+        get_contract_version(&deps.storage).unwrap().version,
+        "0.1.0"
+    );
+
+    // nothing runs on the half migrated collection
+    let freeze = || ExecuteMsg::UpdateSupply(SupplyMsg::FreezeCap {});
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), freeze()).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::Unfinished { cursor: 1 })
+    ));
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(1) }).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "indexed_cosmics" && a.value == "1"));
+    assert!(!res.attributes.iter().any(|a| a.key == "migration"));
+    assert_eq!(
+        get_contract_version(&deps.storage).unwrap().contract,
+        "crates.io:cosmic"
+    );
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), freeze()).unwrap();
+}
+
+#[test]
+fn migrate_takes_legacy_name_only_before_rename() {
+    let mut deps = setup();
+    try_mint_to(&mut deps, "user").unwrap();
+    set_contract_version(deps.as_mut().storage, "crates.io:dragon-mint", "0.2.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::WrongContract { .. })
+    ));
+
+    set_contract_version(deps.as_mut().storage, "crates.io:cosmic", "9.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::Downgrade { .. })
+    ));
+}
+
+#[test]
+fn range_user_cosmics_pages_through_owner_index() {
+    let mut deps = setup();
+    for owner in ["alice", "bob", "alice", "bob", "bob", "alice"] {
+        try_mint_to(&mut deps, owner).unwrap();
+    }
+    let page = |start_after: Option<u64>| -> Vec<String> {
+        let msg = QueryMsg::RangeUserCosmics {
+            start_after,
+            limit: Some(2),
+            owner: "bob".to_string(),
+        };
+        let res: CosmicListResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.cosmics.into_iter().map(|c| c.token_id).collect()
+    };
+    assert_eq!(page(None), vec!["2", "4"]);
+    assert_eq!(page(Some(4)), vec!["5"]);
+    assert!(page(Some(5)).is_empty());
+}
+
+#[test]
+fn user_cosmic_summarises_every_owned_cosmic() {
+    let mut deps = setup();
+    for owner in ["user", "other", "user", "user"] {
+        try_mint_to(&mut deps, owner).unwrap();
+    }
+    for id in [1, 3] {
+        stake(&mut deps, id, 1000);
+    }
+
+    let msg = QueryMsg::QueryUserCosmic {
+        owner: "user".to_string(),
+        start_after: Some(1),
+        limit: Some(1),
+    };
+    let res: UserCosmicResponse =
+        from_binary(&query(deps.as_ref(), at(1000 + 86400), msg).unwrap()).unwrap();
+    assert_eq!(res.total_owned, 3);
+    assert_eq!(res.staked, 2);
+    assert_eq!(res.pending_reward, Uint128::new(20_000_000));
+    assert_eq!(res.cosmics.len(), 1);
+    assert_eq!(res.cosmics[0].cosmic.token_id, "3");
+    assert_eq!(res.cosmics[0].pending_reward, Uint128::new(10_000_000));
+
+    let msg = QueryMsg::CosmicInfoList {
+        start_after: None,
+        limit: None,
+    };
+    let res: CosmicInfoListResponse =
+        from_binary(&query(deps.as_ref(), at(1000 + 86400), msg).unwrap()).unwrap();
+    let rewards: Vec<_> = res
+        .cosmics
+        .iter()
+        .map(|c| c.pending_reward.u128())
+        .collect();
+    assert_eq!(rewards, vec![10_000_000, 0, 10_000_000, 0]);
+}
+
+fn cosmic(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>, id: u64) -> CosmicResponse {
+    let msg = QueryMsg::CosmicInfo {
+        id: Uint64::new(id),
+    };
+    from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+}
+
+fn owner_of(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>, id: u64) -> String {
+    cosmic(deps, id).owner
+}
+
+#[test]
+fn every_transfer_path_moves_the_cosmic_record() {
+    let mut deps = setup();
+    try_mint_to(&mut deps, "user").unwrap();
+    try_mint_to(&mut deps, "user").unwrap();
+    let msg = ExecuteMsg::ApproveAll {
+        operator: "operator".to_string(),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "buyer".to_string(),
+        token_id: Uint64::new(1),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap();
+    assert_eq!(owner_of(&deps, 1), "buyer");
+
+    let msg = ExecuteMsg::SendNft {
+        contract: "market".to_string(),
+        token_id: "2".to_string(),
+        msg: Binary::default(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(owner_of(&deps, 2), "market");
+
+    // the operator was approved by the old owner only
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "operator".to_string(),
+        token_id: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Cw721(_)));
+    assert_eq!(owner_of(&deps, 1), "buyer");
+}
+
+#[test]
+fn staked_cosmics_cannot_be_sent() {
+    let mut deps = setup();
+    try_mint_to(&mut deps, "user").unwrap();
+    stake(&mut deps, 1, 1000);
+
+    let msg = ExecuteMsg::SendNft {
+        contract: "market".to_string(),
+        token_id: "1".to_string(),
+        msg: Binary::default(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::StakedCosmicCantBeTransferred {}
+    ));
+    assert_eq!(owner_of(&deps, 1), "user");
+}
+
+fn claimed(res: &Response) -> Uint128 {
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            let msg: ClaimMessage = from_binary(msg).unwrap();
+            msg.claim.amount
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+}
+
+#[test]
+fn batch_claim_pays_the_total_once() {
+    let mut deps = setup();
+    for owner in ["user", "user", "other"] {
+        try_mint_to(&mut deps, owner).unwrap();
+    }
+    let token_ids = vec![Uint64::new(1), Uint64::new(2)];
+
+    let msg = ExecuteMsg::BatchStakeCosmic {
+        token_ids: vec![Uint64::new(1), Uint64::new(3)],
+    };
+    let err = execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    assert!(!cosmic(&deps, 1).is_staked);
+    let msg = ExecuteMsg::BatchStakeCosmic {
+        token_ids: vec![Uint64::new(2), Uint64::new(2)],
+    };
+    let err = execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Batch(BatchError::Duplicate { .. })
+    ));
+
+    let msg = ExecuteMsg::BatchStakeCosmic {
+        token_ids: token_ids.clone(),
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::BatchClaimReward { token_ids };
+    let res = execute(deps.as_mut(), at(1000 + 86400), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(claimed(&res), Uint128::new(20_000_000));
+    let rewards: Vec<_> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "reward")
+        .map(|a| a.value.as_str())
+        .collect();
+    assert_eq!(rewards, vec!["10000000", "10000000"]);
+}
+
+#[test]
+fn batch_unstake_waits_out_the_cooldown() {
+    let mut deps = setup();
+    for _ in 0..2 {
+        try_mint_to(&mut deps, "user").unwrap();
+    }
+    let token_ids = vec![Uint64::new(1), Uint64::new(2)];
+    let msg = ExecuteMsg::BatchStakeCosmic {
+        token_ids: token_ids.clone(),
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::BatchStartUnstakingProcess {
+        token_ids: token_ids.clone(),
+    };
+    execute(deps.as_mut(), at(2000), mock_info("user", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::BatchUnstakeCosmic {
+        token_ids: token_ids.clone(),
+    };
+    let err = execute(deps.as_mut(), at(3000), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::MinUnstakingTimeRequired {}));
+    let msg = ExecuteMsg::BatchUnstakeCosmic { token_ids };
+    let res = execute(
+        deps.as_mut(),
+        at(2000 + 1209600),
+        mock_info("user", &[]),
+        msg,
+    )
+    .unwrap();
+    let ids: Vec<_> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "token_id")
+        .map(|a| a.value.as_str())
+        .collect();
+    assert_eq!(ids, vec!["1", "2"]);
+    assert!(!cosmic(&deps, 1).is_staked && !cosmic(&deps, 2).is_staked);
+}
+
+fn calculate(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>, now: u64) -> Uint128 {
+    let msg = QueryMsg::CalculateReward {
+        token_id: Uint64::new(1),
+    };
+    from_binary(&query(deps.as_ref(), at(now), msg).unwrap()).unwrap()
+}
+
+#[test]
+fn cancelled_unstaking_keeps_earned_reward() {
+    let mut deps = setup();
+    try_mint_to(&mut deps, "user").unwrap();
+    stake(&mut deps, 1, 0);
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(1),
+    };
+    execute(deps.as_mut(), at(86400), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(calculate(&deps, 3 * 86400), Uint128::new(10_000_000));
+
+    let cancel = ExecuteMsg::CancelUnstaking {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(
+        deps.as_mut(),
+        at(3 * 86400),
+        mock_info("other", &[]),
+        cancel.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let res = execute(
+        deps.as_mut(),
+        at(3 * 86400),
+        mock_info("user", &[]),
+        cancel.clone(),
+    )
+    .unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "action" && a.value == "cancel_unstaking"));
+    assert!(!cosmic(&deps, 1).unstaking_process);
+    assert_eq!(calculate(&deps, 4 * 86400), Uint128::new(20_000_000));
+
+    let err = execute(deps.as_mut(), at(4 * 86400), mock_info("user", &[]), cancel).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::UnstakingProcessIsNotStarted {}
+    ));
+    let msg = ExecuteMsg::UnstakeCosmic {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(
+        deps.as_mut(),
+        at(3 * 86400 + 1209600),
+        mock_info("user", &[]),
+        msg,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::UnstakingProcessIsNotStarted {}
+    ));
+
+    // the older claim message pays what was carried over too
+    let msg = ExecuteMsg::Claim {
+        token_id: Uint64::new(1),
+    };
+    let res = execute(deps.as_mut(), at(4 * 86400), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(claimed(&res), Uint128::new(20_000_000));
+    assert_eq!(calculate(&deps, 4 * 86400), Uint128::zero());
+}
+
+#[test]
+fn staking_stats_follow_every_write() {
+    let mut deps = setup();
+    for _ in 0..3 {
+        try_mint_to(&mut deps, "user").unwrap();
+    }
+    let msg = ExecuteMsg::BatchStakeCosmic {
+        token_ids: vec![Uint64::new(1), Uint64::new(2)],
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(2),
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::ClaimReward {
+        token_id: Uint64::new(1),
+    };
+    execute(deps.as_mut(), at(86400), mock_info("user", &[]), msg).unwrap();
+
+    let burn = |token_id: &str| ExecuteMsg::Burn {
+        token_id: token_id.to_string(),
+        reason: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), burn("1")).unwrap_err();
+    assert!(matches!(err, ContractError::StakedCosmicCantBeBurned {}));
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), burn("3")).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StakingStats {}).unwrap();
+    let stats: StakingStatsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        (stats.minted, stats.burned, stats.staked, stats.unstaking),
+        (3, 1, 2, 1)
+    );
+    assert_eq!(stats.rewards_claimed, Uint128::new(10_000_000));
+    let by_kind: Vec<_> = stats
+        .staked_by_kind
+        .iter()
+        .map(|k| (k.kind.as_str(), k.staked))
+        .collect();
+    assert_eq!(by_kind, vec![("cosmic", 2)]);
+}
+
+fn payouts(res: &Response) -> Vec<(String, Uint128)> {
+    res.messages
+        .iter()
+        .map(|sub| match &sub.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+                let msg: ClaimMessage = from_binary(msg).unwrap();
+                (msg.claim.recipient.to_string(), msg.claim.amount)
+            }
+            msg => panic!("unexpected message {:?}", msg),
+        })
+        .collect()
+}
+
+#[test]
+fn delegates_claim_to_the_owners_recipient() {
+    let mut deps = setup();
+    for owner in ["user", "other"] {
+        try_mint_to(&mut deps, owner).unwrap();
+        let msg = ExecuteMsg::UpdateClaimSettings(ClaimSettingsMsg::SetClaimDelegate {
+            delegate: "hot".to_string(),
+        });
+        execute(deps.as_mut(), at(0), mock_info(owner, &[]), msg).unwrap();
+    }
+    stake(&mut deps, 1, 0);
+    let msg = ExecuteMsg::StakeCosmic {
+        token_id: Uint64::new(2),
+    };
+    execute(deps.as_mut(), at(0), mock_info("other", &[]), msg).unwrap();
+    let msg = ExecuteMsg::UpdateClaimSettings(ClaimSettingsMsg::SetRewardRecipient {
+        recipient: "vault".to_string(),
+    });
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::ClaimReward {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), at(86400), mock_info("stranger", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let msg = ExecuteMsg::BatchClaimReward {
+        token_ids: vec![Uint64::new(1), Uint64::new(2)],
+    };
+    let res = execute(deps.as_mut(), at(86400), mock_info("hot", &[]), msg).unwrap();
+    assert_eq!(
+        payouts(&res),
+        vec![
+            ("other".to_string(), Uint128::new(10_000_000)),
+            ("vault".to_string(), Uint128::new(10_000_000)),
+        ]
+    );
+
+    // claiming is all a delegate can do
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), at(86400), mock_info("hot", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    let msg = QueryMsg::ClaimSettings {
+        owner: "user".to_string(),
+    };
+    let res: ClaimSettingsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.claim_delegate, Some(Addr::unchecked("hot")));
+    assert_eq!(res.reward_recipient, "vault");
+}
+
+#[test]
+fn nft_info_describes_the_cosmic_as_it_is_now() {
+    let mut deps = setup();
+    try_mint_to(&mut deps, "user").unwrap();
+    stake(&mut deps, 1, 1000);
+
+    let update = ExecuteMsg::UpdateMetadata {
+        token_id: "1".to_string(),
+        metadata: MetadataUpdate {
+            image: Some("ipfs://new".to_string()),
+            ..MetadataUpdate::default()
+        },
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        update.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update).unwrap();
+
+    let traits = |deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>| {
+        let msg = QueryMsg::NftInfo {
+            token_id: "1".to_string(),
+        };
+        let res: NftInfoResponse<Extension> =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let metadata = res.extension.unwrap();
+        assert_eq!(metadata.image.as_deref(), Some("ipfs://new"));
+        let attributes = metadata.attributes.unwrap();
+        ["staked", "unstaking", "daily_income"].map(|trait_type| {
+            let found = attributes.iter().find(|t| t.trait_type == trait_type);
+            found.unwrap().value.clone()
+        })
+    };
+    assert_eq!(traits(&deps), ["true", "false", "10"]);
+
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(1),
+    };
+    execute(deps.as_mut(), at(2000), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(traits(&deps), ["true", "true", "10"]);
+
+    // the income shown follows the current cosmic rate
+    let msg = ExecuteMsg::UpdateDailyIncome {
+        new_daily_income: Uint64::new(4),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(traits(&deps), ["true", "true", "4"]);
+    let msg = QueryMsg::CosmicInfo { id: Uint64::new(1) };
+    let cosmic: CosmicResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(cosmic.daily_income, "4");
+}
+
+#[test]
+fn mint_stops_at_the_collection_size() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        size: Uint64::new(2),
+        ..instantiate_msg()
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    try_mint_to(&mut deps, "user").unwrap();
+    try_mint_to(&mut deps, "user").unwrap();
+    let msg = ExecuteMsg::Burn {
+        token_id: "2".to_string(),
+        reason: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+    let err = try_mint_to(&mut deps, "user").unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Supply(SupplyError::MaxSupplyReached { .. })
+    ));
+
+    let raise = |size: u64| {
+        ExecuteMsg::UpdateSupply(SupplyMsg::RaiseCap {
+            size: Uint64::new(size),
+        })
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), raise(3)).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), raise(3)).unwrap();
+    try_mint_to(&mut deps, "user").unwrap();
+    let msg = ExecuteMsg::UpdateSupply(SupplyMsg::FreezeCap {});
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), raise(4)).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Supply(SupplyError::CapFrozen {})
+    ));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Supply {}).unwrap();
+    let supply: SupplyResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        supply,
+        SupplyResponse {
+            minted: Uint64::new(3),
+            burned: Uint64::new(1),
+            remaining: Uint64::zero(),
+            cap: Uint64::new(3),
+            frozen: true,
+        }
+    );
+}
@@ -1,11 +1,11 @@
 use crate::error::ContractError;
 use crate::msg::{
-    CollectionInfoResponse, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, QueryMsg,
-    ReceiveMsg, StateResponse,
+    CollectionInfoResponse, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, MigrateMsg,
+    QueryMsg, ReceiveMsg, StateResponse, Trait,
 };
 use crate::state::{
-    CollectionInfo, Crystal, CrystalListResponse, CrystalResponse, State, COLLECTION_INFO,
-    COSMIC_LENGTH, CRYSTAL_INFO, CRYSTAL_INFO_SEQ, STATE,
+    BURNED_CRYSTALS, CollectionInfo, Crystal, CrystalListResponse, CrystalResponse, State, COLLECTION_INFO,
+    COSMIC_LENGTH, crystal_info, CRYSTAL_INFO_SEQ, STATE,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
@@ -13,9 +13,10 @@ use cosmwasm_std::entry_point;
 use cosmwasm_std::WasmMsg::Execute;
 use cosmwasm_std::{
     from_slice, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Order,
-    Response, StdError, StdResult, SubMsg, Uint128, Uint64,
+    Response, StdError, StdResult, Storage, SubMsg, Uint128, Uint64,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw721::{AllNftInfoResponse, Cw721Query, NftInfoResponse};
 use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg};
 use std::ops::Add;
 
@@ -25,9 +26,19 @@ pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
 use crate::helper::generate_cosmic_mint_msg;
 use cw_storage_plus::Bound;
 use cw_utils::Expiration;
+use stake_dragons_common::burns::{query_burned_tokens, BurnReason, BurnedToken};
+use stake_dragons_common::migrate::{
+    check_renamed_migration, ensure_migrated, save_migration_cursor, Version,
+    DEFAULT_MIGRATION_LIMIT, MIGRATION_CURSOR,
+};
+use stake_dragons_common::nft::{update_metadata, with_live_traits, MetadataUpdate};
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
+use stake_dragons_common::supply::{check_supply, query_supply, update_supply, SupplyMsg};
 
 // version info for migration info
-const CONTRACT_NAME: &str = "crates.io:dragon-mint";
+const CONTRACT_NAME: &str = "crates.io:crystal";
+// 0.1.0 was deployed under the dragon contract's cw2 name
+const LEGACY_CONTRACT_NAME: &str = "crates.io:dragon-mint";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -71,8 +82,10 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    ensure_migrated(deps.storage)?;
     match msg {
-        ExecuteMsg::UpdateOwner { new_owner } => execute_update_owner(deps, info, new_owner),
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, env, info, msg),
+        ExecuteMsg::UpdateSupply(msg) => execute_update_supply(deps, info, msg),
         ExecuteMsg::UpdateState {
             cosmic_contract,
             drgn_recipient,
@@ -88,7 +101,10 @@ pub fn execute(
         ),
 
         ExecuteMsg::Mint(msg) => execute_mint(deps, env, info, msg),
-        ExecuteMsg::Burn { token_id } => execute_burn(deps, env, info, token_id),
+        ExecuteMsg::Burn { token_id, reason } => execute_burn(deps, env, info, token_id, reason),
+        ExecuteMsg::UpdateMetadata { token_id, metadata } => {
+            execute_update_metadata(deps, info, token_id, metadata)
+        }
         ExecuteMsg::TransferNft {
             recipient,
             token_id,
@@ -188,11 +204,11 @@ pub fn execute_open_cw20(
 
     //Check for different types exist
     let (fire, ice, storm, divine, udin) = (
-        CRYSTAL_INFO.load(deps.storage, fire_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, ice_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, storm_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, divine_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, udin_id.u64())?,
+        crystal_info().load(deps.storage, fire_id.u64())?,
+        crystal_info().load(deps.storage, ice_id.u64())?,
+        crystal_info().load(deps.storage, storm_id.u64())?,
+        crystal_info().load(deps.storage, divine_id.u64())?,
+        crystal_info().load(deps.storage, udin_id.u64())?,
     );
     let crystals = [fire, ice, storm, divine, udin];
     let kinds = ["fire", "ice", "storm", "divine", "udin"];
@@ -211,18 +227,10 @@ pub fn execute_open_cw20(
             .execute(deps.branch(), env.clone(), info.clone(), msg)
             .unwrap();
     }
-    //Delete from the local list
-    for index in 0..5 {
-        let mut crystal = CRYSTAL_INFO.load(
-            deps.storage,
-            crystals[index].clone().token_id.parse::<u64>().unwrap(),
-        )?;
-        crystal.owner = "".to_string();
-        CRYSTAL_INFO.save(
-            deps.storage,
-            crystals[index].clone().token_id.parse::<u64>().unwrap(),
-            &crystal,
-        )?;
+    //Move to the burned list
+    let burned_at = Uint64::new(env.block.time.seconds());
+    for crystal in crystals {
+        bury_crystal(deps.storage, crystal, BurnReason::Forge, burned_at)?;
     }
     //Successfully generate cosmic
     let cosmic_id =
@@ -236,18 +244,41 @@ pub fn execute_open_cw20(
     Ok(Response::new().add_submessages(vec![SubMsg::new(fee_payout_msg), SubMsg::new(mint_msg)]))
 }
 
-fn execute_update_owner(
+fn execute_update_supply(
     deps: DepsMut,
     info: MessageInfo,
-    new_owner: String,
+    msg: SupplyMsg,
 ) -> Result<Response, ContractError> {
-    let mut state = STATE.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
     if info.sender.to_string() != state.owner {
         return Err(ContractError::Unauthorized {});
     }
-    state.owner = new_owner;
+    let mut collection_info = COLLECTION_INFO.load(deps.storage)?;
+    collection_info.size = update_supply(deps.storage, collection_info.size, msg)?;
+    COLLECTION_INFO.save(deps.storage, &collection_info)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_supply")
+        .add_attribute("size", collection_info.size))
+}
+
+fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
     STATE.save(deps.storage, &state)?;
-    Ok(Response::default().add_attribute("new_owner", state.owner))
+    Ok(res)
 }
 
 fn execute_update_state(
@@ -269,19 +300,66 @@ fn execute_update_state(
     STATE.save(deps.storage, &state)?;
     Ok(Response::default().add_attribute("new_cosmic_contract", state.cosmic_contract))
 }
-fn execute_burn(
+fn execute_update_metadata(
     deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    metadata: MetadataUpdate,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender.to_string() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    update_metadata(deps.storage, &token_id, metadata)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_metadata")
+        .add_attribute("token_id", token_id))
+}
+
+fn execute_burn(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     token_id: String,
+    reason: Option<BurnReason>,
 ) -> Result<Response, ContractError> {
+    let id = token_id
+        .parse::<u64>()
+        .map_err(|err| StdError::parse_err("u64", err))?;
+    let crystal = crystal_info().load(deps.storage, id)?;
+    let reason = reason.unwrap_or(BurnReason::User);
+    if reason != BurnReason::User
+        && info.sender != Cw721Contract::default().minter.load(deps.storage)?
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    let burned_at = Uint64::new(env.block.time.seconds());
     let msg = Cw721ExecuteMsg::Burn { token_id };
-    Cw721Contract::default()
-        .execute(deps, env, info, msg)
-        .unwrap();
+    Cw721Contract::default().execute(deps.branch(), env, info, msg)?;
+    bury_crystal(deps.storage, crystal, reason, burned_at)?;
     Ok(Response::new())
 }
 
+/// Moves `crystal`, whose token was just burned, to the burned crystals.
+fn bury_crystal(
+    storage: &mut dyn Storage,
+    crystal: Crystal,
+    reason: BurnReason,
+    burned_at: Uint64,
+) -> StdResult<()> {
+    let id = crystal
+        .token_id
+        .parse::<u64>()
+        .map_err(|err| StdError::parse_err("u64", err))?;
+    crystal_info().remove(storage, id)?;
+    let burned = BurnedToken {
+        token: crystal,
+        reason,
+        burned_at,
+    };
+    BURNED_CRYSTALS.save(storage, id, &burned)
+}
+
 fn execute_mint(
     deps: DepsMut,
     env: Env,
@@ -299,6 +377,8 @@ fn execute_mint(
             _ => return Err(ContractError::UnexpectedTraitType { trait_type }),
         }
     }
+    let cap = COLLECTION_INFO.load(deps.storage)?.size;
+    check_supply(CRYSTAL_INFO_SEQ.load(deps.storage)?, cap)?;
     let id =
         CRYSTAL_INFO_SEQ.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint64::new(1))))?;
     let crystal = Crystal {
@@ -306,7 +386,7 @@ fn execute_mint(
         owner: msg.clone().base.owner,
         kind,
     };
-    CRYSTAL_INFO.save(deps.storage, id.u64(), &crystal)?;
+    crystal_info().save(deps.storage, id.u64(), &crystal)?;
     msg.base.token_id = id.to_string();
     let mint_msg = Cw721ExecuteMsg::Mint(msg.base.clone());
     Cw721Contract::default()
@@ -336,11 +416,11 @@ fn execute_generate_cosmic(
     }
     //Check for different types exist
     let (fire, ice, storm, divine, udin) = (
-        CRYSTAL_INFO.load(deps.storage, fire_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, ice_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, storm_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, divine_id.u64())?,
-        CRYSTAL_INFO.load(deps.storage, udin_id.u64())?,
+        crystal_info().load(deps.storage, fire_id.u64())?,
+        crystal_info().load(deps.storage, ice_id.u64())?,
+        crystal_info().load(deps.storage, storm_id.u64())?,
+        crystal_info().load(deps.storage, divine_id.u64())?,
+        crystal_info().load(deps.storage, udin_id.u64())?,
     );
     let crystals = [fire, ice, storm, divine, udin];
     let kinds = ["fire", "ice", "storm", "divine", "udin"];
@@ -362,18 +442,10 @@ fn execute_generate_cosmic(
             });
         }
     }
-    //Delete from the local list
-    for index in 0..5 {
-        let mut crystal = CRYSTAL_INFO.load(
-            deps.storage,
-            crystals[index].clone().token_id.parse::<u64>().unwrap(),
-        )?;
-        crystal.owner = "".to_string();
-        CRYSTAL_INFO.save(
-            deps.storage,
-            crystals[index].clone().token_id.parse::<u64>().unwrap(),
-            &crystal,
-        )?;
+    //Move to the burned list
+    let burned_at = Uint64::new(env.block.time.seconds());
+    for crystal in crystals {
+        bury_crystal(deps.storage, crystal, BurnReason::Forge, burned_at)?;
     }
     //Successfully generate cosmic
     let cosmic_id =
@@ -394,18 +466,33 @@ fn execute_transfer_nft(
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
     let msg = Cw721ExecuteMsg::TransferNft {
-        recipient: recipient.clone(),
+        recipient,
         token_id: token_id.to_string(),
     };
-    let mut crystal = CRYSTAL_INFO.load(deps.storage, token_id.u64())?;
-    let valid_recipient = deps.api.addr_validate(&*recipient)?;
-    crystal.owner = valid_recipient.to_string();
-    CRYSTAL_INFO.save(deps.storage, token_id.u64(), &crystal)?;
-    Cw721Contract::default()
-        .execute(deps, env, info.clone(), msg)
-        .unwrap();
-    Ok(Response::default()
-        .add_attribute("old owner", info.sender.to_string())
+    move_crystal(deps, env, info, token_id.u64(), msg)
+}
+
+/// Runs a cw721 transfer or send, which checks that the sender owns the
+/// token or is approved for it, then copies the new cw721 owner into the
+/// crystal record.
+fn move_crystal(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    msg: Cw721ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let mut crystal = crystal_info().load(deps.storage, id)?;
+    let old_owner = crystal.owner.clone();
+    let res = Cw721Contract::default().execute(deps.branch(), env, info, msg)?;
+    crystal.owner = Cw721Contract::default()
+        .tokens
+        .load(deps.storage, &crystal.token_id)?
+        .owner
+        .into_string();
+    crystal_info().save(deps.storage, id, &crystal)?;
+    Ok(res
+        .add_attribute("old owner", old_owner)
         .add_attribute("new owner", crystal.owner))
 }
 
@@ -489,24 +576,106 @@ fn execute_send_nft(
     token_id: String,
     msg: Binary,
 ) -> Result<Response, ContractError> {
+    let id = token_id
+        .parse::<u64>()
+        .map_err(|err| StdError::parse_err("u64", err))?;
     let send_msg = Cw721ExecuteMsg::SendNft {
         contract,
         token_id,
         msg,
     };
-    let res = Cw721Contract::default().execute(deps, env, info, send_msg);
-    if res.is_err() {
-        return Err(ContractError::NftContractError {
-            method: "send nft".to_string(),
-        });
+    move_crystal(deps, env, info, id, send_msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let from = check_renamed_migration(
+        &stored.contract,
+        &stored.version,
+        LEGACY_CONTRACT_NAME,
+        &Version::new(0, 2, 0),
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+    let mut res = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version);
+    if from < Version::new(0, 2, 0) {
+        let (indexed, done) = migrate_v0_2(deps.storage, msg.limit)?;
+        res = res.add_attribute("indexed_crystals", indexed.to_string());
+        if !done {
+            return Ok(res.add_attribute("migration", "unfinished"));
+        }
     }
-    Ok(Response::new())
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(res.add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// 0.2.0 indexes crystals by owner and takes the owner from cw721. Rewrites
+/// up to `limit` crystals after the stored cursor so the index entries get
+/// created, moving those whose token is gone to the burned ones; returns how
+/// many and whether that was the last of them.
+fn migrate_v0_2(storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<(usize, bool)> {
+    let limit = limit.unwrap_or(DEFAULT_MIGRATION_LIMIT).max(1) as usize;
+    let start = MIGRATION_CURSOR.may_load(storage)?.map(Bound::exclusive);
+    let mut crystals: Vec<_> = crystal_info()
+        .range(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<_>>()?;
+    let done = crystals.len() <= limit;
+    crystals.truncate(limit);
+    for (id, crystal) in crystals.iter_mut() {
+        match cw721_owner(storage, &crystal.token_id)? {
+            Some(owner) => {
+                crystal.owner = owner;
+                crystal_info().replace(storage, *id, Some(crystal), None)?;
+            }
+            None => {
+                crystal_info().replace(storage, *id, None, None)?;
+                // forging used to leave the crystals behind without an owner
+                let reason = if crystal.owner.is_empty() {
+                    BurnReason::Forge
+                } else {
+                    BurnReason::User
+                };
+                let burned = BurnedToken {
+                    token: crystal.clone(),
+                    reason,
+                    burned_at: Uint64::zero(),
+                };
+                BURNED_CRYSTALS.save(storage, *id, &burned)?;
+            }
+        }
+    }
+    let last = crystals.last().map(|(id, _)| *id);
+    Ok((crystals.len(), save_migration_cursor(storage, last, done)?))
+}
+
+/// Owner of `token_id` according to cw721, which is what the records follow.
+fn cw721_owner(storage: &dyn Storage, token_id: &str) -> StdResult<Option<String>> {
+    Ok(Cw721Contract::default()
+        .tokens
+        .may_load(storage, token_id)?
+        .map(|token| token.owner.into_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::CollectionInfo {} => to_binary(&query_config(deps)?),
+        QueryMsg::Supply {} => to_binary(&query_supply(
+            deps.storage,
+            CRYSTAL_INFO_SEQ.load(deps.storage)?,
+            Cw721Contract::default().token_count(deps.storage)?,
+            COLLECTION_INFO.load(deps.storage)?.size,
+        )?),
+        QueryMsg::BurnedTokens { start_after, limit } => to_binary(&query_burned_tokens(
+            deps.storage,
+            &BURNED_CRYSTALS,
+            start_after,
+            limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize,
+        )?),
         QueryMsg::CrystalInfo { id } => to_binary(&query_crystal(deps, id)?),
         QueryMsg::RangeCrystals { start_after, limit } => {
             to_binary(&range_crystals(deps, start_after, limit)?)
@@ -517,12 +686,56 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             owner,
         } => to_binary(&range_user_crystals(deps, start_after, limit, owner)?),
         QueryMsg::GetState {} => to_binary(&query_state(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_binary(&query_all_nft_info(deps, env, token_id, include_expired)?),
         _ => Cw721Contract::default().query(deps, env, msg.into()),
     }
 }
 
+/// cw721 info of `token_id` with the crystal's recorded kind set over the
+/// attributes it was minted with.
+fn query_nft_info(deps: Deps, token_id: String) -> StdResult<NftInfoResponse<Extension>> {
+    let live = live_traits(deps.storage, &token_id)?;
+    let mut info = Cw721Contract::default().nft_info(deps, token_id)?;
+    info.extension = with_live_traits(info.extension, live);
+    Ok(info)
+}
+
+fn query_all_nft_info(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: Option<bool>,
+) -> StdResult<AllNftInfoResponse<Extension>> {
+    let live = live_traits(deps.storage, &token_id)?;
+    let mut res = Cw721Contract::default().all_nft_info(
+        deps,
+        env,
+        token_id,
+        include_expired.unwrap_or(false),
+    )?;
+    res.info.extension = with_live_traits(res.info.extension, live);
+    Ok(res)
+}
+
+/// What the crystal record of `token_id` says; nothing for tokens without one.
+fn live_traits(storage: &dyn Storage, token_id: &str) -> StdResult<Vec<Trait>> {
+    let crystal = match token_id.parse() {
+        Ok(id) => crystal_info().may_load(storage, id)?,
+        Err(_) => None,
+    };
+    Ok(match crystal {
+        Some(crystal) => vec![Trait::new("kind", crystal.kind)],
+        None => vec![],
+    })
+}
+
 fn query_crystal(deps: Deps, id: Uint64) -> StdResult<CrystalResponse> {
-    let crystal = CRYSTAL_INFO.load(deps.storage, id.u64())?;
+    let crystal = crystal_info().load(deps.storage, id.u64())?;
     Ok(CrystalResponse {
         token_id: crystal.token_id,
         owner: crystal.owner,
@@ -563,7 +776,7 @@ fn range_crystals(
 ) -> StdResult<CrystalListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
-    let crystals: StdResult<Vec<_>> = CRYSTAL_INFO
+    let crystals: StdResult<Vec<_>> = crystal_info()
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .collect();
@@ -581,9 +794,11 @@ fn range_user_crystals(
 ) -> StdResult<CrystalListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
-    let crystals: StdResult<Vec<_>> = CRYSTAL_INFO
+    let crystals: StdResult<Vec<_>> = crystal_info()
+        .idx
+        .owner
+        .prefix(owner)
         .range(deps.storage, start, None, Order::Ascending)
-        .filter(|r| r.as_ref().unwrap().1.owner == owner)
         .take(limit)
         .collect();
     let res = CrystalListResponse {
@@ -1,6 +1,9 @@
 use cosmwasm_std::{OverflowError, StdError, Uint128};
 use cw721_base::ContractError as Cw721ContractError;
 use cw_utils::PaymentError;
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::ownership::OwnershipError;
+use stake_dragons_common::supply::SupplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +11,18 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
+    #[error("{0}")]
+    Supply(#[from] SupplyError),
+
+    #[error("{0}")]
+    Cw721(#[from] Cw721ContractError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
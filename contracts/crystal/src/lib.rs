@@ -3,5 +3,7 @@ mod error;
 mod helper;
 pub mod msg;
 pub mod state;
+#[cfg(test)]
+mod tests;
 
 pub use crate::error::ContractError;
@@ -1,11 +1,15 @@
 use cosmwasm_std::{Addr, Binary, Uint128, Uint64};
 use cw20::Cw20ReceiveMsg;
 use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::burns::BurnReason;
+use stake_dragons_common::nft::MetadataUpdate;
+use stake_dragons_common::ownership::OwnershipMsg;
+use stake_dragons_common::supply::SupplyMsg;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, MintExecuteMsg, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -17,31 +21,11 @@ pub struct InstantiateMsg {
     pub attune_price: Uint128,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub external_url: Option<String>,
-    pub attributes: Vec<Trait>,
-    pub image_data: Option<String>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub extension: Vec<Trait>,
+pub struct MigrateMsg {
+    /// Tokens to rewrite in this call, `DEFAULT_MIGRATION_LIMIT` if unset; a
+    /// collection that doesn't fit is finished by migrating again.
+    pub limit: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -59,9 +43,9 @@ pub enum ReceiveMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    UpdateOwner {
-        new_owner: String,
-    },
+    UpdateOwnership(OwnershipMsg),
+    /// Raises or freezes the collection size. Owner only.
+    UpdateSupply(SupplyMsg),
     TransferNft {
         recipient: String,
         token_id: Uint64,
@@ -87,8 +71,16 @@ pub enum ExecuteMsg {
     RevokeAll {
         operator: String,
     },
+    /// Burns a token. A `reason` other than `user` is only taken from the
+    /// collection minter.
     Burn {
         token_id: String,
+        reason: Option<BurnReason>,
+    },
+    /// Changes the art or URI of a token. Owner only.
+    UpdateMetadata {
+        token_id: String,
+        metadata: MetadataUpdate,
     },
     Mint(CustomMintMsg),
     GenerateCosmic {
@@ -133,6 +125,11 @@ pub enum QueryMsg {
     },
     Minter {},
     CollectionInfo {},
+    Supply {},
+    BurnedTokens {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     CrystalInfoList {},
     CrystalInfo {
         id: Uint64,
@@ -150,6 +147,7 @@ pub enum QueryMsg {
         owner: String,
     },
     GetState {},
+    PendingOwner {},
 }
 
 impl From<QueryMsg> for Cw721QueryMsg {
@@ -206,8 +204,3 @@ pub struct StateResponse {
     pub allowed_cw20: Addr,
     pub attune_price: Uint128,
 }
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct NftMintMsg {
-    pub mint: CustomMintMsg,
-}
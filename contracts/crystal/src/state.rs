@@ -1,8 +1,9 @@
 use crate::ContractError;
 use cosmwasm_std::{Addr, Response, Uint128, Uint64};
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::burns::BurnedToken;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollectionInfo {
@@ -62,6 +63,30 @@ pub struct CrystalListResponse {
 
 pub const STATE: Item<State> = Item::new("state");
 pub const COLLECTION_INFO: Item<CollectionInfo> = Item::new("collection_info");
-pub const CRYSTAL_INFO: Map<u64, Crystal> = Map::new("crystals_list");
 pub const CRYSTAL_INFO_SEQ: Item<Uint64> = Item::new("crystal_list_length");
+/// Burned crystals by id, out of `crystal_info` for good.
+pub const BURNED_CRYSTALS: Map<u64, BurnedToken<Crystal>> = Map::new("burned_crystals");
 pub const COSMIC_LENGTH: Item<Uint64> = Item::new("cosmic_id");
+
+pub struct CrystalIndexes<'a> {
+    pub owner: MultiIndex<'a, String, Crystal, u64>,
+}
+
+impl<'a> IndexList<Crystal> for CrystalIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Crystal>> + '_> {
+        let v: Vec<&dyn Index<Crystal>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Crystals by id, indexed by owner.
+pub fn crystal_info<'a>() -> IndexedMap<'a, u64, Crystal, CrystalIndexes<'a>> {
+    let indexes = CrystalIndexes {
+        owner: MultiIndex::new(
+            |d: &Crystal| d.owner.clone(),
+            "crystals_list",
+            "crystals_list__owner",
+        ),
+    };
+    IndexedMap::new("crystals_list", indexes)
+}
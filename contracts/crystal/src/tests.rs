@@ -0,0 +1,501 @@
+use crate::contract::{execute, instantiate, migrate, query, Cw721Contract};
+use crate::msg::{
+    CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, Metadata, MigrateMsg, QueryMsg, Trait,
+};
+use crate::state::{Crystal, CrystalListResponse, CrystalResponse};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{
+    from_binary, Addr, Binary, DepsMut, Env, MemoryStorage, MessageInfo, OwnedDeps, Response,
+    StdResult, Storage, Uint128, Uint64,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw721::AllNftInfoResponse;
+use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
+use cw721_base::state::TokenInfo;
+use cw721_base::MintMsg;
+use cw_multi_test::{App, ContractWrapper, Executor};
+use cw_storage_plus::Map;
+use stake_dragons_common::burns::{BurnReason, BurnedTokensResponse};
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::nft::MetadataUpdate;
+use stake_dragons_common::supply::{SupplyError, SupplyMsg, SupplyResponse};
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        base: Cw721InstantiateMsg {
+            name: "crystals".to_string(),
+            symbol: "CRYSTAL".to_string(),
+            minter: "minter".to_string(),
+        },
+        size: Uint64::new(100),
+        cosmic_contract: "cosmic".to_string(),
+        drgn_recipient: "recipient".to_string(),
+        allowed_cw20: Addr::unchecked("drgn"),
+        attune_price: Uint128::new(1),
+    }
+}
+
+fn setup() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let msg = instantiate_msg();
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    deps
+}
+
+fn try_mint_to(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    owner: &str,
+    kind: &str,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Mint(CustomMintMsg {
+        base: MintMsg {
+            token_id: "".to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        },
+        extension: vec![Trait {
+            display_type: None,
+            trait_type: "kind".to_string(),
+            value: kind.to_string(),
+        }],
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+}
+
+fn legacy_crystal(id: u64, owner: &str) -> Crystal {
+    Crystal {
+        owner: owner.to_string(),
+        token_id: id.to_string(),
+        kind: "fire".to_string(),
+    }
+}
+
+fn legacy_token(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    let token = TokenInfo {
+        owner: Addr::unchecked("user"),
+        approvals: vec![],
+        token_uri: None,
+        extension: None,
+    };
+    Cw721Contract::default()
+        .tokens
+        .save(storage, &id.to_string(), &token)
+}
+
+/// Instantiates with the 0.1.0 layout under the dragon contract's cw2 name:
+/// unindexed crystals, a forged one left without an owner and a burned one.
+fn instantiate_v0_1(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    let crystals: Map<u64, Crystal> = Map::new("crystals_list");
+    crystals.save(deps.storage, 1, &legacy_crystal(1, "stale"))?;
+    crystals.save(deps.storage, 2, &legacy_crystal(2, ""))?;
+    crystals.save(deps.storage, 3, &legacy_crystal(3, "user"))?;
+    legacy_token(deps.storage, 1)?;
+    set_contract_version(deps.storage, "crates.io:dragon-mint", "0.1.0")?;
+    Ok(res)
+}
+
+#[test]
+fn migrate_upgrades_v0_1_layout() {
+    let mut app = App::default();
+    let legacy = app.store_code(Box::new(ContractWrapper::new(
+        execute,
+        instantiate_v0_1,
+        query,
+    )));
+    let current = app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+    ));
+    let admin = Addr::unchecked("admin");
+    let addr = app
+        .instantiate_contract(
+            legacy,
+            admin.clone(),
+            &instantiate_msg(),
+            &[],
+            "crystals",
+            Some(admin.to_string()),
+        )
+        .unwrap();
+
+    let res = app
+        .migrate_contract(
+            admin.clone(),
+            addr.clone(),
+            &MigrateMsg { limit: None },
+            current,
+        )
+        .unwrap();
+    let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|a| a.key == "indexed_crystals" && a.value == "3"));
+
+    let msg = QueryMsg::RangeUserCrystals {
+        start_after: None,
+        limit: None,
+        owner: "user".to_string(),
+    };
+    let owned: CrystalListResponse = app.wrap().query_wasm_smart(&addr, &msg).unwrap();
+    assert_eq!(owned.crystals.len(), 1);
+    assert_eq!(owned.crystals[0].token_id, "1");
+    let msg = QueryMsg::BurnedTokens {
+        start_after: None,
+        limit: None,
+    };
+    let burned: BurnedTokensResponse<Crystal> = app.wrap().query_wasm_smart(&addr, &msg).unwrap();
+    let reasons: Vec<_> = burned
+        .tokens
+        .iter()
+        .map(|b| (b.token.token_id.as_str(), b.reason))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![("2", BurnReason::Forge), ("3", BurnReason::User)]
+    );
+
+    // stored under its own name now, so it migrates again without the steps
+    let res = app
+        .migrate_contract(admin, addr, &MigrateMsg { limit: None }, current)
+        .unwrap();
+    let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+    assert!(!wasm.attributes.iter().any(|a| a.key == "indexed_crystals"));
+}
+
+#[test]
+fn migrate_resumes_from_its_cursor() {
+    let mut deps = mock_dependencies();
+    let info = mock_info("owner", &[]);
+    instantiate_v0_1(deps.as_mut(), mock_env(), info, instantiate_msg()).unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "migration" && a.value == "unfinished"));
+    assert_eq!(
+        get_contract_version(&deps.storage).unwrap().version,
+        "0.1.0"
+    );
+
+    // nothing runs on the half migrated collection
+    let freeze = || ExecuteMsg::UpdateSupply(SupplyMsg::FreezeCap {});
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), freeze()).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::Unfinished { cursor: 2 })
+    ));
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "indexed_crystals" && a.value == "1"));
+    assert!(!res.attributes.iter().any(|a| a.key == "migration"));
+    assert_eq!(
+        get_contract_version(&deps.storage).unwrap().contract,
+        "crates.io:crystal"
+    );
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), freeze()).unwrap();
+}
+
+#[test]
+fn migrate_takes_legacy_name_only_before_rename() {
+    let mut deps = setup();
+    try_mint_to(&mut deps, "user", "fire").unwrap();
+    set_contract_version(deps.as_mut().storage, "crates.io:dragon-mint", "0.2.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::WrongContract { .. })
+    ));
+
+    set_contract_version(deps.as_mut().storage, "crates.io:crystal", "9.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::Downgrade { .. })
+    ));
+}
+
+#[test]
+fn range_user_crystals_pages_through_owner_index() {
+    let mut deps = setup();
+    for owner in ["alice", "bob", "alice", "bob", "bob", "alice"] {
+        try_mint_to(&mut deps, owner, "fire").unwrap();
+    }
+    let page = |start_after: Option<u64>| -> Vec<String> {
+        let msg = QueryMsg::RangeUserCrystals {
+            start_after,
+            limit: Some(2),
+            owner: "bob".to_string(),
+        };
+        let res: CrystalListResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.crystals.into_iter().map(|c| c.token_id).collect()
+    };
+    assert_eq!(page(None), vec!["2", "4"]);
+    assert_eq!(page(Some(4)), vec!["5"]);
+    assert!(page(Some(5)).is_empty());
+}
+
+fn owner_of(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>, id: u64) -> String {
+    let msg = QueryMsg::CrystalInfo {
+        id: Uint64::new(id),
+    };
+    let crystal: CrystalResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    crystal.owner
+}
+
+#[test]
+fn every_transfer_path_moves_the_crystal_record() {
+    let mut deps = setup();
+    for _ in 0..3 {
+        try_mint_to(&mut deps, "user", "fire").unwrap();
+    }
+    let msg = ExecuteMsg::ApproveAll {
+        operator: "operator".to_string(),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::Approve {
+        spender: "spender".to_string(),
+        token_id: "3".to_string(),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "buyer".to_string(),
+        token_id: Uint64::new(1),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap();
+    assert_eq!(owner_of(&deps, 1), "buyer");
+
+    let msg = ExecuteMsg::SendNft {
+        contract: "market".to_string(),
+        token_id: "2".to_string(),
+        msg: Binary::default(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(owner_of(&deps, 2), "market");
+
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "spender".to_string(),
+        token_id: Uint64::new(3),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("spender", &[]), msg).unwrap();
+    assert_eq!(owner_of(&deps, 3), "spender");
+
+    // the operator was approved by the old owner only
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "operator".to_string(),
+        token_id: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Cw721(_)));
+    assert_eq!(owner_of(&deps, 1), "buyer");
+    let msg = QueryMsg::RangeUserCrystals {
+        start_after: None,
+        limit: None,
+        owner: "user".to_string(),
+    };
+    let res: CrystalListResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert!(res.crystals.is_empty());
+}
+
+#[test]
+fn nft_info_describes_the_crystal_as_it_is_now() {
+    let mut deps = setup();
+    // the kind in the minted metadata is overridden by the crystal record
+    let msg = ExecuteMsg::Mint(CustomMintMsg {
+        base: MintMsg {
+            token_id: "".to_string(),
+            owner: "user".to_string(),
+            token_uri: None,
+            extension: Some(Metadata {
+                attributes: Some(vec![Trait {
+                    display_type: None,
+                    trait_type: "kind".to_string(),
+                    value: "stale".to_string(),
+                }]),
+                ..Metadata::default()
+            }),
+        },
+        extension: vec![Trait {
+            display_type: None,
+            trait_type: "kind".to_string(),
+            value: "fire".to_string(),
+        }],
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg).unwrap();
+
+    let update = ExecuteMsg::UpdateMetadata {
+        token_id: "1".to_string(),
+        metadata: MetadataUpdate {
+            image: Some("ipfs://new".to_string()),
+            ..MetadataUpdate::default()
+        },
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        update.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update).unwrap();
+
+    let msg = QueryMsg::AllNftInfo {
+        token_id: "1".to_string(),
+        include_expired: None,
+    };
+    let res: AllNftInfoResponse<Extension> =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.access.owner, "user");
+    let metadata = res.info.extension.unwrap();
+    assert_eq!(metadata.image.as_deref(), Some("ipfs://new"));
+    let kinds: Vec<_> = metadata
+        .attributes
+        .unwrap()
+        .into_iter()
+        .filter(|t| t.trait_type == "kind")
+        .map(|t| t.value)
+        .collect();
+    assert_eq!(kinds, vec!["fire"]);
+}
+
+#[test]
+fn mint_stops_at_the_collection_size() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        size: Uint64::new(2),
+        ..instantiate_msg()
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    try_mint_to(&mut deps, "user", "fire").unwrap();
+    try_mint_to(&mut deps, "user", "fire").unwrap();
+    let msg = ExecuteMsg::Burn {
+        token_id: "2".to_string(),
+        reason: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+    let err = try_mint_to(&mut deps, "user", "fire").unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Supply(SupplyError::MaxSupplyReached { .. })
+    ));
+
+    let raise = |size: u64| {
+        ExecuteMsg::UpdateSupply(SupplyMsg::RaiseCap {
+            size: Uint64::new(size),
+        })
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), raise(3)).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), raise(3)).unwrap();
+    try_mint_to(&mut deps, "user", "fire").unwrap();
+    let msg = ExecuteMsg::UpdateSupply(SupplyMsg::FreezeCap {});
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), raise(4)).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Supply(SupplyError::CapFrozen {})
+    ));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Supply {}).unwrap();
+    let supply: SupplyResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        supply,
+        SupplyResponse {
+            minted: Uint64::new(3),
+            burned: Uint64::new(1),
+            remaining: Uint64::zero(),
+            cap: Uint64::new(3),
+            frozen: true,
+        }
+    );
+}
+
+#[test]
+fn forged_crystals_leave_the_live_records() {
+    let mut deps = setup();
+    for kind in ["fire", "ice", "storm", "divine", "udin"] {
+        try_mint_to(&mut deps, "owner", kind).unwrap();
+    }
+    try_mint_to(&mut deps, "user", "fire").unwrap();
+    let msg = ExecuteMsg::Burn {
+        token_id: "6".to_string(),
+        reason: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+
+    let forge = |fire_id: u64, ice_id: u64| ExecuteMsg::GenerateCosmic {
+        fire_id: Uint64::new(fire_id),
+        ice_id: Uint64::new(ice_id),
+        storm_id: Uint64::new(3),
+        divine_id: Uint64::new(4),
+        udin_id: Uint64::new(5),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        forge(2, 1),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::KindNotFound {}));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        forge(1, 2),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    let msg = QueryMsg::RangeUserCrystals {
+        start_after: None,
+        limit: None,
+        owner: "owner".to_string(),
+    };
+    let res: CrystalListResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert!(res.crystals.is_empty());
+    let msg = QueryMsg::CrystalInfo { id: Uint64::new(1) };
+    assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+
+    let msg = QueryMsg::BurnedTokens {
+        start_after: None,
+        limit: None,
+    };
+    let burned: BurnedTokensResponse<Crystal> =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let reasons: Vec<_> = burned
+        .tokens
+        .iter()
+        .map(|b| (b.token.kind.as_str(), b.reason))
+        .collect();
+    assert_eq!(
+        reasons,
+        vec![
+            ("fire", BurnReason::Forge),
+            ("ice", BurnReason::Forge),
+            ("storm", BurnReason::Forge),
+            ("divine", BurnReason::Forge),
+            ("udin", BurnReason::Forge),
+            ("fire", BurnReason::User),
+        ]
+    );
+}
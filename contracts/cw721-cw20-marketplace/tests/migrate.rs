@@ -0,0 +1,106 @@
+use cosmwasm_std::{Addr, Decimal, DepsMut, Env, MessageInfo, Response};
+use cw2::set_contract_version;
+use cw721_cw20_marketplace::execute::{execute, instantiate, migrate};
+use cw721_cw20_marketplace::msg::{InstantiateMsg, MigrateMsg};
+use cw721_cw20_marketplace::query::query;
+use cw721_cw20_marketplace::ContractError;
+use cw_multi_test::{App, ContractWrapper, Executor};
+use stake_dragons_common::migrate::MigrateError;
+
+const ADMIN: &str = "admin";
+
+type InstantiateFn =
+    fn(DepsMut, Env, MessageInfo, InstantiateMsg) -> Result<Response, ContractError>;
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        admin: ADMIN.to_string(),
+        nft_addr: "nft".to_string(),
+        allowed_native: Some("ujuno".to_string()),
+        allowed_cw20: None,
+        fee_percentage: Decimal::percent(3),
+        collector_addr: "collector".to_string(),
+    }
+}
+
+/// Instantiates a marketplace that reports the 0.3.0 release.
+fn instantiate_v0_3(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    set_contract_version(deps.storage, "crates.io:cw721-marketplace", "0.3.0")?;
+    Ok(res)
+}
+
+/// Instantiates a contract that reports another contract's cw2 name.
+fn instantiate_other(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    set_contract_version(deps.storage, "crates.io:dragon-mint", "0.1.0")?;
+    Ok(res)
+}
+
+fn deploy(app: &mut App, instantiate_fn: InstantiateFn) -> Addr {
+    let code_id = app.store_code(Box::new(ContractWrapper::new(
+        execute,
+        instantiate_fn,
+        query,
+    )));
+    app.instantiate_contract(
+        code_id,
+        Addr::unchecked(ADMIN),
+        &instantiate_msg(),
+        &[],
+        "flex",
+        Some(ADMIN.to_string()),
+    )
+    .unwrap()
+}
+
+fn current_code(app: &mut App) -> u64 {
+    app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+    ))
+}
+
+#[test]
+fn test_migrate() {
+    let mut app = App::default();
+    let marketplace = deploy(&mut app, instantiate_v0_3);
+    let current = current_code(&mut app);
+
+    let res = app
+        .migrate_contract(Addr::unchecked(ADMIN), marketplace, &MigrateMsg {}, current)
+        .unwrap();
+    let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|a| a.key == "from_version" && a.value == "0.3.0"));
+}
+
+#[test]
+fn migrate_refuses_other_contracts() {
+    let mut app = App::default();
+    let other = deploy(&mut app, instantiate_other);
+    let current = current_code(&mut app);
+
+    let err = app
+        .migrate_contract(Addr::unchecked(ADMIN), other, &MigrateMsg {}, current)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap().to_string(),
+        ContractError::from(MigrateError::WrongContract {
+            stored: "crates.io:dragon-mint".to_string(),
+            expected: "crates.io:cw721-marketplace".to_string(),
+        })
+        .to_string()
+    );
+}
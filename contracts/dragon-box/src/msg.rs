@@ -2,45 +2,17 @@ use cosmwasm_std::{Binary, Uint64};
 use cw_utils::Expiration;
 
 use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub base: Cw721InstantiateMsg,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-
-// see: https://docs.opensea.io/docs/metadata-standards
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub image: Option<String>,
-    pub image_data: Option<String>,
-    pub external_url: Option<String>,
-    pub description: Option<String>,
-    pub name: Option<String>,
-    pub attributes: Option<Vec<Trait>>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-}
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
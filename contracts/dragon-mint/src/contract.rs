@@ -1,17 +1,23 @@
 use crate::error::ContractError;
 use crate::msg::{
-    CollectionInfoResponse, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, QueryMsg, ClaimMessage, Claim, StateResponse
+    CollectionInfoResponse, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, MigrateMsg, QueryMsg, ClaimMessage, Claim, StateResponse,
+    Trait,
 };
 use crate::state::{
-    CollectionInfo, State, Dragon, DragonListResponse, DragonResponse, COLLECTION_INFO, DRAGON_INFO,
-    DRAGON_INFO_SEQ, STATE, MIN_STAKE_TIME,
+    default_emission_rates, CollectionInfo, State, Dragon, DragonInfoListResponse, DragonListResponse, DragonResponse,
+    DragonRewardResponse, BurnersResponse, Level, LevelsResponse, Lock, LockTier, LockTiersResponse, PendingRewardsResponse, TokenRewardResponse,
+    UserDragonResponse, BURNED_DRAGONS, COLLECTION_INFO, dragon_info, DRAGON_INFO_SEQ, LEGACY_DRAGON_INFO, STATE, MIN_STAKE_TIME, LOCK_TIERS,
+    LEVELS, SECONDS_PER_XP, BURNERS,
 };
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::WasmMsg::Execute;
-use cosmwasm_std::{to_binary, Binary, CosmosMsg, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Uint64, Uint128, SubMsg};
-use cw2::set_contract_version;
+use cosmwasm_std::{to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint64, Uint128, SubMsg};
+use cw2::{get_contract_version, set_contract_version};
+use cw721::{AllNftInfoResponse, Cw721Query, NftInfoResponse};
+use std::collections::BTreeMap;
 use std::ops::Add;
+use std::str::FromStr;
 use std::ptr::null;
 
 pub type Cw721Contract<'a> = cw721_base::Cw721Contract<'a, Extension, Empty>;
@@ -19,6 +25,25 @@ pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
 use crate::helper::generate_dragon_birth_msg;
 use cw_storage_plus::Bound;
 use cw_utils::Expiration;
+use stake_dragons_common::batch::check_batch;
+use stake_dragons_common::burns::{query_burned_tokens, BurnReason, BurnedToken};
+use stake_dragons_common::claims::{
+    can_claim, query_claim_settings, reward_recipient, update_claim_settings,
+};
+use stake_dragons_common::migrate::{
+    check_migration, ensure_migrated, save_migration_cursor, Version, DEFAULT_MIGRATION_LIMIT,
+    MIGRATION_CURSOR,
+};
+use stake_dragons_common::nft::{update_metadata, with_live_traits, MetadataUpdate};
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
+use stake_dragons_common::supply::{check_supply, query_supply, update_supply, SupplyMsg};
+use stake_dragons_common::reward::{
+    accrued_reward, query_emission_rates, query_rate_history, save_emission_rates, Accrual,
+    EmissionRate, SECONDS_PER_DAY,
+};
+use stake_dragons_common::stats::{
+    query_staking_stats, record_burn, record_claim, record_mint, record_status_change, StakeStatus,
+};
 use schemars::_serde_json::Value;
 use schemars::_serde_json::Value::Null;
 
@@ -56,6 +81,11 @@ pub fn instantiate(
     COLLECTION_INFO.save(deps.storage, &collection_info)?;
     DRAGON_INFO_SEQ.save(deps.storage, &Uint64::zero())?;
     MIN_STAKE_TIME.save(deps.storage, &Uint64::new(1209600))?;
+    save_emission_rates(
+        deps.storage,
+        &msg.emission_rates.unwrap_or_else(default_emission_rates),
+        0,
+    )?;
     Ok(Response::new().add_attribute("method", "instantiate"))
 }
 
@@ -66,20 +96,67 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    ensure_migrated(deps.storage)?;
     match msg {
-        ExecuteMsg::UpdateOwner {new_owner} => execute_update_owner(deps, info, new_owner),
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, env, info, msg),
+        ExecuteMsg::UpdateSupply(msg) => execute_update_supply(deps, info, msg),
+        ExecuteMsg::UpdateClaimSettings(msg) => {
+            Ok(update_claim_settings(deps.storage, deps.api, &info.sender, msg)?)
+        }
         ExecuteMsg::UpdateRewardContractAddress {new_address} => execute_update_reward_contract_address(deps, info, new_address),
         ExecuteMsg::UpdateMinStakeTime {time} => execute_update_min_stake_time(deps, info, time),
+        ExecuteMsg::UpdateEmissionRates { rates } => {
+            execute_update_emission_rates(deps, env, info, rates)
+        }
+        ExecuteMsg::UpdateLockTiers { tiers } => execute_update_lock_tiers(deps, info, tiers),
+        ExecuteMsg::UpdateLevels { levels } => execute_update_levels(deps, info, levels),
+        ExecuteMsg::UpdateBurners { burners } => execute_update_burners(deps, info, burners),
         ExecuteMsg::Mint(msg) => execute_mint(deps, env, info, msg),
         ExecuteMsg::PlantEgg { token_id } => execute_plant_egg(deps, info, env, token_id),
-        ExecuteMsg::StakeDragon { token_id } => execute_stake_dragon(deps, info, env, token_id),
+        ExecuteMsg::StakeDragon {
+            token_id,
+            lock_days,
+        } => execute_stake_dragon(deps, info, env, token_id, lock_days),
         ExecuteMsg::StartUnstakingProcess { token_id } => {
             execute_start_unstake_process(deps, info, env, token_id)
         }
         ExecuteMsg::UnstakeDragon { token_id } => execute_unstake_dragon(deps, info, env, token_id),
-        ExecuteMsg::ClaimReward { token_id } => execute_claim_reward(deps, info, env, token_id),
-        ExecuteMsg::Claim { token_id } => execute_claim(deps, info, env, token_id),
-        ExecuteMsg::Burn { token_id } => execute_burn(deps, env, info, token_id),
+        ExecuteMsg::CancelUnstaking { token_id } => {
+            execute_cancel_unstaking(deps, info, env, token_id)
+        }
+        ExecuteMsg::ClaimReward { token_id } | ExecuteMsg::Claim { token_id } => {
+            execute_claim_reward(deps, info, env, token_id)
+        }
+        ExecuteMsg::BatchStakeDragon {
+            token_ids,
+            lock_days,
+        } => execute_batch(
+            deps,
+            info,
+            env,
+            token_ids,
+            "batch_stake_dragon",
+            |storage, dragon, sender, env| stake_dragon(storage, dragon, sender, env, lock_days),
+        ),
+        ExecuteMsg::BatchStartUnstakingProcess { token_ids } => execute_batch(
+            deps,
+            info,
+            env,
+            token_ids,
+            "batch_start_unstaking_process",
+            start_unstake_process,
+        ),
+        ExecuteMsg::BatchUnstakeDragon { token_ids } => {
+            execute_batch(deps, info, env, token_ids, "batch_unstake_dragon", unstake_dragon)
+        }
+        ExecuteMsg::BatchClaimReward { token_ids } => {
+            execute_batch_claim_reward(deps, info, env, token_ids)
+        }
+        ExecuteMsg::ClaimAllRewards { owner } => execute_claim_all_rewards(deps, info, env, owner),
+        ExecuteMsg::Burn { token_id, reason } => execute_burn(deps, env, info, token_id, reason),
+        ExecuteMsg::UpdateMetadata { token_id, metadata } => {
+            execute_update_metadata(deps, info, token_id, metadata)
+        }
         ExecuteMsg::TransferNft {
             recipient,
             token_id,
@@ -104,18 +181,41 @@ pub fn execute(
     }
 }
 
-fn execute_update_owner(
+fn execute_update_supply(
     deps: DepsMut,
     info: MessageInfo,
-    new_owner: String,
+    msg: SupplyMsg,
 ) -> Result<Response, ContractError> {
-    let mut state = STATE.load(deps.storage)?;
+    let state = STATE.load(deps.storage)?;
     if info.sender.to_string() != state.owner {
         return Err(ContractError::Unauthorized {});
     }
-    state.owner = new_owner;
+    let mut collection_info = COLLECTION_INFO.load(deps.storage)?;
+    collection_info.size = update_supply(deps.storage, collection_info.size, msg)?;
+    COLLECTION_INFO.save(deps.storage, &collection_info)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_supply")
+        .add_attribute("size", collection_info.size))
+}
+
+fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
     STATE.save(deps.storage, &state)?;
-    Ok(Response::default().add_attribute("new_owner", state.owner))
+    Ok(res)
 }
 
 fn execute_update_reward_contract_address(
@@ -146,28 +246,164 @@ fn execute_update_min_stake_time(
     Ok(Response::default().add_attribute("min_stake_time", time))
 }
 
-fn execute_burn(
+fn execute_update_emission_rates(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rates: Vec<EmissionRate>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender.to_string() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    save_emission_rates(deps.storage, &rates, env.block.time.seconds())?;
+    let mut res = Response::default().add_attribute("action", "update_emission_rates");
+    for rate in rates {
+        res = res.add_attribute(rate.kind, rate.per_day);
+    }
+    Ok(res)
+}
+
+fn execute_update_lock_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    tiers: Vec<LockTier>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender.to_string() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if tiers
+        .iter()
+        .any(|tier| tier.days == 0 || tier.multiplier.is_zero())
+    {
+        return Err(ContractError::InvalidLockTier {});
+    }
+    let old: Vec<u64> = LOCK_TIERS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<_>>()?;
+    for days in old {
+        LOCK_TIERS.remove(deps.storage, days);
+    }
+    let mut res = Response::default().add_attribute("action", "update_lock_tiers");
+    for tier in tiers {
+        LOCK_TIERS.save(deps.storage, tier.days, &tier.multiplier)?;
+        res = res.add_attribute(format!("{}_days", tier.days), tier.multiplier.to_string());
+    }
+    Ok(res)
+}
+
+fn execute_update_levels(
+    deps: DepsMut,
+    info: MessageInfo,
+    levels: Vec<Level>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender.to_string() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut last = 0;
+    for level in &levels {
+        if level.xp <= last {
+            return Err(ContractError::InvalidLevels {});
+        }
+        last = level.xp;
+    }
+    LEVELS.save(deps.storage, &levels)?;
+    let mut res = Response::default().add_attribute("action", "update_levels");
+    for (number, level) in levels.iter().enumerate() {
+        res = res.add_attribute(format!("level_{}_xp", number + 1), level.xp.to_string());
+    }
+    Ok(res)
+}
+
+fn execute_update_burners(
     deps: DepsMut,
+    info: MessageInfo,
+    burners: Vec<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let burners = burners
+        .iter()
+        .map(|burner| deps.api.addr_validate(burner))
+        .collect::<StdResult<Vec<_>>>()?;
+    BURNERS.save(deps.storage, &burners)?;
+    let mut res = Response::default().add_attribute("action", "update_burners");
+    for burner in burners {
+        res = res.add_attribute("burner", burner);
+    }
+    Ok(res)
+}
+
+fn execute_update_metadata(
+    deps: DepsMut,
+    info: MessageInfo,
+    token_id: String,
+    metadata: MetadataUpdate,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender.to_string() != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    update_metadata(deps.storage, &token_id, metadata)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_metadata")
+        .add_attribute("token_id", token_id))
+}
+
+fn execute_burn(
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     token_id: String,
+    reason: Option<BurnReason>,
 ) -> Result<Response, ContractError> {
+    let id = token_id
+        .parse::<u64>()
+        .map_err(|err| StdError::parse_err("u64", err))?;
+    let dragon = dragon_info().load(deps.storage, id)?;
+    if dragon.is_staked {
+        return Err(ContractError::StakedDragonCantBeBurned {});
+    }
+    let reason = reason.unwrap_or(BurnReason::User);
+    if reason != BurnReason::User && !can_burn_for(deps.storage, &info.sender)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    let burned_at = Uint64::new(env.block.time.seconds());
     let msg = Cw721ExecuteMsg::Burn { token_id };
-    Cw721Contract::default()
-        .execute(deps, env, info, msg)
-        .unwrap();
-    Ok(Response::new())
+    let res = Cw721Contract::default().execute(deps.branch(), env, info, msg)?;
+    dragon_info().remove(deps.storage, id)?;
+    let burned = BurnedToken {
+        token: dragon,
+        reason,
+        burned_at,
+    };
+    BURNED_DRAGONS.save(deps.storage, id, &burned)?;
+    record_burn(deps.storage)?;
+    Ok(res)
+}
+
+/// Whether `sender` may burn dragons it consumes, for a reason other than `user`.
+fn can_burn_for(storage: &dyn Storage, sender: &Addr) -> StdResult<bool> {
+    if *sender == Cw721Contract::default().minter.load(storage)? {
+        return Ok(true);
+    }
+    let burners = BURNERS.may_load(storage)?.unwrap_or_default();
+    Ok(burners.contains(sender))
 }
 
 fn execute_mint(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     mut msg: CustomMintMsg,
 ) -> Result<Response, ContractError> {
     let mut kind = String::new();
     let mut ovulation_period: u64 = 0;
-    let mut daily_income: String = String::from("");
+    let mut daily_income = Decimal::zero();
     for item in &msg.extension {
         // iterate immutably
         let trait_type: String = item.clone().trait_type;
@@ -175,11 +411,17 @@ fn execute_mint(
 
         match &trait_type[..] {
             "kind" => kind = value,
-            "ovulation_period" => ovulation_period = value.parse::<u64>().unwrap(),
-            "daily_income" => daily_income = value,
+            "ovulation_period" => {
+                ovulation_period = value
+                    .parse::<u64>()
+                    .map_err(|_| ContractError::InvalidTraitValue { trait_type, value })?
+            }
+            "daily_income" => daily_income = parse_daily_income(&value)?,
             _ => return Err(ContractError::UnexpectedTraitType { trait_type }),
         }
     }
+    let cap = COLLECTION_INFO.load(deps.storage)?.size;
+    check_supply(DRAGON_INFO_SEQ.load(deps.storage)?, cap)?;
     let id =
         DRAGON_INFO_SEQ.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint64::new(1))))?;
     let dragon = Dragon {
@@ -195,13 +437,21 @@ fn execute_mint(
         unstaking_start_time: Uint64::zero(),
         unstaking_process: false,
         reward_end_time: Uint64::zero(),
+        lock: None,
+        carried_reward: Uint128::zero(),
+        xp: 0,
+        level: 0,
+        income_bonus: Decimal::zero(),
+        xp_start_time: Uint64::zero(),
     };
-    DRAGON_INFO.save(deps.storage, id.u64(), &dragon)?;
+    dragon_info().save(deps.storage, id.u64(), &dragon)?;
+    record_mint(deps.storage)?;
     msg.base.token_id = id.to_string();
     let mint_msg = Cw721ExecuteMsg::Mint(msg.base.clone());
     Cw721Contract::default()
-        .execute(deps, env, info, mint_msg)
+        .execute(deps.branch(), env, info, mint_msg)
         .unwrap();
+    sync_metadata(deps.storage, &dragon)?;
     Ok(Response::default()
         .add_attribute("new owner", dragon.owner.clone())
         .add_attribute("dragon id", dragon.token_id)
@@ -210,7 +460,7 @@ fn execute_mint(
             "dragon ovulation_period",
             dragon.ovulation_period.to_string(),
         )
-        .add_attribute("dragon daily income", dragon.daily_income)
+        .add_attribute("dragon daily income", dragon.daily_income.to_string())
         .add_attribute("hatch", dragon.hatch.to_string())
         .add_attribute("stake_start_time", dragon.stake_start_time.to_string())
         .add_attribute("reward_start_time", dragon.reward_start_time.to_string())
@@ -220,13 +470,20 @@ fn execute_mint(
         .add_attribute("reward_end_time, {}", dragon.unstaking_start_time.to_string()))
 }
 
+fn parse_daily_income(value: &str) -> Result<Decimal, ContractError> {
+    Decimal::from_str(value).map_err(|_| ContractError::InvalidTraitValue {
+        trait_type: "daily_income".to_string(),
+        value: value.to_string(),
+    })
+}
+
 fn execute_plant_egg(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut dragon = DRAGON_INFO.load(deps.storage, token_id.u64())?;
+    let mut dragon = dragon_info().load(deps.storage, token_id.u64())?;
     if dragon.owner != info.sender.to_string() {
         return Err(ContractError::Unauthorized {});
     }
@@ -238,7 +495,7 @@ fn execute_plant_egg(
     } else {
         return Err(ContractError::OvulationInProgress {});
     }
-    DRAGON_INFO.save(deps.storage, token_id.u64(), &dragon)?;
+    dragon_info().save(deps.storage, token_id.u64(), &dragon)?;
 
     let collection = COLLECTION_INFO.load(deps.storage)?;
     let msg = generate_dragon_birth_msg(token_id.to_string(), info.sender.to_string())?;
@@ -259,24 +516,36 @@ fn execute_transfer_nft(
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
     let msg = Cw721ExecuteMsg::TransferNft {
-        recipient: recipient.clone(),
+        recipient,
         token_id: token_id.to_string(),
     };
-    let mut dragon = DRAGON_INFO.load(deps.storage, token_id.u64())?;
-    let is_staked = dragon.clone().is_staked;
-    if is_staked {
+    move_dragon(deps, env, info, token_id.u64(), msg)
+}
+
+/// Runs a cw721 transfer or send, which checks that the sender owns the
+/// token or is approved for it, then copies the new cw721 owner into the
+/// dragon record. Staked dragons can't be moved.
+fn move_dragon(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    msg: Cw721ExecuteMsg,
+) -> Result<Response, ContractError> {
+    let mut dragon = dragon_info().load(deps.storage, id)?;
+    if dragon.is_staked {
         return Err(ContractError::StakedDragonCantBeTransferred {});
     }
-    let _is_owner = dragon.clone().is_owner(info.sender.to_string())?;
-    let valid_recipient = deps.api.addr_validate(&*recipient)?;
-    dragon.owner = valid_recipient.to_string();
-    DRAGON_INFO.save(deps.storage, token_id.u64(), &dragon)?;
-    Cw721Contract::default()
-        .execute(deps, env, info.clone(), msg)
-        .unwrap();
-
-    Ok(Response::default()
-        .add_attribute("old owner", info.sender.to_string())
+    let old_owner = dragon.owner.clone();
+    let res = Cw721Contract::default().execute(deps.branch(), env, info, msg)?;
+    dragon.owner = Cw721Contract::default()
+        .tokens
+        .load(deps.storage, &dragon.token_id)?
+        .owner
+        .into_string();
+    dragon_info().save(deps.storage, id, &dragon)?;
+    Ok(res
+        .add_attribute("old owner", old_owner)
         .add_attribute("new owner", dragon.owner))
 }
 
@@ -285,32 +554,61 @@ pub fn execute_stake_dragon(
     info: MessageInfo,
     env: Env,
     token_id: Uint64,
+    lock_days: Option<u64>,
 ) -> Result<Response, ContractError> {
-    let mut dragon = DRAGON_INFO.load(deps.storage, token_id.u64())?;
+    let mut dragon = dragon_info().load(deps.storage, token_id.u64())?;
+    let was = dragon.stake_status();
+    stake_dragon(deps.storage, &mut dragon, &info.sender, &env, lock_days)?;
+    save_dragon(deps.storage, token_id.u64(), was, &dragon)?;
+    Ok(Response::default()
+        .add_attribute("token_id", dragon.token_id.to_string())
+        .add_attribute("is_staked", dragon.is_staked.to_string())
+        .add_attribute("start_time", dragon.stake_start_time)
+        .add_attribute("reward_start_time", dragon.reward_start_time)
+        .add_attribute("hatched dragon", token_id.to_string())
+        .add_attribute("hatch value", dragon.hatch.to_string()))
+}
+
+fn stake_dragon(
+    storage: &dyn Storage,
+    dragon: &mut Dragon,
+    sender: &Addr,
+    env: &Env,
+    lock_days: Option<u64>,
+) -> Result<(), ContractError> {
     if dragon.is_staked {
         return Err(ContractError::DragonAlreadyStaked {});
     }
-    let _is_owner = dragon.clone().is_owner(info.sender.to_string())?;
+    let _is_owner = dragon.clone().is_owner(sender.to_string())?;
     dragon.is_staked = true;
     let now = Uint64::new(env.block.time.seconds());
     dragon.stake_start_time = now;
     dragon.reward_start_time = now;
+    dragon.xp_start_time = now;
+    progress(storage, dragon, now.u64())?;
     //get today as seconds
     let today_in_seconds = Uint64::new(env.block.time.seconds());
     //calculate the second that it will take using the ovulation period
     //1 day -> 86400 seconds
-    let ovulation_period_in_seconds: Uint64 = Uint64::new(dragon.ovulation_period);
+    let ovulation_period_in_seconds: Uint64 = Uint64::new(ovulation_period(storage, dragon)?);
     let total_seconds_to_add = ovulation_period_in_seconds.checked_mul(Uint64::new(86400))?;
     let hatch_time: Uint64 = total_seconds_to_add.checked_add(Uint64::from(today_in_seconds))?;
     dragon.hatch = hatch_time;
-    DRAGON_INFO.save(deps.storage, token_id.u64(), &dragon)?;
-    Ok(Response::default()
-        .add_attribute("token_id", dragon.token_id.to_string())
-        .add_attribute("is_staked", dragon.is_staked.to_string())
-        .add_attribute("start_time", dragon.stake_start_time)
-        .add_attribute("reward_start_time", dragon.reward_start_time)
-        .add_attribute("hatched dragon", token_id.to_string())
-        .add_attribute("hatch value", dragon.hatch.to_string()))
+    dragon.lock = match lock_days {
+        Some(days) => {
+            let multiplier = LOCK_TIERS
+                .may_load(storage, days)?
+                .ok_or(ContractError::UnknownLockTier { days })?;
+            let lock_seconds = Uint64::new(days).checked_mul(Uint64::new(SECONDS_PER_DAY))?;
+            Some(Lock {
+                days,
+                multiplier,
+                end_time: now.checked_add(lock_seconds)?,
+            })
+        }
+        None => None,
+    };
+    Ok(())
 }
 
 fn execute_start_unstake_process(
@@ -319,57 +617,98 @@ fn execute_start_unstake_process(
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut dragon = DRAGON_INFO.load(deps.storage, token_id.u64())?;
+    let mut dragon = dragon_info().load(deps.storage, token_id.u64())?;
+    let was = dragon.stake_status();
+    start_unstake_process(deps.storage, &mut dragon, &info.sender, &env)?;
+    save_dragon(deps.storage, token_id.u64(), was, &dragon)?;
+    Ok(Response::default()
+        .add_attribute("token_id", dragon.clone().token_id.to_string())
+        .add_attribute("unstaking_start_time", dragon.unstaking_start_time)
+        .add_attribute("reward_end_time", dragon.reward_end_time))
+}
+
+fn start_unstake_process(
+    storage: &dyn Storage,
+    dragon: &mut Dragon,
+    sender: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
     if dragon.unstaking_process {
         return Err(ContractError::OngoingUnstakingProcess {});
     }
     if !dragon.is_staked {
         return Err(ContractError::DragonNotStaked {});
     }
-    if dragon.owner != info.sender.to_string() {
+    if dragon.owner != sender.to_string() {
         return Err(ContractError::Unauthorized {});
     }
+    if let Some(lock) = &dragon.lock {
+        if env.block.time.seconds() < lock.end_time.u64() {
+            return Err(ContractError::DragonLocked {
+                until: lock.end_time,
+            });
+        }
+    }
+    progress(storage, dragon, env.block.time.seconds())?;
+    dragon.xp_start_time = Uint64::zero();
     dragon.unstaking_process = true;
     dragon.unstaking_start_time = Uint64::new(env.block.time.seconds());
     dragon.reward_end_time = Uint64::new(env.block.time.seconds());
-    DRAGON_INFO.save(deps.storage, token_id.u64(), &dragon)?;
-    Ok(Response::default()
-        .add_attribute("token_id", dragon.clone().token_id.to_string())
-        .add_attribute("unstaking_start_time", dragon.unstaking_start_time)
-        .add_attribute("reward_end_time", dragon.reward_end_time))
+    Ok(())
 }
 
-fn execute_unstake_dragon(
+fn execute_cancel_unstaking(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut dragon = DRAGON_INFO.load(deps.storage, token_id.u64())?;
-    if !dragon.is_staked {
-        return Err(ContractError::DragonNotStaked {});
-    }
-    if dragon.owner != info.sender.to_string() {
+    let mut dragon = dragon_info().load(deps.storage, token_id.u64())?;
+    let was = dragon.stake_status();
+    cancel_unstaking(deps.storage, &mut dragon, &info.sender, &env)?;
+    save_dragon(deps.storage, token_id.u64(), was, &dragon)?;
+    Ok(Response::default()
+        .add_attribute("action", "cancel_unstaking")
+        .add_attribute("token_id", dragon.token_id.clone())
+        .add_attribute("unstaking_process", dragon.unstaking_process.to_string())
+        .add_attribute("reward_start_time", dragon.reward_start_time)
+        .add_attribute("hatch", dragon.hatch))
+}
+
+/// Clears the unstaking process of `dragon`. What it earned so far is carried over
+/// and accrual restarts now, so later rate changes can't touch it.
+fn cancel_unstaking(
+    storage: &dyn Storage,
+    dragon: &mut Dragon,
+    sender: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
+    if dragon.owner != sender.to_string() {
         return Err(ContractError::Unauthorized {});
     }
     if !dragon.unstaking_process {
         return Err(ContractError::UnstakingProcessIsNotStarted {});
     }
-    let now = Uint64::new(env.block.time.seconds());
-    let min_stake_time = MIN_STAKE_TIME.load(deps.storage)?;
-    if now.checked_sub(dragon.unstaking_start_time)? < min_stake_time
-        || dragon.unstaking_start_time.is_zero()
-    {
-        return Err(ContractError::MinUnstakingTimeRequired {});
-    }
-    //Hatch
-    dragon.hatch = Uint64::zero();
-    dragon.is_staked = false;
-    dragon.stake_start_time = Uint64::zero();
-    dragon.reward_start_time = Uint64::zero();
+    let now = env.block.time.seconds();
+    dragon.carried_reward = dragon_reward(storage, dragon, now)?;
+    dragon.reward_start_time = Uint64::new(now);
+    dragon.xp_start_time = Uint64::new(now);
     dragon.unstaking_process = false;
     dragon.unstaking_start_time = Uint64::zero();
-    DRAGON_INFO.save(deps.storage, token_id.u64(), &dragon)?;
+    dragon.reward_end_time = Uint64::zero();
+    Ok(())
+}
+
+fn execute_unstake_dragon(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    token_id: Uint64,
+) -> Result<Response, ContractError> {
+    let mut dragon = dragon_info().load(deps.storage, token_id.u64())?;
+    let was = dragon.stake_status();
+    unstake_dragon(deps.storage, &mut dragon, &info.sender, &env)?;
+    save_dragon(deps.storage, token_id.u64(), was, &dragon)?;
     Ok(Response::default()
         .add_attribute("token_id", dragon.clone().token_id.to_string())
         .add_attribute("is_staked", dragon.clone().is_staked.to_string())
@@ -391,92 +730,306 @@ fn execute_unstake_dragon(
         ))
 }
 
+fn unstake_dragon(
+    storage: &dyn Storage,
+    dragon: &mut Dragon,
+    sender: &Addr,
+    env: &Env,
+) -> Result<(), ContractError> {
+    if !dragon.is_staked {
+        return Err(ContractError::DragonNotStaked {});
+    }
+    if dragon.owner != sender.to_string() {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !dragon.unstaking_process {
+        return Err(ContractError::UnstakingProcessIsNotStarted {});
+    }
+    let now = Uint64::new(env.block.time.seconds());
+    let min_stake_time = MIN_STAKE_TIME.load(storage)?;
+    if now.checked_sub(dragon.unstaking_start_time)? < min_stake_time
+        || dragon.unstaking_start_time.is_zero()
+    {
+        return Err(ContractError::MinUnstakingTimeRequired {});
+    }
+    //Hatch
+    dragon.hatch = Uint64::zero();
+    dragon.is_staked = false;
+    dragon.stake_start_time = Uint64::zero();
+    dragon.reward_start_time = Uint64::zero();
+    dragon.carried_reward = Uint128::zero();
+    dragon.unstaking_process = false;
+    dragon.unstaking_start_time = Uint64::zero();
+    dragon.lock = None;
+    Ok(())
+}
+
 fn execute_claim_reward(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
     token_id: Uint64,
 ) -> Result<Response, ContractError> {
-    let mut dragon = DRAGON_INFO.load(deps.storage, token_id.u64())?;
+    let mut dragon = dragon_info().load(deps.storage, token_id.u64())?;
+    let was = dragon.stake_status();
+    let reward = claim_dragon_reward(deps.storage, &mut dragon, &info.sender, &env)?;
+    save_dragon(deps.storage, token_id.u64(), was, &dragon)?;
+    record_claim(deps.storage, reward)?;
+    let recipient = reward_recipient(deps.storage, &dragon.owner)?;
+    let claim_reward_msg = claim_message(deps.storage, &recipient, reward)?;
+    Ok(Response::new().add_submessages(vec![
+        SubMsg::new(claim_reward_msg),
+    ]))
+}
+
+/// Settles the reward of one dragon and restarts its reward window. The owner
+/// or their claim delegate may do this.
+fn claim_dragon_reward(
+    storage: &dyn Storage,
+    dragon: &mut Dragon,
+    sender: &Addr,
+    env: &Env,
+) -> Result<Uint128, ContractError> {
     if !dragon.is_staked {
         return Err(ContractError::DragonNotStaked {});
     }
-    if dragon.owner != info.sender.to_string() {
+    if !can_claim(storage, &dragon.owner, sender)? {
         return Err(ContractError::Unauthorized {});
     }
-    //Reward calculation
-    let now = env.block.time.seconds();
-    let mut reward = Uint128::zero();
+    progress(storage, dragon, env.block.time.seconds())?;
+    let reward = dragon_reward(storage, dragon, env.block.time.seconds())?;
+    dragon.carried_reward = Uint128::zero();
     if dragon.unstaking_process {
-        if dragon.kind == "common".to_string() {
-            let second_difference = Uint128::new(dragon.reward_end_time.u64() as u128).checked_sub(Uint128::new(dragon.reward_start_time.u64() as u128))?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint128::new(500000))?;
-            reward = second_difference_multiplied.checked_div(Uint128::new(86400)).unwrap_or(Uint128::zero());
-        } else {
-            let second_difference = Uint128::new(dragon.reward_end_time.u64() as u128).checked_sub(Uint128::new(dragon.reward_start_time.u64() as u128))?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint128::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint128::new(dragon.daily_income.parse::<u128>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint128::new(86400)).unwrap_or(Uint128::zero());
-        }
+        dragon.reward_start_time = Uint64::zero();
+        dragon.reward_end_time = Uint64::zero();
     } else {
-        if dragon.kind == "common".to_string() {
-            let second_difference = Uint128::new(now as u128).checked_sub(Uint128::new(dragon.reward_start_time.u64() as u128))?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint128::new(500000))?;
-            reward = second_difference_multiplied.checked_div(Uint128::new(86400)).unwrap_or(Uint128::zero());
-        } else {
-            let second_difference = Uint128::new(now as u128).checked_sub(Uint128::new(dragon.reward_start_time.u64() as u128))?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint128::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint128::new(dragon.daily_income.parse::<u128>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint128::new(86400)).unwrap_or(Uint128::zero());
-        }
+        dragon.reward_start_time = Uint64::new(env.block.time.seconds());
     }
-    let state = STATE.load(deps.storage)?;
+    Ok(reward)
+}
+
+/// Asks the reward contract to pay `amount` to `recipient`.
+fn claim_message(
+    storage: &dyn Storage,
+    recipient: &Addr,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    let state = STATE.load(storage)?;
     let msg = ClaimMessage {
         claim: Claim {
-            recipient: info.sender.to_string(),
-            amount: reward,
+            recipient: recipient.to_string(),
+            amount,
         }
     };
-
-    let claim_reward_msg = CosmosMsg::Wasm(Execute {
+    Ok(CosmosMsg::Wasm(Execute {
         contract_addr: state.reward_contract_address,
         msg: to_binary(&msg)?,
         funds: vec![],
-    });
+    }))
+}
+
+/// Saves `dragon`, moves the staking counters along from its status `was` and
+/// keeps its cw721 metadata in step with its level.
+fn save_dragon(storage: &mut dyn Storage, id: u64, was: StakeStatus, dragon: &Dragon) -> StdResult<()> {
+    record_status_change(storage, &dragon.kind, was, dragon.stake_status())?;
+    dragon_info().save(storage, id, dragon)?;
+    sync_metadata(storage, dragon)
+}
+
+/// Writes the level and XP of `dragon` into the attributes of its cw721 token,
+/// if it still has one.
+fn sync_metadata(storage: &mut dyn Storage, dragon: &Dragon) -> StdResult<()> {
+    let tokens = Cw721Contract::default().tokens;
+    let mut token = match tokens.may_load(storage, &dragon.token_id)? {
+        Some(token) => token,
+        None => return Ok(()),
+    };
+    let metadata = token.extension.get_or_insert_with(Default::default);
+    let before = metadata.clone();
+    metadata.set_trait("level", dragon.level.to_string());
+    metadata.set_trait("xp", dragon.xp.to_string());
+    if *metadata == before {
+        return Ok(());
+    }
+    tokens.save(storage, &dragon.token_id, &token)
+}
+
+/// Turns the staked time of `dragon` up to `now` into XP and moves it to the
+/// level its XP has reached. A staked dragon keeps what it earned at its old
+/// level up to the moment it reached the new one, and earns the new level's
+/// bonus from then on. Nothing changes while unstaking, as the reward window
+/// is already closed.
+fn progress(storage: &dyn Storage, dragon: &mut Dragon, now: u64) -> StdResult<()> {
     if dragon.unstaking_process {
-        dragon.reward_start_time = Uint64::zero();
-        dragon.reward_end_time = Uint64::zero();
-    } else {
-        dragon.reward_start_time = Uint64::new(env.block.time.seconds());
+        return Ok(());
     }
-    DRAGON_INFO.save(deps.storage, token_id.u64(), &dragon)?;
-    Ok(Response::new().add_submessages(vec![
-        SubMsg::new(claim_reward_msg),
-    ]))
+    let levels = LEVELS.may_load(storage)?.unwrap_or_default();
+    if dragon.is_staked && !dragon.xp_start_time.is_zero() {
+        let (xp, start) = (dragon.xp, dragon.xp_start_time.u64());
+        let hours = now.saturating_sub(start) / SECONDS_PER_XP;
+        for (index, level) in levels.iter().enumerate() {
+            if level.xp > xp && level.xp <= xp + hours {
+                let reached_at = start + (level.xp - xp) * SECONDS_PER_XP;
+                level_up(storage, dragon, index as u32 + 1, level.income_bonus, reached_at)?;
+            }
+        }
+        dragon.xp += hours;
+        dragon.xp_start_time = Uint64::new(start + hours * SECONDS_PER_XP);
+    }
+    // the levels may have been edited since the dragon last moved
+    let reached: Vec<&Level> = levels.iter().filter(|l| l.xp <= dragon.xp).collect();
+    let level = reached.len() as u32;
+    let income_bonus = reached.last().map(|l| l.income_bonus).unwrap_or_default();
+    if level == dragon.level && income_bonus == dragon.income_bonus {
+        return Ok(());
+    }
+    level_up(storage, dragon, level, income_bonus, now)
+}
+
+/// Moves `dragon` to `level` at `at`. A staked dragon carries over what it
+/// earned before, so the new `income_bonus` only applies from `at` on.
+fn level_up(
+    storage: &dyn Storage,
+    dragon: &mut Dragon,
+    level: u32,
+    income_bonus: Decimal,
+    at: u64,
+) -> StdResult<()> {
+    if dragon.is_staked {
+        let at = at.max(dragon.reward_start_time.u64());
+        dragon.carried_reward = dragon_reward(storage, dragon, at)?;
+        dragon.reward_start_time = Uint64::new(at);
+    }
+    dragon.level = level;
+    dragon.income_bonus = income_bonus;
+    Ok(())
+}
+
+/// Ovulation period of `dragon` in days, shortened by its level but never
+/// below a day.
+fn ovulation_period(storage: &dyn Storage, dragon: &Dragon) -> StdResult<u64> {
+    let levels = LEVELS.may_load(storage)?.unwrap_or_default();
+    let reduction = match dragon.level.checked_sub(1) {
+        Some(index) => levels.get(index as usize).map_or(0, |l| l.ovulation_reduction),
+        None => 0,
+    };
+    if reduction == 0 {
+        return Ok(dragon.ovulation_period);
+    }
+    Ok(dragon.ovulation_period.saturating_sub(reduction).max(1))
 }
 
-fn execute_claim(
+/// Loads every dragon of a batch. Duplicates and unknown ids are rejected
+/// before anything is changed.
+fn load_batch(storage: &dyn Storage, token_ids: &[Uint64]) -> Result<Vec<Dragon>, ContractError> {
+    check_batch(token_ids)?;
+    let dragons = token_ids
+        .iter()
+        .map(|token_id| dragon_info().load(storage, token_id.u64()))
+        .collect::<StdResult<_>>()?;
+    Ok(dragons)
+}
+
+/// Applies `update` to every dragon of a batch. All of them are checked before
+/// the first one is saved, so either all change or none do.
+fn execute_batch(
     deps: DepsMut,
     info: MessageInfo,
     env: Env,
-    token_id: Uint64,
+    token_ids: Vec<Uint64>,
+    action: &str,
+    update: impl Fn(&dyn Storage, &mut Dragon, &Addr, &Env) -> Result<(), ContractError>,
 ) -> Result<Response, ContractError> {
-    let mut dragon = DRAGON_INFO.load(deps.storage, token_id.u64())?;
-    if !dragon.is_staked {
-        return Err(ContractError::DragonNotStaked {});
+    let mut dragons = load_batch(deps.storage, &token_ids)?;
+    let was: Vec<_> = dragons.iter().map(Dragon::stake_status).collect();
+    for dragon in dragons.iter_mut() {
+        update(deps.storage, dragon, &info.sender, &env)?;
     }
-    if dragon.owner != info.sender.to_string() {
-        return Err(ContractError::Unauthorized {});
+    let mut res = Response::new().add_attribute("action", action);
+    for ((token_id, dragon), was) in token_ids.iter().zip(&dragons).zip(was) {
+        save_dragon(deps.storage, token_id.u64(), was, dragon)?;
+        res = res.add_attribute("token_id", *token_id);
     }
-    if dragon.unstaking_process {
-        dragon.reward_start_time = Uint64::zero();
-        dragon.reward_end_time = Uint64::zero();
-    } else {
-        dragon.reward_start_time = Uint64::new(env.block.time.seconds());
+    Ok(res)
+}
+
+/// Claims for every dragon of a batch and pays each recipient its total with one
+/// message.
+fn execute_batch_claim_reward(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    token_ids: Vec<Uint64>,
+) -> Result<Response, ContractError> {
+    let mut dragons = load_batch(deps.storage, &token_ids)?;
+    let was: Vec<_> = dragons.iter().map(Dragon::stake_status).collect();
+    let rewards = dragons
+        .iter_mut()
+        .map(|dragon| claim_dragon_reward(deps.storage, dragon, &info.sender, &env))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut total = Uint128::zero();
+    let mut payouts: BTreeMap<Addr, Uint128> = BTreeMap::new();
+    let mut res = Response::new().add_attribute("action", "batch_claim_reward");
+    for (((token_id, dragon), reward), was) in token_ids.iter().zip(&dragons).zip(rewards).zip(was) {
+        save_dragon(deps.storage, token_id.u64(), was, dragon)?;
+        total = total.checked_add(reward)?;
+        let due = payouts
+            .entry(reward_recipient(deps.storage, &dragon.owner)?)
+            .or_default();
+        *due = due.checked_add(reward)?;
+        res = res
+            .add_attribute("token_id", *token_id)
+            .add_attribute("reward", reward);
     }
-    DRAGON_INFO.save(deps.storage, token_id.u64(), &dragon)?;
-    Ok(Response::new().add_attribute("reward_start_time", dragon.reward_start_time)
-        .add_attribute("reward_end_time", dragon.reward_end_time))
+    record_claim(deps.storage, total)?;
+    // a delegate can batch tokens of several owners, each paid separately
+    for (recipient, amount) in payouts {
+        res = res.add_submessage(SubMsg::new(claim_message(deps.storage, &recipient, amount)?));
+    }
+    Ok(res.add_attribute("total_reward", total))
+}
+
+/// Staked dragons of `owner`, found through the owner index.
+fn staked_dragons(storage: &dyn Storage, owner: &str) -> StdResult<Vec<(u64, Dragon)>> {
+    dragon_info()
+        .idx
+        .owner
+        .prefix(owner.to_string())
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, dragon)) if !dragon.is_staked))
+        .collect()
+}
+
+/// Claims for every staked dragon of `owner`, the sender by default, and pays
+/// the total with one message.
+fn execute_claim_all_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+    env: Env,
+    owner: Option<String>,
+) -> Result<Response, ContractError> {
+    let owner = owner.unwrap_or_else(|| info.sender.to_string());
+    let dragons = staked_dragons(deps.storage, &owner)?;
+    if dragons.is_empty() {
+        return Err(ContractError::NoStakedDragons {});
+    }
+    let mut total = Uint128::zero();
+    let mut res = Response::new().add_attribute("action", "claim_all_rewards");
+    for (token_id, mut dragon) in dragons {
+        let was = dragon.stake_status();
+        let reward = claim_dragon_reward(deps.storage, &mut dragon, &info.sender, &env)?;
+        save_dragon(deps.storage, token_id, was, &dragon)?;
+        total = total.checked_add(reward)?;
+        res = res
+            .add_attribute("token_id", token_id.to_string())
+            .add_attribute("reward", reward);
+    }
+    record_claim(deps.storage, total)?;
+    let recipient = reward_recipient(deps.storage, &owner)?;
+    let claim_reward_msg = claim_message(deps.storage, &recipient, total)?;
+    Ok(res
+        .add_submessage(SubMsg::new(claim_reward_msg))
+        .add_attribute("total_reward", total))
 }
 
 fn execute_revoke_all(
@@ -559,87 +1112,306 @@ fn execute_send_nft(
     token_id: String,
     msg: Binary,
 ) -> Result<Response, ContractError> {
+    let id = token_id
+        .parse::<u64>()
+        .map_err(|err| StdError::parse_err("u64", err))?;
     let send_msg = Cw721ExecuteMsg::SendNft {
         contract,
         token_id,
         msg,
     };
-    let res = Cw721Contract::default().execute(deps, env, info, send_msg);
-    if res.is_err() {
-        return Err(ContractError::NftContractError {
-            method: "send nft".to_string(),
-        });
+    move_dragon(deps, env, info, id, send_msg)
+}
+
+/// Rewrites every dragon with its `daily_income` parsed into a `Decimal`. The
+/// version is only bumped once the whole collection is done, so a migration
+/// cut short by its limit carries on when migrated again.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let from = check_migration(
+        &stored.contract,
+        &stored.version,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+    let mut res = Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version);
+    if from < Version::new(0, 2, 0) {
+        let (migrated, done) = migrate_v0_2(deps.storage, env.block.time.seconds(), msg.limit)?;
+        res = res.add_attribute("migrated_dragons", migrated.to_string());
+        if !done {
+            return Ok(res.add_attribute("migration", "unfinished"));
+        }
     }
-    Ok(Response::new())
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(res.add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// 0.2.0 stores `daily_income` as a `Decimal`, indexes dragons by their cw721
+/// owner, keeps emission rates, with their history, in storage and counts the
+/// staking stats from the records; claims made before it aren't counted.
+/// Dragons whose token is gone move to the burned ones. Rewrites up to `limit`
+/// dragons after the stored cursor; returns how many and whether that was the
+/// last of them.
+fn migrate_v0_2(
+    storage: &mut dyn Storage,
+    now: u64,
+    limit: Option<u32>,
+) -> Result<(usize, bool), ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_MIGRATION_LIMIT).max(1) as usize;
+    let start = MIGRATION_CURSOR.may_load(storage)?.map(Bound::exclusive);
+    let mut legacy: Vec<_> = LEGACY_DRAGON_INFO
+        .range(storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<_>>()?;
+    let done = legacy.len() <= limit;
+    legacy.truncate(limit);
+    for (id, dragon) in &legacy {
+        let owner = cw721_owner(storage, &dragon.token_id)?;
+        let burned = owner.is_none();
+        let migrated = Dragon {
+            owner: owner.unwrap_or_else(|| dragon.owner.clone()),
+            token_id: dragon.token_id.clone(),
+            kind: dragon.kind.clone(),
+            ovulation_period: dragon.ovulation_period,
+            hatch: dragon.hatch,
+            daily_income: parse_daily_income(&dragon.daily_income)?,
+            is_staked: dragon.is_staked,
+            stake_start_time: dragon.stake_start_time,
+            reward_start_time: dragon.reward_start_time,
+            unstaking_start_time: dragon.unstaking_start_time,
+            unstaking_process: dragon.unstaking_process,
+            reward_end_time: dragon.reward_end_time,
+            lock: None,
+            carried_reward: Uint128::zero(),
+            xp: 0,
+            level: 0,
+            income_bonus: Decimal::zero(),
+            // staked dragons gain XP from here on
+            xp_start_time: if dragon.is_staked && !dragon.unstaking_process {
+                Uint64::new(now)
+            } else {
+                Uint64::zero()
+            },
+        };
+        record_mint(storage)?;
+        // the stored value is the legacy layout, so don't let the index read it back
+        if burned {
+            dragon_info().replace(storage, *id, None, None)?;
+            let burned = BurnedToken {
+                token: migrated,
+                reason: BurnReason::User,
+                burned_at: Uint64::zero(),
+            };
+            BURNED_DRAGONS.save(storage, *id, &burned)?;
+            record_burn(storage)?;
+        } else {
+            dragon_info().replace(storage, *id, Some(&migrated), None)?;
+            let status = migrated.stake_status();
+            record_status_change(storage, &migrated.kind, StakeStatus::default(), status)?;
+        }
+    }
+    if !save_migration_cursor(storage, legacy.last().map(|(id, _)| *id), done)? {
+        return Ok((legacy.len(), false));
+    }
+    let mut rates = query_emission_rates(storage)?.rates;
+    if rates.is_empty() {
+        rates = default_emission_rates();
+    }
+    // the rates so far have applied from the start
+    save_emission_rates(storage, &rates, 0)?;
+    Ok((legacy.len(), true))
+}
+
+/// Owner of `token_id` according to cw721, which is what the records follow.
+fn cw721_owner(storage: &dyn Storage, token_id: &str) -> StdResult<Option<String>> {
+    Ok(Cw721Contract::default()
+        .tokens
+        .may_load(storage, token_id)?
+        .map(|token| token.owner.into_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::CollectionInfo {} => to_binary(&query_config(deps)?),
-        QueryMsg::DragonInfo { id } => to_binary(&query_dragon(deps, id)?),
+        QueryMsg::Supply {} => to_binary(&query_supply(
+            deps.storage,
+            DRAGON_INFO_SEQ.load(deps.storage)?,
+            Cw721Contract::default().token_count(deps.storage)?,
+            COLLECTION_INFO.load(deps.storage)?.size,
+        )?),
+        QueryMsg::BurnedTokens { start_after, limit } => to_binary(&query_burned_tokens(
+            deps.storage,
+            &BURNED_DRAGONS,
+            start_after,
+            limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize,
+        )?),
+        QueryMsg::DragonInfo { id } => to_binary(&query_dragon(deps, env, id)?),
         QueryMsg::RangeDragons { start_after, limit } => {
-            to_binary(&range_dragons(deps, start_after, limit)?)
+            to_binary(&range_dragons(deps, env, start_after, limit)?)
         }
         QueryMsg::RangeUserDragons {
             start_after,
             limit,
             owner,
-        } => to_binary(&range_user_dragons(deps, start_after, limit, owner)?),
+        } => to_binary(&range_user_dragons(deps, env, start_after, limit, owner)?),
+        QueryMsg::DragonInfoList { start_after, limit } => {
+            to_binary(&query_dragon_info_list(deps, env, start_after, limit)?)
+        }
+        QueryMsg::QueryUserDragon {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_user_dragon(deps, env, owner, start_after, limit)?),
         QueryMsg::CalculateReward { token_id } => {
             to_binary(&query_calculate_reward(deps, env, token_id)?)
         }
+        QueryMsg::PendingRewards { owner } => {
+            to_binary(&query_pending_rewards(deps, env, owner)?)
+        }
+        QueryMsg::StakingStats {} => to_binary(&query_staking_stats(deps.storage)?),
+        QueryMsg::EmissionRates {} => to_binary(&query_emission_rates(deps.storage)?),
+        QueryMsg::RateHistory { kind } => to_binary(&query_rate_history(deps.storage, kind)?),
+        QueryMsg::LockTiers {} => to_binary(&query_lock_tiers(deps)?),
+        QueryMsg::Levels {} => to_binary(&LevelsResponse {
+            levels: LEVELS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::Burners {} => to_binary(&BurnersResponse {
+            burners: BURNERS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
         QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
+        QueryMsg::ClaimSettings { owner } => to_binary(&query_claim_settings(deps.storage, owner)?),
+        QueryMsg::NftInfo { token_id } => to_binary(&query_nft_info(deps, env, token_id)?),
+        QueryMsg::AllNftInfo {
+            token_id,
+            include_expired,
+        } => to_binary(&query_all_nft_info(deps, env, token_id, include_expired)?),
         _ => Cw721Contract::default().query(deps, env, msg.into()),
     }
 }
 
-fn query_calculate_reward(deps: Deps, env: Env, token_id: Uint64) -> StdResult<Uint64> {
-    let dragon = DRAGON_INFO.load(deps.storage, token_id.u64()).unwrap();
-    let now = Uint64::new(env.block.time.seconds());
-    let mut reward = Uint64::zero();
-    if dragon.unstaking_process {
-        if dragon.kind == "common".to_string() {
-            let second_difference = dragon.reward_end_time.checked_sub(dragon.reward_start_time)?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint64::new(500000))?;
-            reward = second_difference_multiplied.checked_div(Uint64::new(86400))?;
-        } else {
-            let second_difference = dragon.reward_end_time.checked_sub(dragon.reward_start_time)?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint64::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint64::new(dragon.daily_income.parse::<u64>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint64::new(86400))?;
-        }
-    } else {
-        if dragon.kind == "common".to_string() {
-            let second_difference = now.checked_sub(dragon.reward_start_time)?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint64::new(500000))?;
-            reward = second_difference_multiplied.checked_div(Uint64::new(86400))?;
-        } else {
-            let second_difference = now.checked_sub(dragon.reward_start_time)?;
-            let second_difference_multiplied = second_difference.checked_mul(Uint64::new(1000000))?;
-            let second_difference_multiplied_daily_income = second_difference_multiplied.checked_mul(Uint64::new(dragon.daily_income.parse::<u64>().unwrap()))?;
-            reward = second_difference_multiplied_daily_income.checked_div(Uint64::new(86400))?;
-        }
+/// cw721 info of `token_id` with the dragon's staking state and level set over
+/// the attributes it was minted with.
+fn query_nft_info(deps: Deps, env: Env, token_id: String) -> StdResult<NftInfoResponse<Extension>> {
+    let live = live_traits(deps.storage, &token_id, env.block.time.seconds())?;
+    let mut info = Cw721Contract::default().nft_info(deps, token_id)?;
+    info.extension = with_live_traits(info.extension, live);
+    Ok(info)
+}
+
+fn query_all_nft_info(
+    deps: Deps,
+    env: Env,
+    token_id: String,
+    include_expired: Option<bool>,
+) -> StdResult<AllNftInfoResponse<Extension>> {
+    let live = live_traits(deps.storage, &token_id, env.block.time.seconds())?;
+    let mut res = Cw721Contract::default().all_nft_info(
+        deps,
+        env,
+        token_id,
+        include_expired.unwrap_or(false),
+    )?;
+    res.info.extension = with_live_traits(res.info.extension, live);
+    Ok(res)
+}
+
+/// What the dragon record of `token_id` says at `now`; nothing for tokens
+/// without one.
+fn live_traits(storage: &dyn Storage, token_id: &str, now: u64) -> StdResult<Vec<Trait>> {
+    let dragon = match token_id.parse() {
+        Ok(id) => dragon_info().may_load(storage, id)?,
+        Err(_) => None,
+    };
+    let mut dragon = match dragon {
+        Some(dragon) => dragon,
+        None => return Ok(vec![]),
+    };
+    progress(storage, &mut dragon, now)?;
+    Ok(vec![
+        Trait::new("staked", dragon.is_staked),
+        Trait::new("unstaking", dragon.unstaking_process),
+        Trait::new("hatch", dragon.hatch),
+        Trait::new("level", dragon.level),
+        Trait::new("xp", dragon.xp),
+    ])
+}
+
+fn query_calculate_reward(deps: Deps, env: Env, token_id: Uint64) -> StdResult<Uint128> {
+    let dragon = dragon_info().load(deps.storage, token_id.u64())?;
+    pending_reward(deps.storage, dragon, env.block.time.seconds())
+}
+
+/// What claiming `dragon` at `now` would pay: like the claim, it first brings
+/// the dragon's XP and level up to date.
+fn pending_reward(storage: &dyn Storage, mut dragon: Dragon, now: u64) -> StdResult<Uint128> {
+    progress(storage, &mut dragon, now)?;
+    dragon_reward(storage, &dragon, now)
+}
+
+/// Reward `dragon` has earned up to `now`, including what it carried over;
+/// both the claim and the query go through here so they always agree. Locked
+/// dragons earn their tier's multiple until the lock ends, levelled ones their
+/// level's bonus.
+fn dragon_reward(storage: &dyn Storage, dragon: &Dragon, now: u64) -> StdResult<Uint128> {
+    let accrual = dragon.accrual();
+    let mut earned = accrued_reward(storage, &dragon.kind, dragon.daily_income, &accrual, now)?;
+    if let Some(lock) = &dragon.lock {
+        let (start, end) = accrual.window(now)?;
+        let lock_end = lock.end_time.u64().clamp(start, end);
+        let locked = Accrual {
+            reward_start_time: Uint64::new(start),
+            reward_end_time: Uint64::new(lock_end),
+            unstaking_process: true,
+        };
+        let locked = accrued_reward(storage, &dragon.kind, dragon.daily_income, &locked, now)?;
+        earned = earned.checked_sub(locked)? + locked * lock.multiplier;
     }
-    Ok(reward)
+    if !dragon.income_bonus.is_zero() {
+        earned = earned * (Decimal::one() + dragon.income_bonus);
+    }
+    Ok(earned.checked_add(dragon.carried_reward)?)
 }
 
-fn query_dragon(deps: Deps, id: Uint64) -> StdResult<DragonResponse> {
-    let dragon = DRAGON_INFO.load(deps.storage, id.u64())?;
-    Ok(DragonResponse {
-        token_id: dragon.token_id,
-        owner: dragon.owner,
-        kind: dragon.kind,
-        ovulation_period: dragon.ovulation_period,
-        hatch: dragon.hatch,
-        daily_income: dragon.daily_income,
-        is_staked: dragon.is_staked,
-        stake_start_time: dragon.stake_start_time,
-        reward_start_time: dragon.reward_start_time,
-        unstaking_start_time: dragon.unstaking_start_time,
-        unstaking_process: dragon.unstaking_process,
-        reward_end_time: dragon.reward_end_time,
-    })
+fn query_lock_tiers(deps: Deps) -> StdResult<LockTiersResponse> {
+    let tiers = LOCK_TIERS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(days, multiplier)| LockTier { days, multiplier }))
+        .collect::<StdResult<_>>()?;
+    Ok(LockTiersResponse { tiers })
+}
+
+fn query_pending_rewards(deps: Deps, env: Env, owner: String) -> StdResult<PendingRewardsResponse> {
+    let now = env.block.time.seconds();
+    let mut res = PendingRewardsResponse {
+        owner: owner.clone(),
+        rewards: vec![],
+        total: Uint128::zero(),
+    };
+    for (token_id, dragon) in staked_dragons(deps.storage, &owner)? {
+        let pending_reward = pending_reward(deps.storage, dragon, now)?;
+        res.total = res.total.checked_add(pending_reward)?;
+        res.rewards.push(TokenRewardResponse {
+            token_id: Uint64::new(token_id),
+            pending_reward,
+        });
+    }
+    Ok(res)
+}
+
+fn query_dragon(deps: Deps, env: Env, id: Uint64) -> StdResult<DragonResponse> {
+    let dragon = dragon_info().load(deps.storage, id.u64())?;
+    dragon_response(deps.storage, dragon, env.block.time.seconds())
+}
+
+/// `dragon` with the XP and level it has at `now`.
+fn dragon_response(storage: &dyn Storage, mut dragon: Dragon, now: u64) -> StdResult<DragonResponse> {
+    progress(storage, &mut dragon, now)?;
+    Ok(dragon.into())
 }
 
 fn query_config(deps: Deps) -> StdResult<CollectionInfoResponse> {
@@ -669,36 +1441,117 @@ const DEFAULT_LIMIT: u32 = 10;
 
 fn range_dragons(
     deps: Deps,
+    env: Env,
     start_after: Option<u64>,
     limit: Option<u32>,
 ) -> StdResult<DragonListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
-    let dragons: StdResult<Vec<_>> = DRAGON_INFO
+    let dragons: StdResult<Vec<_>> = dragon_info()
         .range(deps.storage, start, None, Order::Ascending)
         .take(limit)
         .collect();
+    let now = env.block.time.seconds();
     let res = DragonListResponse {
-        dragons: dragons?.into_iter().map(|l| l.1.into()).collect(),
+        dragons: dragons?
+            .into_iter()
+            .map(|l| dragon_response(deps.storage, l.1, now))
+            .collect::<StdResult<_>>()?,
     };
     Ok(res)
 }
 
 fn range_user_dragons(
     deps: Deps,
+    env: Env,
     start_after: Option<u64>,
     limit: Option<u32>,
     owner: String,
 ) -> StdResult<DragonListResponse> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
     let start = start_after.map(Bound::exclusive);
-    let dragons: StdResult<Vec<_>> = DRAGON_INFO
+    let dragons: StdResult<Vec<_>> = dragon_info()
+        .idx
+        .owner
+        .prefix(owner)
         .range(deps.storage, start, None, Order::Ascending)
-        .filter(|r| r.as_ref().unwrap().1.owner == owner)
         .take(limit)
         .collect();
+    let now = env.block.time.seconds();
     let res = DragonListResponse {
-        dragons: dragons?.into_iter().map(|l| l.1.into()).collect(),
+        dragons: dragons?
+            .into_iter()
+            .map(|l| dragon_response(deps.storage, l.1, now))
+            .collect::<StdResult<_>>()?,
+    };
+    Ok(res)
+}
+
+/// `dragon` with what it can claim at `now`. Only staked dragons earn.
+fn with_reward(storage: &dyn Storage, mut dragon: Dragon, now: u64) -> StdResult<DragonRewardResponse> {
+    progress(storage, &mut dragon, now)?;
+    let pending_reward = if dragon.is_staked {
+        dragon_reward(storage, &dragon, now)?
+    } else {
+        Uint128::zero()
+    };
+    Ok(DragonRewardResponse {
+        dragon: dragon.into(),
+        pending_reward,
+    })
+}
+
+fn query_dragon_info_list(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DragonInfoListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let now = env.block.time.seconds();
+    let dragons = dragon_info()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| with_reward(deps.storage, item?.1, now))
+        .collect::<StdResult<_>>()?;
+    Ok(DragonInfoListResponse { dragons })
+}
+
+fn query_user_dragon(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<UserDragonResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let now = env.block.time.seconds();
+    let mut res = UserDragonResponse {
+        owner: owner.clone(),
+        total_owned: 0,
+        staked: 0,
+        pending_reward: Uint128::zero(),
+        dragons: vec![],
     };
+    // totals cover every dragon of the owner, the list only the requested page
+    for item in dragon_info()
+        .idx
+        .owner
+        .prefix(owner)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (id, dragon) = item?;
+        let entry = with_reward(deps.storage, dragon, now)?;
+        res.total_owned += 1;
+        if entry.dragon.is_staked {
+            res.staked += 1;
+        }
+        res.pending_reward = res.pending_reward.checked_add(entry.pending_reward)?;
+        let after_cursor = !matches!(start_after, Some(cursor) if id <= cursor);
+        if after_cursor && res.dragons.len() < limit {
+            res.dragons.push(entry);
+        }
+    }
     Ok(res)
 }
@@ -1,6 +1,10 @@
-use cosmwasm_std::{OverflowError, StdError};
+use cosmwasm_std::{OverflowError, StdError, Uint64};
 use cw721_base::ContractError as Cw721ContractError;
 use cw_utils::PaymentError;
+use stake_dragons_common::batch::BatchError;
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::ownership::OwnershipError;
+use stake_dragons_common::supply::SupplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -35,12 +39,18 @@ pub enum ContractError {
     #[error("Staked dragon cannot be transferred")]
     StakedDragonCantBeTransferred {},
 
+    #[error("Staked dragon cannot be burned")]
+    StakedDragonCantBeBurned {},
+
     #[error("Dragon must be staked to be hatch")]
     DragonNotStaked {},
 
     #[error("Minimum stake time required for unstaking")]
     MinStakeTimeRequired {},
 
+    #[error("Invalid {trait_type}: {value}")]
+    InvalidTraitValue { trait_type: String, value: String },
+
     #[error("Kind not found")]
     KindNotFound {},
 
@@ -62,8 +72,38 @@ pub enum ContractError {
     #[error("Unstaking process is ongoing")]
     OngoingUnstakingProcess {},
 
+    #[error("No staked dragons to claim for")]
+    NoStakedDragons {},
+
+    #[error("Lock tiers need at least one day and a non-zero multiplier")]
+    InvalidLockTier {},
+
+    #[error("No lock tier of {days} days")]
+    UnknownLockTier { days: u64 },
+
+    #[error("Dragon is locked until {until}")]
+    DragonLocked { until: Uint64 },
+
+    #[error("Level thresholds must be above zero and strictly increasing")]
+    InvalidLevels {},
+
     #[error("{0}")]
     Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
+    #[error("{0}")]
+    Supply(#[from] SupplyError),
+
+    #[error("{0}")]
+    Cw721(#[from] Cw721ContractError),
+
+    #[error("{0}")]
+    Batch(#[from] BatchError),
 }
 
 impl From<ContractError> for Cw721ContractError {
@@ -3,6 +3,7 @@ mod error;
 mod helper;
 pub mod msg;
 pub mod state;
-pub mod tests;
+#[cfg(test)]
+mod tests;
 
 pub use crate::error::ContractError;
@@ -1,10 +1,17 @@
+use crate::state::{Level, LockTier};
 use cosmwasm_std::{Binary, Uint128, Uint64};
 use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::burns::BurnReason;
+use stake_dragons_common::claims::ClaimSettingsMsg;
+use stake_dragons_common::nft::MetadataUpdate;
+use stake_dragons_common::ownership::OwnershipMsg;
+use stake_dragons_common::reward::EmissionRate;
+use stake_dragons_common::supply::SupplyMsg;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -12,34 +19,14 @@ pub struct InstantiateMsg {
     pub size: Uint64,
     pub base_price: Uint64,
     pub reward_contract_address: String,
+    pub emission_rates: Option<Vec<EmissionRate>>,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub external_url: Option<String>,
-    pub attributes: Vec<Trait>,
-    ///////////////////////////////////
-    pub image_data: Option<String>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub extension: Vec<Trait>,
+pub struct MigrateMsg {
+    /// Tokens to rewrite in this call, `DEFAULT_MIGRATION_LIMIT` if unset; a
+    /// collection that doesn't fit is finished by migrating again.
+    pub limit: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -59,12 +46,29 @@ pub enum ExecuteMsg {
     UpdateMinStakeTime {
         time: Uint64,
     },
-    UpdateOwner {
-        new_owner: String,
-    },
+    UpdateOwnership(OwnershipMsg),
+    UpdateClaimSettings(ClaimSettingsMsg),
+    /// Raises or freezes the collection size. Owner only.
+    UpdateSupply(SupplyMsg),
     UpdateRewardContractAddress {
         new_address: String,
     },
+    UpdateEmissionRates {
+        rates: Vec<EmissionRate>,
+    },
+    /// Replaces every lock tier with `tiers`.
+    UpdateLockTiers {
+        tiers: Vec<LockTier>,
+    },
+    /// Replaces every level with `levels`, ordered by XP threshold.
+    UpdateLevels {
+        levels: Vec<Level>,
+    },
+    /// Replaces the contracts that burn dragons for upgrades, like the
+    /// update minter. Owner only.
+    UpdateBurners {
+        burners: Vec<String>,
+    },
     TransferNft {
         recipient: String,
         token_id: Uint64,
@@ -90,8 +94,16 @@ pub enum ExecuteMsg {
     RevokeAll {
         operator: String,
     },
+    /// Burns a token. A `reason` other than `user` is only taken from the
+    /// collection minter or a configured burner.
     Burn {
         token_id: String,
+        reason: Option<BurnReason>,
+    },
+    /// Changes the art or URI of a token. Owner only.
+    UpdateMetadata {
+        token_id: String,
+        metadata: MetadataUpdate,
     },
     Mint(CustomMintMsg),
     PlantEgg {
@@ -99,16 +111,42 @@ pub enum ExecuteMsg {
     },
     StakeDragon {
         token_id: Uint64,
+        lock_days: Option<u64>,
     },
     StartUnstakingProcess {
         token_id: Uint64,
     },
+    /// Calls off an unstaking process; the token keeps what it earned and
+    /// starts earning again.
+    CancelUnstaking {
+        token_id: Uint64,
+    },
     UnstakeDragon {
         token_id: Uint64,
     },
     ClaimReward {
         token_id: Uint64,
     },
+    BatchStakeDragon {
+        token_ids: Vec<Uint64>,
+        lock_days: Option<u64>,
+    },
+    BatchStartUnstakingProcess {
+        token_ids: Vec<Uint64>,
+    },
+    BatchUnstakeDragon {
+        token_ids: Vec<Uint64>,
+    },
+    /// Claims for every listed dragon with a single payout.
+    BatchClaimReward {
+        token_ids: Vec<Uint64>,
+    },
+    /// Claims for every staked dragon of `owner`, the sender by default, with a
+    /// single payout. The owner's claim delegate may call it too.
+    ClaimAllRewards {
+        owner: Option<String>,
+    },
+    /// Older name of `ClaimReward`, paid out the same way.
     Claim {
         token_id: Uint64,
     },
@@ -140,12 +178,22 @@ pub enum QueryMsg {
     },
     Minter {},
     CollectionInfo {},
-    DragonInfoList {},
+    Supply {},
+    BurnedTokens {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    DragonInfoList {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     DragonInfo {
         id: Uint64,
     },
     QueryUserDragon {
         owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
     RangeDragons {
         start_after: Option<u64>,
@@ -159,7 +207,22 @@ pub enum QueryMsg {
     CalculateReward {
         token_id: Uint64,
     },
+    PendingRewards {
+        owner: String,
+    },
+    EmissionRates {},
+    StakingStats {},
+    RateHistory {
+        kind: String,
+    },
+    LockTiers {},
+    Levels {},
+    Burners {},
     State {},
+    PendingOwner {},
+    ClaimSettings {
+        owner: String,
+    },
 }
 
 impl From<QueryMsg> for Cw721QueryMsg {
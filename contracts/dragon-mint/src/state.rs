@@ -1,8 +1,11 @@
 use crate::ContractError;
-use cosmwasm_std::{Response, Uint64};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Response, Uint128, Uint64};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::burns::BurnedToken;
+use stake_dragons_common::reward::{Accrual, EmissionRate};
+use stake_dragons_common::stats::StakeStatus;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CollectionInfo {
@@ -27,13 +30,30 @@ pub struct Dragon {
     pub kind: String,
     pub ovulation_period: u64,
     pub hatch: Uint64,
-    pub daily_income: String,
+    pub daily_income: Decimal,
     pub is_staked: bool,
     pub stake_start_time: Uint64,
     pub reward_start_time: Uint64,
     pub unstaking_start_time: Uint64,
     pub unstaking_process: bool,
     pub reward_end_time: Uint64,
+    #[serde(default)]
+    pub lock: Option<Lock>,
+    /// Reward earned in an earlier window and not claimed yet.
+    #[serde(default)]
+    pub carried_reward: Uint128,
+    #[serde(default)]
+    pub xp: u64,
+    #[serde(default)]
+    pub level: u32,
+    /// Income bonus of `level`, copied when the level is reached so later
+    /// changes to the levels only apply from the dragon's next update.
+    #[serde(default)]
+    pub income_bonus: Decimal,
+    /// Start of the staked time not yet turned into XP; zero while the dragon
+    /// isn't earning.
+    #[serde(default)]
+    pub xp_start_time: Uint64,
 }
 
 impl Dragon {
@@ -44,10 +64,68 @@ impl Dragon {
             Ok(Response::new())
         }
     }
+
+    pub fn stake_status(&self) -> StakeStatus {
+        StakeStatus {
+            staked: self.is_staked,
+            unstaking: self.unstaking_process,
+        }
+    }
+
+    pub fn accrual(&self) -> Accrual {
+        Accrual {
+            reward_start_time: self.reward_start_time,
+            reward_end_time: self.reward_end_time,
+            unstaking_process: self.unstaking_process,
+        }
+    }
 }
 
+/// A lock-up offered at stake time: `days` without unstaking in exchange for
+/// `multiplier` times the usual reward.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct DragonResponse {
+pub struct LockTier {
+    pub days: u64,
+    pub multiplier: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockTiersResponse {
+    pub tiers: Vec<LockTier>,
+}
+
+/// The lock a dragon was staked with. The multiplier is copied from its tier so
+/// later tier changes leave running locks alone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Lock {
+    pub days: u64,
+    pub multiplier: Decimal,
+    pub end_time: Uint64,
+}
+
+/// A level a dragon reaches once it has `xp` experience. While at it the dragon
+/// earns `income_bonus` on top of its usual reward and its ovulation period is
+/// `ovulation_reduction` days shorter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Level {
+    pub xp: u64,
+    pub income_bonus: Decimal,
+    pub ovulation_reduction: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LevelsResponse {
+    pub levels: Vec<Level>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BurnersResponse {
+    pub burners: Vec<Addr>,
+}
+
+/// `Dragon` as stored before `daily_income` became a `Decimal`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyDragon {
     pub owner: String,
     pub token_id: String,
     pub kind: String,
@@ -62,6 +140,25 @@ pub struct DragonResponse {
     pub reward_end_time: Uint64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DragonResponse {
+    pub owner: String,
+    pub token_id: String,
+    pub kind: String,
+    pub ovulation_period: u64,
+    pub hatch: Uint64,
+    pub daily_income: Decimal,
+    pub is_staked: bool,
+    pub stake_start_time: Uint64,
+    pub reward_start_time: Uint64,
+    pub unstaking_start_time: Uint64,
+    pub unstaking_process: bool,
+    pub reward_end_time: Uint64,
+    pub lock: Option<Lock>,
+    pub xp: u64,
+    pub level: u32,
+}
+
 impl Into<DragonResponse> for Dragon {
     fn into(self) -> DragonResponse {
         DragonResponse {
@@ -77,6 +174,9 @@ impl Into<DragonResponse> for Dragon {
             unstaking_start_time: self.unstaking_start_time,
             unstaking_process: self.unstaking_process,
             reward_end_time: self.reward_end_time,
+            lock: self.lock,
+            xp: self.xp,
+            level: self.level,
         }
     }
 }
@@ -86,8 +186,95 @@ pub struct DragonListResponse {
     pub dragons: Vec<DragonResponse>,
 }
 
+/// A dragon together with the reward it can claim right now.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DragonRewardResponse {
+    pub dragon: DragonResponse,
+    pub pending_reward: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DragonInfoListResponse {
+    pub dragons: Vec<DragonRewardResponse>,
+}
+
+/// Totals over everything `owner` holds, plus one page of their dragons.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserDragonResponse {
+    pub owner: String,
+    pub total_owned: u64,
+    pub staked: u64,
+    pub pending_reward: Uint128,
+    pub dragons: Vec<DragonRewardResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenRewardResponse {
+    pub token_id: Uint64,
+    pub pending_reward: Uint128,
+}
+
+/// What each staked dragon of `owner` can claim, and the sum of it all.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRewardsResponse {
+    pub owner: String,
+    pub rewards: Vec<TokenRewardResponse>,
+    pub total: Uint128,
+}
+
 pub const STATE: Item<State> = Item::new("state");
 pub const COLLECTION_INFO: Item<CollectionInfo> = Item::new("collection_info");
-pub const DRAGON_INFO: Map<u64, Dragon> = Map::new("dragons_list");
+pub const LEGACY_DRAGON_INFO: Map<u64, LegacyDragon> = Map::new("dragons_list");
 pub const DRAGON_INFO_SEQ: Item<Uint64> = Item::new("dragon_list_length");
+/// Burned dragons by id, out of `dragon_info` for good.
+pub const BURNED_DRAGONS: Map<u64, BurnedToken<Dragon>> = Map::new("burned_dragons");
 pub const MIN_STAKE_TIME: Item<Uint64> = Item::new("minimum_stake_time");
+/// Reward multiplier by lock-up length in days.
+pub const LOCK_TIERS: Map<u64, Decimal> = Map::new("lock_tiers");
+/// Levels above the starting one, by increasing XP threshold.
+pub const LEVELS: Item<Vec<Level>> = Item::new("levels");
+/// Contracts besides the minter that may burn for a reason other than `user`.
+pub const BURNERS: Item<Vec<Addr>> = Item::new("burners");
+/// Seconds a dragon has to stake to earn one XP.
+pub const SECONDS_PER_XP: u64 = 3600;
+
+pub struct DragonIndexes<'a> {
+    pub owner: MultiIndex<'a, String, Dragon, u64>,
+}
+
+impl<'a> IndexList<Dragon> for DragonIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Dragon>> + '_> {
+        let v: Vec<&dyn Index<Dragon>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Dragons by id, indexed by owner.
+pub fn dragon_info<'a>() -> IndexedMap<'a, u64, Dragon, DragonIndexes<'a>> {
+    let indexes = DragonIndexes {
+        owner: MultiIndex::new(
+            |d: &Dragon| d.owner.clone(),
+            "dragons_list",
+            "dragons_list__owner",
+        ),
+    };
+    IndexedMap::new("dragons_list", indexes)
+}
+
+/// Daily emission of the genesis kinds, in micro DRGN. Matches the
+/// `daily_income` the minter gives each kind.
+pub fn default_emission_rates() -> Vec<EmissionRate> {
+    [
+        ("common", 500_000),
+        ("uncommon", 1_000_000),
+        ("rare", 2_000_000),
+        ("epic", 5_000_000),
+        ("legendary", 10_000_000),
+    ]
+    .iter()
+    .map(|(kind, per_day)| EmissionRate {
+        kind: kind.to_string(),
+        per_day: Uint128::new(*per_day),
+    })
+    .collect()
+}
@@ -0,0 +1,1261 @@
+use crate::contract::{execute, instantiate, migrate, query, Cw721Contract};
+use crate::msg::{
+    ClaimMessage, CustomMintMsg, ExecuteMsg, Extension, InstantiateMsg, MigrateMsg, QueryMsg,
+    StateResponse, Trait,
+};
+use crate::state::{
+    default_emission_rates, BurnersResponse, Dragon, DragonInfoListResponse, DragonListResponse,
+    DragonResponse, LegacyDragon, Level, LockTier, PendingRewardsResponse, UserDragonResponse,
+    LEGACY_DRAGON_INFO,
+};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{
+    from_binary, Addr, Binary, CosmosMsg, Decimal, DepsMut, Env, MemoryStorage, MessageInfo,
+    OwnedDeps, Response, StdResult, Storage, Timestamp, Uint128, Uint64, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw721::{AllNftInfoResponse, NftInfoResponse};
+use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
+use cw721_base::state::TokenInfo;
+use cw721_base::MintMsg;
+use cw_multi_test::{App, ContractWrapper, Executor};
+use proptest::prelude::*;
+use stake_dragons_common::batch::BatchError;
+use stake_dragons_common::burns::{BurnReason, BurnedTokensResponse};
+use stake_dragons_common::claims::{ClaimSettingsMsg, ClaimSettingsResponse};
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::nft::MetadataUpdate;
+use stake_dragons_common::ownership::{OwnershipError, OwnershipMsg, PendingOwnerResponse};
+use stake_dragons_common::reward::{
+    EmissionRate, EmissionRatesResponse, RateHistoryResponse, EMISSION_RATES, RATE_HISTORY,
+};
+use stake_dragons_common::stats::StakingStatsResponse;
+use stake_dragons_common::supply::{SupplyError, SupplyMsg, SupplyResponse};
+use std::str::FromStr;
+
+const KINDS: [(&str, &str); 6] = [
+    ("common", "0.5"),
+    ("uncommon", "1"),
+    ("rare", "2"),
+    ("epic", "5"),
+    ("legendary", "10"),
+    ("legendary+s1", "15"),
+];
+
+fn instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        base: Cw721InstantiateMsg {
+            name: "dragons".to_string(),
+            symbol: "DRAGON".to_string(),
+            minter: "minter".to_string(),
+        },
+        size: Uint64::new(100),
+        base_price: Uint64::new(1),
+        reward_contract_address: "reward".to_string(),
+        emission_rates: None,
+    }
+}
+
+fn setup() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let msg = instantiate_msg();
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    deps
+}
+
+fn legacy_dragon(id: u64, income: &str) -> LegacyDragon {
+    LegacyDragon {
+        owner: "user".to_string(),
+        token_id: id.to_string(),
+        kind: "common".to_string(),
+        ovulation_period: 1,
+        hatch: Uint64::zero(),
+        daily_income: income.to_string(),
+        is_staked: false,
+        stake_start_time: Uint64::zero(),
+        reward_start_time: Uint64::zero(),
+        unstaking_start_time: Uint64::zero(),
+        unstaking_process: false,
+        reward_end_time: Uint64::zero(),
+    }
+}
+
+/// Gives legacy dragon `id` the cw721 token it was minted with.
+fn legacy_token(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    let token = TokenInfo {
+        owner: Addr::unchecked("user"),
+        approvals: vec![],
+        token_uri: None,
+        extension: None,
+    };
+    Cw721Contract::default()
+        .tokens
+        .save(storage, &id.to_string(), &token)
+}
+
+/// Instantiates with the 0.1.0 layout: string incomes and no emission rates.
+fn instantiate_v0_1(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    let res = instantiate(deps.branch(), env, info, msg)?;
+    for rate in default_emission_rates() {
+        EMISSION_RATES.remove(deps.storage, &rate.kind);
+        RATE_HISTORY.remove(deps.storage, (&rate.kind, 0));
+    }
+    LEGACY_DRAGON_INFO.save(deps.storage, 1, &legacy_dragon(1, "0.5"))?;
+    legacy_token(deps.storage, 1)?;
+    set_contract_version(deps.storage, "crates.io:dragon-mint", "0.1.0")?;
+    Ok(res)
+}
+
+fn trait_(trait_type: &str, value: &str) -> Trait {
+    Trait {
+        display_type: None,
+        trait_type: trait_type.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn try_mint(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    kind: &str,
+    income: &str,
+) -> Result<Response, ContractError> {
+    try_mint_to(deps, "user", kind, income)
+}
+
+fn try_mint_to(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    owner: &str,
+    kind: &str,
+    income: &str,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Mint(CustomMintMsg {
+        base: MintMsg {
+            token_id: "".to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: None,
+        },
+        extension: vec![
+            trait_("kind", kind),
+            trait_("ovulation_period", "1"),
+            trait_("daily_income", income),
+        ],
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+}
+
+fn mint(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, kind: &str, income: &str) {
+    try_mint(deps, kind, income).unwrap();
+}
+
+fn at(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(seconds);
+    env
+}
+
+fn calculate(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>, now: u64) -> Uint128 {
+    let msg = QueryMsg::CalculateReward {
+        token_id: Uint64::new(1),
+    };
+    from_binary(&query(deps.as_ref(), at(now), msg).unwrap()).unwrap()
+}
+
+fn claim(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, now: u64) -> Uint128 {
+    let msg = ExecuteMsg::ClaimReward {
+        token_id: Uint64::new(1),
+    };
+    let res = execute(deps.as_mut(), at(now), mock_info("user", &[]), msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            let msg: ClaimMessage = from_binary(msg).unwrap();
+            msg.claim.amount
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+}
+
+#[test]
+fn common_dragons_earn_half_a_token_per_day() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(calculate(&deps, 1000 + 86400), Uint128::new(500_000));
+    assert_eq!(claim(&mut deps, 1000 + 86400), Uint128::new(500_000));
+    assert_eq!(calculate(&deps, 1000 + 86400), Uint128::zero());
+}
+
+#[test]
+fn emission_rates_are_owner_editable() {
+    let mut deps = setup();
+    mint(&mut deps, "rare", "2");
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateEmissionRates {
+        rates: vec![EmissionRate {
+            kind: "rare".to_string(),
+            per_day: Uint128::new(3_000_000),
+        }],
+    };
+    let err = execute(
+        deps.as_mut(),
+        at(86400),
+        mock_info("user", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), at(86400), mock_info("owner", &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::EmissionRates {}).unwrap();
+    let res: EmissionRatesResponse = from_binary(&res).unwrap();
+    let rare = res.rates.iter().find(|r| r.kind == "rare").unwrap();
+    assert_eq!(rare.per_day, Uint128::new(3_000_000));
+
+    // the new rate only counts from the day it was set
+    assert_eq!(calculate(&deps, 86400), Uint128::new(2_000_000));
+    assert_eq!(calculate(&deps, 2 * 86400), Uint128::new(5_000_000));
+    let msg = QueryMsg::RateHistory {
+        kind: "rare".to_string(),
+    };
+    let res: RateHistoryResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let timeline: Vec<_> = res
+        .checkpoints
+        .iter()
+        .map(|c| (c.from.u64(), c.per_day.u128()))
+        .collect();
+    assert_eq!(timeline, vec![(0, 2_000_000), (86400, 3_000_000)]);
+}
+
+#[test]
+fn fractional_income_works_for_any_kind() {
+    let mut deps = setup();
+    mint(&mut deps, "rare+s1", "2.25");
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(calculate(&deps, 86400), Uint128::new(2_250_000));
+
+    for income in ["", "abc", "-1"] {
+        let err = try_mint(&mut deps, "rare", income).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTraitValue { .. }));
+    }
+}
+
+#[test]
+fn migrate_parses_stored_income() {
+    let mut deps = setup();
+    for (id, income) in [(1u64, "0.5"), (2, "10")] {
+        LEGACY_DRAGON_INFO
+            .save(deps.as_mut().storage, id, &legacy_dragon(id, income))
+            .unwrap();
+    }
+    // dragon 2 was burned before the upgrade
+    legacy_token(deps.as_mut().storage, 1).unwrap();
+    set_contract_version(deps.as_mut().storage, "crates.io:dragon-mint", "0.1.0").unwrap();
+    migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+
+    let msg = QueryMsg::DragonInfo { id: Uint64::new(1) };
+    let dragon: DragonResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(dragon.daily_income, Decimal::from_str("0.5").unwrap());
+    let msg = QueryMsg::DragonInfo { id: Uint64::new(2) };
+    assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+    let msg = QueryMsg::BurnedTokens {
+        start_after: None,
+        limit: None,
+    };
+    let res: BurnedTokensResponse<Dragon> =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.tokens.len(), 1);
+    assert_eq!(
+        res.tokens[0].token.daily_income,
+        Decimal::from_str("10").unwrap()
+    );
+    assert_eq!(res.tokens[0].burned_at, Uint64::zero());
+}
+
+#[test]
+fn migrate_resumes_from_its_cursor() {
+    let mut deps = setup();
+    for id in 1..=3 {
+        LEGACY_DRAGON_INFO
+            .save(deps.as_mut().storage, id, &legacy_dragon(id, "1"))
+            .unwrap();
+        legacy_token(deps.as_mut().storage, id).unwrap();
+    }
+    set_contract_version(deps.as_mut().storage, "crates.io:dragon-mint", "0.1.0").unwrap();
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "migration" && a.value == "unfinished"));
+    assert_eq!(
+        get_contract_version(&deps.storage).unwrap().version,
+        "0.1.0"
+    );
+
+    // nothing runs on the half migrated collection
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::Unfinished { cursor: 2 })
+    ));
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(2) }).unwrap();
+    assert!(res
+        .attributes
+        .iter()
+        .any(|a| a.key == "migrated_dragons" && a.value == "1"));
+    assert!(!res.attributes.iter().any(|a| a.key == "migration"));
+    assert_ne!(
+        get_contract_version(&deps.storage).unwrap().version,
+        "0.1.0"
+    );
+    let msg = QueryMsg::DragonInfo { id: Uint64::new(3) };
+    let dragon: DragonResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(dragon.daily_income, Decimal::one());
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+}
+
+#[test]
+fn migrate_upgrades_v0_1_layout() {
+    let mut app = App::default();
+    let legacy = app.store_code(Box::new(ContractWrapper::new(
+        execute,
+        instantiate_v0_1,
+        query,
+    )));
+    let current = app.store_code(Box::new(
+        ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+    ));
+    let admin = Addr::unchecked("admin");
+    let addr = app
+        .instantiate_contract(
+            legacy,
+            admin.clone(),
+            &instantiate_msg(),
+            &[],
+            "dragons",
+            Some(admin.to_string()),
+        )
+        .unwrap();
+
+    let res = app
+        .migrate_contract(
+            admin.clone(),
+            addr.clone(),
+            &MigrateMsg { limit: None },
+            current,
+        )
+        .unwrap();
+    let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+    assert!(wasm
+        .attributes
+        .iter()
+        .any(|a| a.key == "migrated_dragons" && a.value == "1"));
+
+    let dragon: DragonResponse = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::DragonInfo { id: Uint64::new(1) })
+        .unwrap();
+    assert_eq!(dragon.daily_income, Decimal::from_str("0.5").unwrap());
+    let rates: EmissionRatesResponse = app
+        .wrap()
+        .query_wasm_smart(&addr, &QueryMsg::EmissionRates {})
+        .unwrap();
+    assert_eq!(rates.rates.len(), default_emission_rates().len());
+    let msg = QueryMsg::RangeUserDragons {
+        start_after: None,
+        limit: None,
+        owner: "user".to_string(),
+    };
+    let owned: DragonListResponse = app.wrap().query_wasm_smart(&addr, &msg).unwrap();
+    assert_eq!(owned.dragons.len(), 1);
+
+    // the same version can be migrated again without re-running the steps
+    let res = app
+        .migrate_contract(admin, addr, &MigrateMsg { limit: None }, current)
+        .unwrap();
+    let wasm = res.events.iter().find(|e| e.ty == "wasm").unwrap();
+    assert!(!wasm.attributes.iter().any(|a| a.key == "migrated_dragons"));
+}
+
+#[test]
+fn range_user_dragons_pages_through_owner_index() {
+    let mut deps = setup();
+    for owner in ["alice", "bob", "alice", "bob", "bob", "alice"] {
+        try_mint_to(&mut deps, owner, "common", "0.5").unwrap();
+    }
+    let page = |start_after: Option<u64>| -> Vec<String> {
+        let msg = QueryMsg::RangeUserDragons {
+            start_after,
+            limit: Some(2),
+            owner: "bob".to_string(),
+        };
+        let res: DragonListResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        res.dragons.into_iter().map(|d| d.token_id).collect()
+    };
+    assert_eq!(page(None), vec!["2", "4"]);
+    assert_eq!(page(Some(4)), vec!["5"]);
+    assert!(page(Some(5)).is_empty());
+}
+
+#[test]
+fn user_dragon_summarises_every_owned_dragon() {
+    let mut deps = setup();
+    for owner in ["user", "other", "user", "user"] {
+        try_mint_to(&mut deps, owner, "common", "0.5").unwrap();
+    }
+    for id in [1, 3] {
+        let msg = ExecuteMsg::StakeDragon {
+            token_id: Uint64::new(id),
+            lock_days: None,
+        };
+        execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+    }
+
+    let msg = QueryMsg::QueryUserDragon {
+        owner: "user".to_string(),
+        start_after: Some(1),
+        limit: Some(1),
+    };
+    let res: UserDragonResponse =
+        from_binary(&query(deps.as_ref(), at(1000 + 86400), msg).unwrap()).unwrap();
+    assert_eq!(res.total_owned, 3);
+    assert_eq!(res.staked, 2);
+    assert_eq!(res.pending_reward, Uint128::new(1_000_000));
+    assert_eq!(res.dragons.len(), 1);
+    assert_eq!(res.dragons[0].dragon.token_id, "3");
+    assert_eq!(res.dragons[0].pending_reward, Uint128::new(500_000));
+
+    let msg = QueryMsg::DragonInfoList {
+        start_after: None,
+        limit: None,
+    };
+    let res: DragonInfoListResponse =
+        from_binary(&query(deps.as_ref(), at(1000 + 86400), msg).unwrap()).unwrap();
+    let rewards: Vec<_> = res
+        .dragons
+        .iter()
+        .map(|d| d.pending_reward.u128())
+        .collect();
+    assert_eq!(rewards, vec![500_000, 0, 500_000, 0]);
+}
+
+fn owner_of(deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>, id: u64) -> String {
+    let msg = QueryMsg::DragonInfo {
+        id: Uint64::new(id),
+    };
+    let dragon: DragonResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    dragon.owner
+}
+
+#[test]
+fn every_transfer_path_moves_the_dragon_record() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::ApproveAll {
+        operator: "operator".to_string(),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "buyer".to_string(),
+        token_id: Uint64::new(1),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap();
+    assert_eq!(owner_of(&deps, 1), "buyer");
+
+    let msg = ExecuteMsg::SendNft {
+        contract: "market".to_string(),
+        token_id: "2".to_string(),
+        msg: Binary::default(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(owner_of(&deps, 2), "market");
+
+    // the operator was approved by the old owner only
+    let msg = ExecuteMsg::TransferNft {
+        recipient: "operator".to_string(),
+        token_id: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("operator", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Cw721(_)));
+    assert_eq!(owner_of(&deps, 1), "buyer");
+}
+
+#[test]
+fn staked_dragons_cannot_be_sent() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::SendNft {
+        contract: "market".to_string(),
+        token_id: "1".to_string(),
+        msg: Binary::default(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::StakedDragonCantBeTransferred {}
+    ));
+}
+
+#[test]
+fn batch_claim_pays_the_total_once() {
+    let mut deps = setup();
+    for kind in ["common", "rare"] {
+        mint(&mut deps, kind, "0.5");
+    }
+    try_mint_to(&mut deps, "other", "common", "0.5").unwrap();
+    let token_ids = vec![Uint64::new(1), Uint64::new(2)];
+
+    let msg = ExecuteMsg::BatchStakeDragon {
+        token_ids: vec![Uint64::new(1), Uint64::new(3)],
+        lock_days: None,
+    };
+    let err = execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let msg = ExecuteMsg::BatchStakeDragon {
+        token_ids: vec![Uint64::new(2), Uint64::new(2)],
+        lock_days: None,
+    };
+    let err = execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Batch(BatchError::Duplicate { .. })
+    ));
+
+    let msg = ExecuteMsg::BatchStakeDragon {
+        token_ids: token_ids.clone(),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::BatchClaimReward { token_ids };
+    let res = execute(deps.as_mut(), at(1000 + 86400), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            let msg: ClaimMessage = from_binary(msg).unwrap();
+            assert_eq!(msg.claim.amount, Uint128::new(2_500_000));
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    let rewards: Vec<_> = res
+        .attributes
+        .iter()
+        .filter(|a| a.key == "reward")
+        .map(|a| a.value.as_str())
+        .collect();
+    assert_eq!(rewards, vec!["500000", "2000000"]);
+}
+
+fn pending(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    now: u64,
+) -> PendingRewardsResponse {
+    let msg = QueryMsg::PendingRewards {
+        owner: "user".to_string(),
+    };
+    from_binary(&query(deps.as_ref(), at(now), msg).unwrap()).unwrap()
+}
+
+#[test]
+fn claim_all_rewards_sums_every_staked_dragon() {
+    let mut deps = setup();
+    for kind in ["common", "rare", "common"] {
+        mint(&mut deps, kind, "0.5");
+    }
+    let msg = ExecuteMsg::BatchStakeDragon {
+        token_ids: vec![Uint64::new(1), Uint64::new(2)],
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+
+    let res = pending(&deps, 1000 + 86400);
+    let rewards: Vec<_> = res
+        .rewards
+        .iter()
+        .map(|r| (r.token_id.u64(), r.pending_reward.u128()))
+        .collect();
+    assert_eq!(rewards, vec![(1, 500_000), (2, 2_000_000)]);
+    assert_eq!(res.total, Uint128::new(2_500_000));
+
+    let msg = ExecuteMsg::ClaimAllRewards { owner: None };
+    let err = execute(deps.as_mut(), at(1000), mock_info("other", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::NoStakedDragons {}));
+    let msg = ExecuteMsg::ClaimAllRewards { owner: None };
+    let res = execute(deps.as_mut(), at(1000 + 86400), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            let msg: ClaimMessage = from_binary(msg).unwrap();
+            assert_eq!(msg.claim.amount, Uint128::new(2_500_000));
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    assert_eq!(pending(&deps, 1000 + 86400).total, Uint128::zero());
+}
+
+#[test]
+fn locked_dragons_earn_more_and_cannot_start_unstaking() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    mint(&mut deps, "common", "0.5");
+    let tiers = vec![LockTier {
+        days: 30,
+        multiplier: Decimal::from_str("2").unwrap(),
+    }];
+    let msg = ExecuteMsg::UpdateLockTiers {
+        tiers: tiers.clone(),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let msg = ExecuteMsg::UpdateLockTiers { tiers };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(2),
+        lock_days: Some(90),
+    };
+    let err = execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::UnknownLockTier { days: 90 }));
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: Some(30),
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+    let msg = QueryMsg::DragonInfo { id: Uint64::new(1) };
+    let dragon: DragonResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let end_time = Uint64::new(1000 + 30 * 86400);
+    assert_eq!(dragon.lock.unwrap().end_time, end_time);
+    assert_eq!(calculate(&deps, 1000 + 86400), Uint128::new(1_000_000));
+
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(
+        deps.as_mut(),
+        at(1000 + 86400),
+        mock_info("user", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::DragonLocked { until } if until == end_time));
+    execute(
+        deps.as_mut(),
+        at(end_time.u64()),
+        mock_info("user", &[]),
+        msg,
+    )
+    .unwrap();
+}
+
+#[test]
+fn lock_multiplier_stops_when_the_lock_ends() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::UpdateLockTiers {
+        tiers: vec![LockTier {
+            days: 30,
+            multiplier: Decimal::from_str("2").unwrap(),
+        }],
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: Some(30),
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+
+    // 30 locked days at twice the rate, then 10 days at the usual rate
+    let expired = 1000 + 40 * 86400;
+    assert_eq!(calculate(&deps, expired), Uint128::new(35_000_000));
+    assert_eq!(pending(&deps, expired).total, Uint128::new(35_000_000));
+    assert_eq!(claim(&mut deps, expired), Uint128::new(35_000_000));
+
+    let msg = ExecuteMsg::ClaimAllRewards { owner: None };
+    let res = execute(
+        deps.as_mut(),
+        at(expired + 86400),
+        mock_info("user", &[]),
+        msg,
+    )
+    .unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            let msg: ClaimMessage = from_binary(msg).unwrap();
+            assert_eq!(msg.claim.amount, Uint128::new(500_000));
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+}
+
+#[test]
+fn staked_time_levels_dragons_up() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let level = |xp| Level {
+        xp,
+        income_bonus: Decimal::one(),
+        ovulation_reduction: 0,
+    };
+    let msg = ExecuteMsg::UpdateLevels {
+        levels: vec![level(48), level(24)],
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::InvalidLevels {}));
+    let msg = ExecuteMsg::UpdateLevels {
+        levels: vec![level(24)],
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+    let msg = QueryMsg::DragonInfo { id: Uint64::new(1) };
+    let dragon: DragonResponse =
+        from_binary(&query(deps.as_ref(), at(1000 + 86400), msg).unwrap()).unwrap();
+    assert_eq!((dragon.xp, dragon.level), (24, 1));
+
+    // the first day is paid at level 0, the bonus applies from the claim on
+    assert_eq!(claim(&mut deps, 1000 + 86400), Uint128::new(500_000));
+    assert_eq!(calculate(&deps, 1000 + 2 * 86400), Uint128::new(1_000_000));
+
+    let msg = QueryMsg::NftInfo {
+        token_id: "1".to_string(),
+    };
+    let info: NftInfoResponse<Extension> =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let attributes = info.extension.unwrap().attributes.unwrap();
+    assert!(attributes
+        .iter()
+        .any(|t| t.trait_type == "level" && t.value == "1"));
+}
+
+#[test]
+fn levels_pay_their_bonus_from_when_they_were_reached() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let level = |xp, bonus| Level {
+        xp,
+        income_bonus: Decimal::from_str(bonus).unwrap(),
+        ovulation_reduction: 0,
+    };
+    let msg = ExecuteMsg::UpdateLevels {
+        levels: vec![level(24, "1"), level(48, "2")],
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+
+    // untouched for three days: one day at each level, each at its own bonus
+    let expected = Uint128::new(500_000 + 1_000_000 + 1_500_000);
+    assert_eq!(calculate(&deps, 1000 + 3 * 86400), expected);
+    let msg = QueryMsg::PendingRewards {
+        owner: "user".to_string(),
+    };
+    let pending: PendingRewardsResponse =
+        from_binary(&query(deps.as_ref(), at(1000 + 3 * 86400), msg).unwrap()).unwrap();
+    assert_eq!(pending.total, expected);
+    assert_eq!(claim(&mut deps, 1000 + 3 * 86400), expected);
+    assert_eq!(calculate(&deps, 1000 + 4 * 86400), Uint128::new(1_500_000));
+}
+
+#[test]
+fn nft_info_describes_the_dragon_as_it_is_now() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(1000), mock_info("user", &[]), msg).unwrap();
+
+    let update = ExecuteMsg::UpdateMetadata {
+        token_id: "1".to_string(),
+        metadata: MetadataUpdate {
+            image: Some("ipfs://new".to_string()),
+            ..MetadataUpdate::default()
+        },
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        update.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), update).unwrap();
+
+    let msg = QueryMsg::AllNftInfo {
+        token_id: "1".to_string(),
+        include_expired: None,
+    };
+    let res: AllNftInfoResponse<Extension> =
+        from_binary(&query(deps.as_ref(), at(1000 + 3600), msg).unwrap()).unwrap();
+    assert_eq!(res.access.owner, "user");
+    let metadata = res.info.extension.unwrap();
+    assert_eq!(metadata.image.as_deref(), Some("ipfs://new"));
+    let value = |trait_type: &str| {
+        let attributes = metadata.attributes.as_ref().unwrap();
+        let found = attributes.iter().find(|t| t.trait_type == trait_type);
+        found.unwrap().value.clone()
+    };
+    assert_eq!(value("staked"), "true");
+    assert_eq!(value("hatch"), (1000 + 86400).to_string());
+    assert_eq!(value("xp"), "1");
+}
+
+#[test]
+fn cancelled_unstaking_keeps_earned_reward() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(1),
+    };
+    execute(deps.as_mut(), at(86400), mock_info("user", &[]), msg).unwrap();
+    assert_eq!(calculate(&deps, 3 * 86400), Uint128::new(500_000));
+
+    let cancel = ExecuteMsg::CancelUnstaking {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(
+        deps.as_mut(),
+        at(3 * 86400),
+        mock_info("other", &[]),
+        cancel.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(
+        deps.as_mut(),
+        at(3 * 86400),
+        mock_info("user", &[]),
+        cancel.clone(),
+    )
+    .unwrap();
+    assert_eq!(calculate(&deps, 4 * 86400), Uint128::new(1_000_000));
+
+    let err = execute(deps.as_mut(), at(4 * 86400), mock_info("user", &[]), cancel).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::UnstakingProcessIsNotStarted {}
+    ));
+
+    // the older claim message pays what was carried over too
+    let msg = ExecuteMsg::Claim {
+        token_id: Uint64::new(1),
+    };
+    let res = execute(deps.as_mut(), at(4 * 86400), mock_info("user", &[]), msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            let msg: ClaimMessage = from_binary(msg).unwrap();
+            assert_eq!(msg.claim.amount, Uint128::new(1_000_000));
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    assert_eq!(calculate(&deps, 4 * 86400), Uint128::zero());
+}
+
+#[test]
+fn staking_stats_follow_every_write() {
+    let mut deps = setup();
+    for kind in ["common", "rare", "common"] {
+        mint(&mut deps, kind, "0.5");
+    }
+    let msg = ExecuteMsg::BatchStakeDragon {
+        token_ids: vec![Uint64::new(1), Uint64::new(2)],
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(2),
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    claim(&mut deps, 86400);
+
+    let burn = |token_id: &str| ExecuteMsg::Burn {
+        token_id: token_id.to_string(),
+        reason: None,
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), burn("1")).unwrap_err();
+    assert!(matches!(err, ContractError::StakedDragonCantBeBurned {}));
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), burn("3")).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::StakingStats {}).unwrap();
+    let stats: StakingStatsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        (stats.minted, stats.burned, stats.staked, stats.unstaking),
+        (3, 1, 2, 1)
+    );
+    assert_eq!(stats.rewards_claimed, Uint128::new(500_000));
+    let by_kind: Vec<_> = stats
+        .staked_by_kind
+        .iter()
+        .map(|k| (k.kind.as_str(), k.staked))
+        .collect();
+    assert_eq!(by_kind, vec![("common", 1), ("rare", 1)]);
+}
+
+#[test]
+fn mint_stops_at_the_collection_size() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        size: Uint64::new(2),
+        ..instantiate_msg()
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    mint(&mut deps, "common", "0.5");
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::Burn {
+        token_id: "2".to_string(),
+        reason: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+    let err = try_mint(&mut deps, "common", "0.5").unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Supply(SupplyError::MaxSupplyReached { .. })
+    ));
+
+    let raise = |size: u64| {
+        ExecuteMsg::UpdateSupply(SupplyMsg::RaiseCap {
+            size: Uint64::new(size),
+        })
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("user", &[]), raise(3)).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), raise(3)).unwrap();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::UpdateSupply(SupplyMsg::FreezeCap {});
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), raise(4)).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Supply(SupplyError::CapFrozen {})
+    ));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Supply {}).unwrap();
+    let supply: SupplyResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        supply,
+        SupplyResponse {
+            minted: Uint64::new(3),
+            burned: Uint64::new(1),
+            remaining: Uint64::zero(),
+            cap: Uint64::new(3),
+            frozen: true,
+        }
+    );
+}
+
+#[test]
+fn burned_dragons_leave_the_live_records() {
+    let mut deps = setup();
+    for _ in 0..3 {
+        mint(&mut deps, "common", "0.5");
+    }
+    let burn = |token_id: &str, reason: Option<BurnReason>| ExecuteMsg::Burn {
+        token_id: token_id.to_string(),
+        reason,
+    };
+    execute(
+        deps.as_mut(),
+        at(100),
+        mock_info("user", &[]),
+        burn("2", None),
+    )
+    .unwrap();
+    let upgrade = burn("3", Some(BurnReason::Upgrade));
+    let err = execute(
+        deps.as_mut(),
+        at(200),
+        mock_info("user", &[]),
+        upgrade.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let msg = ExecuteMsg::Approve {
+        spender: "minter".to_string(),
+        token_id: "3".to_string(),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+    execute(deps.as_mut(), at(200), mock_info("minter", &[]), upgrade).unwrap();
+
+    let msg = QueryMsg::RangeDragons {
+        start_after: None,
+        limit: None,
+    };
+    let res: DragonListResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let live: Vec<_> = res.dragons.into_iter().map(|d| d.token_id).collect();
+    assert_eq!(live, vec!["1"]);
+    let msg = QueryMsg::DragonInfo { id: Uint64::new(2) };
+    assert!(query(deps.as_ref(), mock_env(), msg).is_err());
+
+    let msg = QueryMsg::BurnedTokens {
+        start_after: None,
+        limit: None,
+    };
+    let res: BurnedTokensResponse<Dragon> =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    let burned: Vec<_> = res
+        .tokens
+        .iter()
+        .map(|t| (t.token.token_id.as_str(), t.reason, t.burned_at.u64()))
+        .collect();
+    assert_eq!(
+        burned,
+        vec![
+            ("2", BurnReason::User, 100),
+            ("3", BurnReason::Upgrade, 200)
+        ]
+    );
+}
+
+#[test]
+fn configured_burners_burn_for_upgrades() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::ApproveAll {
+        operator: "updater".to_string(),
+        expires: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("user", &[]), msg).unwrap();
+    let upgrade = ExecuteMsg::Burn {
+        token_id: "1".to_string(),
+        reason: Some(BurnReason::Upgrade),
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("updater", &[]),
+        upgrade.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    let msg = ExecuteMsg::UpdateBurners {
+        burners: vec!["updater".to_string()],
+    };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let res: BurnersResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Burners {}).unwrap()).unwrap();
+    assert_eq!(res.burners, vec![Addr::unchecked("updater")]);
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("updater", &[]),
+        upgrade,
+    )
+    .unwrap();
+    let msg = QueryMsg::BurnedTokens {
+        start_after: None,
+        limit: None,
+    };
+    let res: BurnedTokensResponse<Dragon> =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.tokens[0].reason, BurnReason::Upgrade);
+}
+
+#[test]
+fn delegates_claim_to_the_owners_recipient() {
+    let mut deps = setup();
+    mint(&mut deps, "common", "0.5");
+    let msg = ExecuteMsg::StakeDragon {
+        token_id: Uint64::new(1),
+        lock_days: None,
+    };
+    execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    for msg in [
+        ClaimSettingsMsg::SetClaimDelegate {
+            delegate: "hot".to_string(),
+        },
+        ClaimSettingsMsg::SetRewardRecipient {
+            recipient: "vault".to_string(),
+        },
+    ] {
+        let msg = ExecuteMsg::UpdateClaimSettings(msg);
+        execute(deps.as_mut(), at(0), mock_info("user", &[]), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::ClaimReward {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(
+        deps.as_mut(),
+        at(86400),
+        mock_info("other", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+    let res = execute(deps.as_mut(), at(86400), mock_info("hot", &[]), msg).unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => {
+            let msg: ClaimMessage = from_binary(msg).unwrap();
+            assert_eq!(msg.claim.recipient, "vault");
+            assert_eq!(msg.claim.amount, Uint128::new(500_000));
+        }
+        msg => panic!("unexpected message {:?}", msg),
+    }
+    let msg = ExecuteMsg::ClaimAllRewards {
+        owner: Some("user".to_string()),
+    };
+    execute(deps.as_mut(), at(2 * 86400), mock_info("hot", &[]), msg).unwrap();
+
+    // claiming is all a delegate can do
+    let msg = ExecuteMsg::StartUnstakingProcess {
+        token_id: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), at(2 * 86400), mock_info("hot", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+
+    let msg = QueryMsg::ClaimSettings {
+        owner: "user".to_string(),
+    };
+    let res: ClaimSettingsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.claim_delegate, Some(Addr::unchecked("hot")));
+    assert_eq!(res.reward_recipient, "vault");
+}
+
+#[test]
+fn ownership_moves_on_accept() {
+    let mut deps = setup();
+    let msg = ExecuteMsg::UpdateOwnership(OwnershipMsg::ProposeOwner {
+        new_owner: "next".to_string(),
+        expires: None,
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending: PendingOwnerResponse = from_binary(&res).unwrap();
+    assert_eq!(pending.pending_owner, Some(Addr::unchecked("next")));
+
+    let accept = ExecuteMsg::UpdateOwnership(OwnershipMsg::AcceptOwnership {});
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        accept.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Ownership(OwnershipError::NotPendingOwner {})
+    ));
+    execute(deps.as_mut(), mock_env(), mock_info("next", &[]), accept).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.owner, "next");
+
+    let msg = ExecuteMsg::UpdateMinStakeTime {
+        time: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+}
+
+#[test]
+fn migrate_refuses_downgrade() {
+    let mut deps = setup();
+    set_contract_version(deps.as_mut().storage, "crates.io:dragon-mint", "9.0.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::Downgrade { .. })
+    ));
+
+    set_contract_version(deps.as_mut().storage, "crates.io:box-minter", "0.1.0").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Migrate(MigrateError::WrongContract { .. })
+    ));
+}
+
+proptest! {
+    #[test]
+    fn query_matches_claim(
+        kind in 0..KINDS.len(),
+        staked_at in 0u64..1_000_000_000,
+        unstake_after in proptest::option::of(0u64..100_000_000),
+        claim_after in 0u64..100_000_000,
+    ) {
+        let (kind, income) = KINDS[kind];
+        let mut deps = setup();
+        mint(&mut deps, kind, income);
+        let msg = ExecuteMsg::StakeDragon { token_id: Uint64::new(1), lock_days: None };
+        execute(deps.as_mut(), at(staked_at), mock_info("user", &[]), msg).unwrap();
+
+        let mut now = staked_at + claim_after;
+        if let Some(after) = unstake_after {
+            let msg = ExecuteMsg::StartUnstakingProcess { token_id: Uint64::new(1) };
+            execute(deps.as_mut(), at(staked_at + after), mock_info("user", &[]), msg).unwrap();
+            now = now.max(staked_at + after);
+        }
+
+        let quoted = calculate(&deps, now);
+        prop_assert_eq!(claim(&mut deps, now), quoted);
+    }
+}
@@ -6,6 +6,7 @@ use cosmwasm_std::{
 };
 use cw2::set_contract_version;
 use cw_utils::Expiration;
+use stake_dragons_common::supply::{check_supply, query_supply, update_supply, SupplyMsg};
 use std::ops::Add;
 
 use crate::error::ContractError;
@@ -60,6 +61,7 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Mint(msg) => execute_mint(deps, env, info, msg),
+        ExecuteMsg::UpdateSupply(msg) => execute_update_supply(deps, info, msg),
         ExecuteMsg::Burn { token_id } => execute_burn(deps, env, info, token_id),
         ExecuteMsg::Approve {
             spender,
@@ -91,6 +93,8 @@ fn execute_mint(
     info: MessageInfo,
     msg: CustomMintMsg,
 ) -> Result<Response, ContractError> {
+    let cap = COLLECTION_INFO.load(deps.storage)?.size;
+    check_supply(OWNED_EGG_COUNT.load(deps.storage)?, cap)?;
     OWNED_EGG_COUNT.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint64::new(1))))?;
 
     let mint_msg = Cw721ExecuteMsg::Mint(msg.base.clone());
@@ -103,6 +107,23 @@ fn execute_mint(
     Ok(Response::default().add_attribute("new owner", msg.base.owner.clone()))
 }
 
+/// The egg collection has no owner of its own, so its minter manages the size.
+fn execute_update_supply(
+    deps: DepsMut,
+    info: MessageInfo,
+    msg: SupplyMsg,
+) -> Result<Response, ContractError> {
+    let mut collection_info = COLLECTION_INFO.load(deps.storage)?;
+    if info.sender != collection_info.minter {
+        return Err(ContractError::Unauthorized {});
+    }
+    collection_info.size = update_supply(deps.storage, collection_info.size, msg)?;
+    COLLECTION_INFO.save(deps.storage, &collection_info)?;
+    Ok(Response::default()
+        .add_attribute("action", "update_supply")
+        .add_attribute("size", collection_info.size))
+}
+
 fn execute_approve_all(
     deps: DepsMut,
     env: Env,
@@ -234,6 +255,12 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::CollectionInfo {} => to_binary(&query_config(deps)?),
         QueryMsg::OwnedEggCount {} => to_binary(&query_owned_egg_count(deps)?),
+        QueryMsg::Supply {} => to_binary(&query_supply(
+            deps.storage,
+            OWNED_EGG_COUNT.load(deps.storage)?,
+            Cw721Contract::default().token_count(deps.storage)?,
+            COLLECTION_INFO.load(deps.storage)?.size,
+        )?),
         _ => Cw721Contract::default().query(deps, env, msg.into()),
     }
 }
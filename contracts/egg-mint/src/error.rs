@@ -1,6 +1,7 @@
 use cosmwasm_std::StdError;
 use cw721_base::ContractError as Cw721ContractError;
 use cw_utils::PaymentError;
+use stake_dragons_common::supply::SupplyError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -31,6 +32,9 @@ pub enum ContractError {
 
     #[error("{0}")]
     Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Supply(#[from] SupplyError),
 }
 
 impl From<ContractError> for Cw721ContractError {
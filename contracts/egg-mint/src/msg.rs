@@ -1,12 +1,13 @@
 use cosmwasm_std::{Binary, Uint64};
 
 use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 use cw_utils::Expiration;
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::supply::SupplyMsg;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -15,35 +16,6 @@ pub struct InstantiateMsg {
     pub base_price: Uint64,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-
-// see: https://docs.opensea.io/docs/metadata-standards
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub image: Option<String>,
-    pub image_data: Option<String>,
-    pub external_url: Option<String>,
-    pub description: Option<String>,
-    pub name: Option<String>,
-    pub attributes: Option<Vec<Trait>>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub hatch: Option<String>,
-}
-
 /// cw721 execute messages with custom messages
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -79,6 +51,8 @@ pub enum ExecuteMsg {
 
     /// Mint a new NFT, can only be called by the contract minter
     Mint(CustomMintMsg),
+    /// Raises or freezes the collection size. Minter only.
+    UpdateSupply(SupplyMsg),
 }
 
 /// cw721 query messages with custom messages
@@ -151,6 +125,7 @@ pub enum QueryMsg {
     Minter {},
     CollectionInfo {},
     OwnedEggCount {},
+    Supply {},
 }
 
 impl From<QueryMsg> for Cw721QueryMsg {
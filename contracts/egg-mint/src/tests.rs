@@ -9,6 +9,9 @@ mod tests {
     use cw721::{ContractInfoResponse, Cw721Query};
     use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
     use cw721_base::{Cw721Contract, MintMsg};
+    use stake_dragons_common::supply::{SupplyError, SupplyMsg, SupplyResponse};
+
+    use crate::ContractError;
 
     #[test]
     fn proper_initialization() {
@@ -48,7 +51,7 @@ mod tests {
                 token_uri: Some(token_uri.clone()),
                 extension: None,
             },
-            hatch: None,
+            extension: vec![],
         };
         let msg = ExecuteMsg::Mint(mint_msg);
 
@@ -65,7 +68,7 @@ mod tests {
                 token_uri: Some(token_uri_2.clone()),
                 extension: None,
             },
-            hatch: None,
+            extension: vec![],
         };
         let msg2 = ExecuteMsg::Mint(mint_msg);
 
@@ -82,7 +85,7 @@ mod tests {
                 token_uri: Some(token_uri_3.clone()),
                 extension: None,
             },
-            hatch: None,
+            extension: vec![],
         };
         let msg = ExecuteMsg::Mint(mint_msg);
 
@@ -171,4 +174,65 @@ mod tests {
         let res = query_owned_egg_count(deps.as_ref()).unwrap();
         println!("owned egg count from state {}", res.owned);
     }
+
+    #[test]
+    fn mint_stops_at_the_collection_size() {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            base: Cw721InstantiateMsg {
+                name: String::from("eggs"),
+                symbol: String::from("EGG"),
+                minter: String::from("creator"),
+            },
+            base_price: Uint64::new(1),
+            size: Uint64::new(2),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mint = |id: &str| {
+            ExecuteMsg::Mint(CustomMintMsg {
+                base: MintMsg {
+                    token_id: id.to_string(),
+                    owner: String::from("medusa"),
+                    token_uri: None,
+                    extension: None,
+                },
+                extension: vec![],
+            })
+        };
+        let creator = mock_info("creator", &[]);
+        execute(deps.as_mut(), mock_env(), creator.clone(), mint("1")).unwrap();
+        execute(deps.as_mut(), mock_env(), creator.clone(), mint("2")).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), creator.clone(), mint("3")).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Supply(SupplyError::MaxSupplyReached { cap }) if cap == Uint64::new(2)
+        ));
+
+        let raise = |size: u64| {
+            ExecuteMsg::UpdateSupply(SupplyMsg::RaiseCap {
+                size: Uint64::new(size),
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("medusa", &[]),
+            raise(3),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        // the size only grows, so it never drops below what was minted
+        let err = execute(deps.as_mut(), mock_env(), creator.clone(), raise(1)).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Supply(SupplyError::CapNotRaised { .. })
+        ));
+
+        execute(deps.as_mut(), mock_env(), creator.clone(), raise(3)).unwrap();
+        execute(deps.as_mut(), mock_env(), creator, mint("3")).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Supply {}).unwrap();
+        let supply: SupplyResponse = from_binary(&res).unwrap();
+        assert_eq!(supply.minted, Uint64::new(3));
+        assert_eq!(supply.remaining, Uint64::zero());
+    }
 }
@@ -4,10 +4,18 @@ use cosmwasm_std::entry_point;
 use cosmwasm_std::WasmMsg::Execute;
 use cosmwasm_std::{
     from_slice, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, SubMsg, Uint128, Uint64,
+    Order, Response, StdError, StdResult, SubMsg, Uint128, Uint64,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20Contract, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use stake_dragons_common::migrate::{check_migration, Version};
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
+use stake_dragons_common::random::{
+    fulfil_randomness, request_randomness, validate_source, RandomnessRequest, RandomnessSource,
+};
+use stake_dragons_common::templates::{
+    query_metadata_templates, save_metadata_templates, MetadataTemplate, METADATA_TEMPLATES,
+};
 use std::ops::Add;
 
 pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Metadata>;
@@ -15,10 +23,14 @@ pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Metadata>;
 use crate::error::ContractError;
 use crate::helper::{generate_dragon_mint_msg, generate_egg_mint_msg};
 use crate::msg::{
-    ExecuteMsg, GetEggsaleInfoResponse, GetStateResponse, InstantiateMsg, Metadata, MintEggDragon,
-    QueryMsg, ReceiveMsg,
+    ExecuteMsg, GetEggsaleInfoResponse, GetStateResponse, InstantiateMsg, Metadata, MigrateMsg,
+    MintEggDragon, QueryMsg, RarityWeightsResponse, ReceiveMsg,
+};
+use crate::state::{
+    default_metadata_templates, default_rarity_weights, ContractAddressList, PendingHatch,
+    RarityWeight, State, CONTRACTS, DRAGON_KINDS, EGG_SALE_COUNT, PENDING_HATCHES,
+    RANDOMNESS_SOURCE, RARITY_WEIGHTS, STATE, TOTAL_EGGS,
 };
-use crate::state::{ContractAddressList, State, CONTRACTS, EGG_SALE_COUNT, STATE, TOTAL_EGGS};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:minter";
@@ -35,7 +47,6 @@ pub fn instantiate(
         owner: String::from(info.sender.clone()),
         base_price: msg.base_price,
         hatch_price: msg.hatch_price,
-        random_key: msg.random_key,
         egg_sale_size: msg.egg_sale_size,
         allowed_cw20: msg.allowed_cw20,
     };
@@ -46,8 +57,15 @@ pub fn instantiate(
         recipient: "juno1luw9kspq5dwrarxgkvfwt443ue99umdaqmm57afg7ms5yOrkz3dsfeudxp".to_string(),
         multisig: "juno1luw9kspq5dwrqrxgkvfwt443ue99umdaqmm57afg7ms5y0rkz3dsfeudxp".to_string(),
     };
+    let rarity_weights = msg.rarity_weights.unwrap_or_else(default_rarity_weights);
+    validate_rarity_weights(&rarity_weights)?;
+    validate_source(deps.api, &msg.randomness_source)?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    RARITY_WEIGHTS.save(deps.storage, &rarity_weights)?;
+    RANDOMNESS_SOURCE.save(deps.storage, &msg.randomness_source)?;
+    save_metadata_templates(deps.storage, &default_metadata_templates())?;
     EGG_SALE_COUNT.save(deps.storage, &Uint64::new(0))?;
     TOTAL_EGGS.save(deps.storage, &Uint64::new(0))?;
     CONTRACTS.save(deps.storage, &contracts)?;
@@ -67,24 +85,33 @@ pub fn execute(
     match msg {
         ExecuteMsg::MintEgg {} => execute_egg_mint(deps, info),
         ExecuteMsg::GenesisHatch(msg) => execute_free_hatch(deps, info, msg),
+        ExecuteMsg::FulfilHatch { egg_id, secret } => {
+            execute_fulfil_hatch(deps, _env, egg_id, secret)
+        }
         ExecuteMsg::DragonBirth { id, owner } => execute_dragon_birth(deps, info, id, owner),
         ExecuteMsg::EditState {
-            new_owner,
             base_price,
-            random_key,
             hatch_price,
             egg_sale_size,
             allowed_cw20,
         } => execute_edit_state(
             deps,
             info,
-            new_owner,
             base_price,
-            random_key,
             hatch_price,
             egg_sale_size,
             allowed_cw20,
         ),
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, _env, info, msg),
+        ExecuteMsg::EditRarityWeights { weights } => {
+            execute_edit_rarity_weights(deps, info, weights)
+        }
+        ExecuteMsg::EditMetadataTemplate { template } => {
+            execute_edit_metadata_template(deps, info, template)
+        }
+        ExecuteMsg::EditRandomnessSource { source } => {
+            execute_edit_randomness_source(deps, info, source)
+        }
         ExecuteMsg::EditContracts {
             egg,
             dragon,
@@ -107,19 +134,21 @@ pub fn execute_receive(
     let amount = wrapper.amount;
     let sender = wrapper.sender;
     match msg {
-        ReceiveMsg::Hatch { id, egg_id } => {
-            execute_hatch_cw20(deps, _env, info, sender, id, egg_id, amount)
-        }
+        ReceiveMsg::Hatch {
+            id,
+            egg_id,
+            commitment,
+        } => execute_hatch_cw20(deps, info, sender, id, egg_id, commitment, amount),
     }
 }
 
 pub fn execute_hatch_cw20(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
     sender: String,
     id: String,
     egg_id: String,
+    commitment: Option<Binary>,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let cfg = STATE.load(deps.storage)?;
@@ -144,60 +173,26 @@ pub fn execute_hatch_cw20(
         .map_err(ContractError::Std)?;
 
     //HATCH
-    let dragon = contracts.dragon;
-    let egg = contracts.egg;
-
-    //id
-    let id_type = id.clone();
-
-    //dragon type
-    let key1 = cfg.random_key / 10000;
-    let key2 = cfg.random_key % 100;
-    let secret = key1 + key2;
-    let type_id = id_type.chars().nth(secret as usize).unwrap();
-
-    let type_name;
-    match type_id {
-        '2' => type_name = "uncommon",
-        '3' => type_name = "rare",
-        '4' => type_name = "epic",
-        '5' => type_name = "legendary",
-        'P' => type_name = "legendary",
-        'K' => type_name = "epic",
-        'G' => type_name = "rare",
-        'W' => type_name = "uncommon",
-        _ => type_name = "common",
-    };
-
-    let dragon_mint =
-        generate_dragon_mint_msg(&*egg_id.clone(), type_name.to_string(), sender.to_string())?;
-
-    let dragon_mint_msg = CosmosMsg::Wasm(Execute {
-        contract_addr: dragon,
-        msg: to_binary(&dragon_mint)?,
-        funds: vec![],
-    });
+    let owner = deps.api.addr_validate(&sender)?;
+    let request = request_hatch(deps, &egg_id, owner, commitment)?;
 
     let burn_egg = Cw721ExecuteMsg::Burn {
         token_id: egg_id.clone(),
     };
 
     let burn_egg_msg = CosmosMsg::Wasm(Execute {
-        contract_addr: egg,
+        contract_addr: contracts.egg,
         msg: to_binary(&burn_egg)?,
         funds: vec![],
     });
 
     let res = Response::new()
-        .add_submessages(vec![
-            SubMsg::new(fee_payout_msg),
-            SubMsg::new(dragon_mint_msg),
-            SubMsg::new(burn_egg_msg),
-        ])
+        .add_submessages(vec![SubMsg::new(fee_payout_msg), SubMsg::new(burn_egg_msg)])
         .add_attribute("id", id)
         .add_attribute("egg_id ", egg_id)
         .add_attribute("action", "buy_cw20")
-        .add_attribute("fee", amount);
+        .add_attribute("fee", amount)
+        .add_attributes(request.attributes());
 
     Ok(res)
 }
@@ -215,54 +210,100 @@ pub fn execute_free_hatch(
             msg: "free hatch is no longer available".to_string(),
         });
     }
-    let dragon = contracts.dragon;
-    let egg = contracts.egg;
-
-    //id
-    let id_type = msg.clone().id;
-
-    //dragon type
-    let key1 = state.random_key / 10000;
-    let key2 = state.random_key % 100;
-    let secret = key1 + key2;
-    let type_id = id_type.chars().nth(secret as usize).unwrap();
-
-    let type_name;
-    match type_id {
-        '2' => type_name = "uncommon",
-        '3' => type_name = "rare",
-        '4' => type_name = "epic",
-        '5' => type_name = "legendary",
-        'P' => type_name = "legendary",
-        'K' => type_name = "epic",
-        'G' => type_name = "rare",
-        'W' => type_name = "uncommon",
-        _ => type_name = "common",
-    };
-
-    let dragon_mint = generate_dragon_mint_msg(
-        &*msg.clone().egg_id.to_string(),
-        type_name.to_string(),
-        info.sender.to_string(),
-    )?;
+    let request = request_hatch(deps, &msg.egg_id, info.sender.clone(), msg.commitment)?;
 
     let transfer_egg = Cw721ExecuteMsg::Burn {
-        token_id: String::from(msg.clone().egg_id),
+        token_id: msg.egg_id,
     };
 
     Ok(Response::new()
         .add_message(CosmosMsg::Wasm(Execute {
-            contract_addr: egg,
+            contract_addr: contracts.egg,
             msg: to_binary(&transfer_egg)?,
             funds: vec![],
         }))
+        .add_attribute("egg burn", msg.id)
+        .add_attribute("hatch for", info.sender)
+        .add_attributes(request.attributes()))
+}
+
+/// Commits the hatch of `egg_id` to a draw that nobody can know yet. The egg
+/// is burned right away, so a hatch can't be taken back once its outcome is
+/// known.
+fn request_hatch(
+    deps: DepsMut,
+    egg_id: &str,
+    owner: Addr,
+    commitment: Option<Binary>,
+) -> Result<RandomnessRequest, ContractError> {
+    if PENDING_HATCHES.has(deps.storage, egg_id) {
+        return Err(ContractError::HatchPending {
+            egg_id: egg_id.to_string(),
+        });
+    }
+    let source = RANDOMNESS_SOURCE.load(deps.storage)?;
+    let request = request_randomness(deps.as_ref(), &source, egg_id.as_bytes(), commitment)?;
+    let hatch = PendingHatch {
+        owner,
+        weights: RARITY_WEIGHTS.load(deps.storage)?,
+        request: request.clone(),
+    };
+    PENDING_HATCHES.save(deps.storage, egg_id, &hatch)?;
+    Ok(request)
+}
+
+/// Draws the kind of the dragon hatched from `egg_id` from the rarity weights
+/// of its request and mints it to the owner of the hatch.
+pub fn execute_fulfil_hatch(
+    deps: DepsMut,
+    env: Env,
+    egg_id: String,
+    secret: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let hatch = PENDING_HATCHES.load(deps.storage, &egg_id)?;
+    let randomness = fulfil_randomness(deps.as_ref(), &env, &hatch.request, secret)?;
+    let (roll, picked) = randomness
+        .pick(&hatch.weights, |w| w.weight)
+        .ok_or(ContractError::InvalidRarityWeights { total: 0 })?;
+    let kind = picked.kind.clone();
+    PENDING_HATCHES.remove(deps.storage, &egg_id);
+
+    let contracts = CONTRACTS.load(deps.storage)?;
+    let dragon_mint =
+        generate_dragon_mint_msg(deps.storage, &egg_id, kind.clone(), hatch.owner.to_string())?;
+
+    Ok(Response::new()
         .add_message(CosmosMsg::Wasm(Execute {
-            contract_addr: dragon,
+            contract_addr: contracts.dragon,
             msg: to_binary(&dragon_mint)?,
             funds: vec![],
         }))
-        .add_attribute("egg burn", msg.id)
-        .add_attribute("mint dragon for", info.sender))
+        .add_attribute("action", "fulfil_hatch")
+        .add_attribute("egg_id", egg_id)
+        .add_attribute("mint dragon for", hatch.owner)
+        .add_attribute("kind", kind)
+        .add_attribute("roll", roll.to_string())
+        .add_attributes(randomness.attributes()))
+}
+
+fn validate_rarity_weights(weights: &[RarityWeight]) -> Result<(), ContractError> {
+    for (i, weight) in weights.iter().enumerate() {
+        if !DRAGON_KINDS.contains(&&*weight.kind) {
+            return Err(ContractError::UnknownKind {
+                kind: weight.kind.clone(),
+            });
+        }
+        if weights[..i].iter().any(|w| w.kind == weight.kind) {
+            return Err(ContractError::DuplicateKind {
+                kind: weight.kind.clone(),
+            });
+        }
+    }
+    let total: u64 = weights.iter().map(|w| w.weight).sum();
+    if total != 10000 {
+        return Err(ContractError::InvalidRarityWeights { total });
+    }
+    Ok(())
 }
 
 /// Egg mint for eggsale
@@ -348,9 +389,7 @@ pub fn execute_dragon_birth(
 pub fn execute_edit_state(
     deps: DepsMut,
     info: MessageInfo,
-    new_owner: String,
     base_price: Uint128,
-    random_key: i32,
     hatch_price: Uint128,
     egg_sale_size: Uint64,
     allowed_cw20: Addr,
@@ -362,10 +401,9 @@ pub fn execute_edit_state(
         });
     }
     let new = State {
-        owner: new_owner,
+        owner: state.owner,
         base_price,
         hatch_price,
-        random_key,
         egg_sale_size,
         allowed_cw20,
     };
@@ -373,6 +411,84 @@ pub fn execute_edit_state(
     Ok(Response::new())
 }
 
+pub fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
+    STATE.save(deps.storage, &state)?;
+    Ok(res)
+}
+
+pub fn execute_edit_rarity_weights(
+    deps: DepsMut,
+    info: MessageInfo,
+    weights: Vec<RarityWeight>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            msg: "rarity weights can only be edited by the owner".to_string(),
+        });
+    }
+    validate_rarity_weights(&weights)?;
+    RARITY_WEIGHTS.save(deps.storage, &weights)?;
+
+    let mut res = Response::new().add_attribute("action", "edit_rarity_weights");
+    for weight in weights {
+        res = res.add_attribute(weight.kind, weight.weight.to_string());
+    }
+    Ok(res)
+}
+
+pub fn execute_edit_randomness_source(
+    deps: DepsMut,
+    info: MessageInfo,
+    source: RandomnessSource,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            msg: "randomness source can only be edited by the owner".to_string(),
+        });
+    }
+    validate_source(deps.api, &source)?;
+    RANDOMNESS_SOURCE.save(deps.storage, &source)?;
+    Ok(Response::new().add_attribute("action", "edit_randomness_source"))
+}
+
+pub fn execute_edit_metadata_template(
+    deps: DepsMut,
+    info: MessageInfo,
+    template: MetadataTemplate,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {
+            msg: "metadata templates can only be edited by the owner".to_string(),
+        });
+    }
+    let kind = template.kind.to_lowercase();
+    if !DRAGON_KINDS.contains(&&*kind) {
+        return Err(ContractError::UnknownKind { kind });
+    }
+    save_metadata_templates(deps.storage, &[template])?;
+    Ok(Response::new()
+        .add_attribute("action", "edit_metadata_template")
+        .add_attribute("kind", kind))
+}
+
 pub fn execute_edit_contracts(
     deps: DepsMut,
     info: MessageInfo,
@@ -398,11 +514,59 @@ pub fn execute_edit_contracts(
     Ok(Response::new())
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let from = check_migration(
+        &stored.contract,
+        &stored.version,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+    if from < Version::new(0, 2, 0) {
+        migrate_v0_2(deps.branch(), msg.randomness_source)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// 0.2.0 dropped `random_key` from `State` and draws kinds from stored
+/// rarity weights and a randomness beacon, minting them from stored metadata
+/// templates.
+fn migrate_v0_2(deps: DepsMut, source: Option<RandomnessSource>) -> Result<(), ContractError> {
+    let storage = deps.storage;
+    let state = STATE.load(storage)?;
+    STATE.save(storage, &state)?;
+    if RARITY_WEIGHTS.may_load(storage)?.is_none() {
+        RARITY_WEIGHTS.save(storage, &default_rarity_weights())?;
+    }
+    if let Some(source) = source {
+        validate_source(deps.api, &source)?;
+        RANDOMNESS_SOURCE.save(storage, &source)?;
+    } else if RANDOMNESS_SOURCE.may_load(storage)?.is_none() {
+        return Err(ContractError::MissingRandomnessSource {});
+    }
+    let templates = METADATA_TEMPLATES.keys(storage, None, None, Order::Ascending);
+    if templates.count() == 0 {
+        save_metadata_templates(storage, &default_metadata_templates())?;
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetState {} => to_binary(&query_state(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
         QueryMsg::GetEggsaleOwnedCount {} => to_binary(&query_eggsale(deps)?),
+        QueryMsg::RarityWeights {} => to_binary(&query_rarity_weights(deps)?),
+        QueryMsg::MetadataTemplates {} => to_binary(&query_metadata_templates(deps.storage)?),
+        QueryMsg::PendingHatch { egg_id } => {
+            to_binary(&PENDING_HATCHES.load(deps.storage, &egg_id)?)
+        }
     }
 }
 
@@ -413,7 +577,6 @@ fn query_state(deps: Deps) -> StdResult<GetStateResponse> {
     Ok(GetStateResponse {
         owner: state.owner,
         base_price: state.base_price,
-        random_key: state.random_key,
         hatch_price: state.hatch_price,
         total_eggs,
         egg_sale_size: state.egg_sale_size,
@@ -434,3 +597,10 @@ fn query_eggsale(deps: Deps) -> StdResult<GetEggsaleInfoResponse> {
         base_price: state.base_price,
     })
 }
+
+fn query_rarity_weights(deps: Deps) -> StdResult<RarityWeightsResponse> {
+    Ok(RarityWeightsResponse {
+        weights: RARITY_WEIGHTS.load(deps.storage)?,
+        source: RANDOMNESS_SOURCE.load(deps.storage)?,
+    })
+}
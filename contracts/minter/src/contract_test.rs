@@ -4,6 +4,29 @@ mod tests {
     //use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
     //use cosmwasm_std::{coins, from_binary};
     //use schemars::_serde_json::to_string;
+    use crate::contract::{execute, instantiate, migrate, query};
+    use crate::msg::{
+        ExecuteMsg, GetStateResponse, InstantiateMsg, MigrateMsg, MintEggDragon, MintExecuteMsg,
+        QueryMsg, RarityWeightsResponse,
+    };
+    use crate::state::{
+        default_metadata_templates, default_rarity_weights, PendingHatch, RarityWeight,
+        RANDOMNESS_SOURCE, RARITY_WEIGHTS, STATE,
+    };
+    use crate::ContractError;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+    use cosmwasm_std::{
+        from_binary, Addr, CosmosMsg, DepsMut, Env, MemoryStorage, MessageInfo, OwnedDeps,
+        Response, Uint128, Uint64, WasmMsg,
+    };
+    use cw2::set_contract_version;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
+    use serde::{Deserialize, Serialize};
+    use stake_dragons_common::ownership::OwnershipMsg;
+    use stake_dragons_common::random::mock::mock_beacon;
+    use stake_dragons_common::random::{RandomnessError, RandomnessSource};
+    use stake_dragons_common::templates::MetadataTemplatesResponse;
 
     /*
     #[test]
@@ -140,4 +163,408 @@ mod tests {
 
         */
     }
+
+    fn instantiate_msg(weights: Option<Vec<RarityWeight>>) -> InstantiateMsg {
+        InstantiateMsg {
+            base_price: Uint128::new(1),
+            hatch_price: Uint128::new(1),
+            egg_sale_size: Uint64::new(10),
+            allowed_cw20: Addr::unchecked("drgn"),
+            rarity_weights: weights,
+            randomness_source: beacon(),
+        }
+    }
+
+    fn beacon() -> RandomnessSource {
+        RandomnessSource::Beacon {
+            contract: "beacon".to_string(),
+        }
+    }
+
+    fn setup(weights: Option<Vec<RarityWeight>>) -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier
+            .update_wasm(mock_beacon("beacon", 1, vec![1u8; 32]));
+        let msg = instantiate_msg(weights);
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let msg = ExecuteMsg::EditContracts {
+            egg: "egg".to_string(),
+            dragon: "dragon".to_string(),
+            recipient: "recipient".to_string(),
+            multisig: "multisig".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        deps
+    }
+
+    fn request_hatch(
+        deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        egg_id: &str,
+    ) -> Response {
+        let msg = ExecuteMsg::GenesisHatch(MintEggDragon {
+            id: "any".to_string(),
+            egg_id: egg_id.to_string(),
+            commitment: None,
+        });
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap()
+    }
+
+    /// Publishes the round the hatch of `egg_id` waits for and fulfils it.
+    fn fulfil_hatch(
+        deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        egg_id: &str,
+    ) -> Response {
+        let msg = QueryMsg::PendingHatch {
+            egg_id: egg_id.to_string(),
+        };
+        let hatch: PendingHatch =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        deps.querier
+            .update_wasm(mock_beacon("beacon", hatch.request.round, vec![1u8; 32]));
+        let msg = ExecuteMsg::FulfilHatch {
+            egg_id: egg_id.to_string(),
+            secret: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap()
+    }
+
+    fn hatch(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>, egg_id: &str) -> Response {
+        request_hatch(deps, egg_id);
+        fulfil_hatch(deps, egg_id)
+    }
+
+    fn hatched_kind(res: &Response) -> String {
+        res.attributes
+            .iter()
+            .find(|a| a.key == "kind")
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    fn only(kind: &str) -> Vec<RarityWeight> {
+        vec![RarityWeight {
+            kind: kind.to_string(),
+            weight: 10000,
+        }]
+    }
+
+    #[test]
+    fn hatch_ignores_caller_id() {
+        let mut deps = setup(Some(only("legendary")));
+        for id in ["2222222222", "WWWWWWWWWW", "0000000000"] {
+            let msg = ExecuteMsg::GenesisHatch(MintEggDragon {
+                id: id.to_string(),
+                egg_id: "000001".to_string(),
+                commitment: None,
+            });
+            let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+            assert_eq!(res.messages.len(), 1);
+            let res = fulfil_hatch(&mut deps, "000001");
+            assert_eq!(hatched_kind(&res), "legendary");
+            assert_eq!(res.messages.len(), 1);
+        }
+    }
+
+    #[test]
+    fn hatch_keeps_the_weights_it_was_requested_with() {
+        let mut deps = setup(Some(only("legendary")));
+        request_hatch(&mut deps, "000001");
+        let msg = ExecuteMsg::EditRarityWeights {
+            weights: only("common"),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let res = fulfil_hatch(&mut deps, "000001");
+        assert_eq!(hatched_kind(&res), "legendary");
+        let res = hatch(&mut deps, "000002");
+        assert_eq!(hatched_kind(&res), "common");
+    }
+
+    #[test]
+    fn rarity_weights_are_validated() {
+        let mut deps = setup(None);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::RarityWeights {}).unwrap();
+        let res: RarityWeightsResponse = from_binary(&res).unwrap();
+        assert_eq!(res.weights, default_rarity_weights());
+        assert_eq!(res.source, beacon());
+
+        let msg = ExecuteMsg::EditRarityWeights {
+            weights: only("rare"),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        let bad = ExecuteMsg::EditRarityWeights {
+            weights: vec![RarityWeight {
+                kind: "rare".to_string(),
+                weight: 9999,
+            }],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), bad).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InvalidRarityWeights { total: 9999 }
+        ));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::EditRarityWeights {
+                weights: only("mythic"),
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UnknownKind { .. }));
+
+        let duplicate = ExecuteMsg::EditRarityWeights {
+            weights: vec![
+                RarityWeight {
+                    kind: "legendary".to_string(),
+                    weight: 1,
+                },
+                RarityWeight {
+                    kind: "common".to_string(),
+                    weight: 9998,
+                },
+                RarityWeight {
+                    kind: "legendary".to_string(),
+                    weight: 1,
+                },
+            ],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            duplicate,
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::DuplicateKind { kind } if kind == "legendary"));
+
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let res = hatch(&mut deps, "000002");
+        assert_eq!(hatched_kind(&res), "rare");
+    }
+
+    #[test]
+    fn hatch_mints_from_the_kind_template() {
+        let mut deps = setup(Some(only("rare")));
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::MetadataTemplates {}).unwrap();
+        let res: MetadataTemplatesResponse = from_binary(&res).unwrap();
+        assert_eq!(res.templates.len(), default_metadata_templates().len());
+
+        let rare = res.templates.iter().find(|t| t.kind == "rare");
+        let mut template = rare.unwrap().clone();
+        template.image_base_uri = "ipfs://new-art".to_string();
+        let msg = ExecuteMsg::EditMetadataTemplate {
+            template: template.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        template.kind = "mythic".to_string();
+        let msg = ExecuteMsg::EditMetadataTemplate { template };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownKind { .. }));
+
+        let res = hatch(&mut deps, "000004");
+        let MintExecuteMsg::Mint(mint) = match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => from_binary(msg).unwrap(),
+            msg => panic!("unexpected message {:?}", msg),
+        };
+        let metadata = mint.base.extension.unwrap();
+        assert_eq!(metadata.image.unwrap(), "ipfs://new-art/3.png");
+        assert_eq!(mint.extension[0].value, "rare");
+    }
+
+    #[test]
+    fn hatch_waits_for_the_next_round() {
+        let mut deps = setup(None);
+        let res = request_hatch(&mut deps, "000003");
+        let round = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "randomness_round")
+            .unwrap();
+        assert_eq!(round.value, "2");
+        let msg = ExecuteMsg::GenesisHatch(MintEggDragon {
+            id: "any".to_string(),
+            egg_id: "000003".to_string(),
+            commitment: None,
+        });
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::HatchPending { .. }));
+
+        let fulfil = ExecuteMsg::FulfilHatch {
+            egg_id: "000003".to_string(),
+            secret: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            fulfil.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Randomness(RandomnessError::RoundNotPublished { round: 2 })
+        ));
+
+        let res = fulfil_hatch(&mut deps, "000003");
+        let round = res
+            .attributes
+            .iter()
+            .find(|a| a.key == "randomness_round")
+            .unwrap();
+        assert_eq!(round.value, "2");
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), fulfil).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+    }
+
+    #[test]
+    fn renounced_minter_cannot_be_edited() {
+        let mut deps = setup(None);
+        let edit = ExecuteMsg::EditState {
+            base_price: Uint128::new(2),
+            hatch_price: Uint128::new(2),
+            egg_sale_size: Uint64::new(10),
+            allowed_cw20: Addr::unchecked("drgn"),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            edit.clone(),
+        )
+        .unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetState {}).unwrap();
+        let state: GetStateResponse = from_binary(&res).unwrap();
+        assert_eq!(state.owner, "owner");
+        assert_eq!(state.base_price, Uint128::new(2));
+
+        let msg = ExecuteMsg::UpdateOwnership(OwnershipMsg::RenounceOwnership {});
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), edit).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+    }
+
+    /// `State` as stored by 0.1.0, which still had the hatch `random_key`.
+    #[derive(Serialize, Deserialize)]
+    struct LegacyState {
+        owner: String,
+        base_price: Uint128,
+        hatch_price: Uint128,
+        random_key: i32,
+        egg_sale_size: Uint64,
+        allowed_cw20: Addr,
+    }
+
+    fn instantiate_v0_1(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> Result<Response, ContractError> {
+        let res = instantiate(deps.branch(), env, info, msg)?;
+        let state = STATE.load(deps.storage)?;
+        let legacy = LegacyState {
+            owner: state.owner,
+            base_price: state.base_price,
+            hatch_price: state.hatch_price,
+            random_key: 7,
+            egg_sale_size: state.egg_sale_size,
+            allowed_cw20: state.allowed_cw20,
+        };
+        Item::new("state").save(deps.storage, &legacy)?;
+        RARITY_WEIGHTS.remove(deps.storage);
+        RANDOMNESS_SOURCE.remove(deps.storage);
+        set_contract_version(deps.storage, "crates.io:minter", "0.1.0")?;
+        Ok(res)
+    }
+
+    #[test]
+    fn migrate_upgrades_v0_1_layout() {
+        let mut app = App::default();
+        let legacy = app.store_code(Box::new(ContractWrapper::new(
+            execute,
+            instantiate_v0_1,
+            query,
+        )));
+        let current = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+        ));
+        let admin = Addr::unchecked("admin");
+        let addr = app
+            .instantiate_contract(
+                legacy,
+                admin.clone(),
+                &instantiate_msg(None),
+                &[],
+                "minter",
+                Some(admin.to_string()),
+            )
+            .unwrap();
+        let err = app
+            .migrate_contract(
+                admin.clone(),
+                addr.clone(),
+                &MigrateMsg {
+                    randomness_source: None,
+                },
+                current,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap().to_string(),
+            ContractError::MissingRandomnessSource {}.to_string()
+        );
+        let msg = MigrateMsg {
+            randomness_source: Some(beacon()),
+        };
+        app.migrate_contract(admin, addr.clone(), &msg, current)
+            .unwrap();
+
+        let state: GetStateResponse = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::GetState {})
+            .unwrap();
+        assert_eq!(state.owner, "admin");
+        let weights: RarityWeightsResponse = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::RarityWeights {})
+            .unwrap();
+        assert_eq!(weights.weights, default_rarity_weights());
+        assert_eq!(weights.source, beacon());
+    }
+
+    #[test]
+    fn migrate_keeps_configured_weights() {
+        let mut deps = setup(Some(only("epic")));
+        set_contract_version(deps.as_mut().storage, "crates.io:minter", "0.1.0").unwrap();
+        let msg = MigrateMsg {
+            randomness_source: None,
+        };
+        migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::RarityWeights {}).unwrap();
+        let weights: RarityWeightsResponse = from_binary(&res).unwrap();
+        assert_eq!(weights.weights, only("epic"));
+
+        set_contract_version(deps.as_mut().storage, "crates.io:minter", "1.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Migrate(_)));
+    }
 }
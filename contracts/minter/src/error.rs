@@ -1,4 +1,7 @@
 use cosmwasm_std::{StdError, Uint128};
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::ownership::OwnershipError;
+use stake_dragons_common::random::RandomnessError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,12 +9,36 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
+    #[error("{0}")]
+    Randomness(#[from] RandomnessError),
+
     #[error("Unauthorized {msg}")]
     Unauthorized { msg: String },
 
     #[error("Generate metadata failed")]
     MintError {},
 
+    #[error("Rarity weights must add up to 10000 basis points, got {total}")]
+    InvalidRarityWeights { total: u64 },
+
+    #[error("Unknown dragon kind: {kind}")]
+    UnknownKind { kind: String },
+
+    #[error("Dragon kind {kind} is weighted more than once")]
+    DuplicateKind { kind: String },
+
+    #[error("Egg {egg_id} is already hatching")]
+    HatchPending { egg_id: String },
+
+    #[error("A randomness source is required to migrate")]
+    MissingRandomnessSource {},
+
     #[error("Egg sale limit reached")]
     EggSaleLimit {},
 
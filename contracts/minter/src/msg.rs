@@ -1,66 +1,35 @@
-use cosmwasm_std::{Addr, Uint128, Uint64};
+use crate::state::RarityWeight;
+use cosmwasm_std::{Addr, Binary, Uint128, Uint64};
 use cw20::Cw20ReceiveMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::ownership::OwnershipMsg;
+use stake_dragons_common::random::RandomnessSource;
+use stake_dragons_common::templates::MetadataTemplate;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, MintExecuteMsg, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub base_price: Uint128,
     pub hatch_price: Uint128,
-    pub random_key: i32,
     pub egg_sale_size: Uint64,
     pub allowed_cw20: Addr,
-}
-
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-//Change some to mandatory some to optional
-// see: https://docs.opensea.io/docs/metadata-standards
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub external_url: Option<String>,
-    pub attributes: Vec<Trait>,
-    pub image_data: Option<String>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub extension: Vec<Trait>,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct BaseMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-}
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct DragonMintMsg {
-    pub mint: CustomMintMsg,
+    pub rarity_weights: Option<Vec<RarityWeight>>,
+    pub randomness_source: RandomnessSource,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct EggMintMsg {
-    pub mint: BaseMintMsg,
+pub struct MigrateMsg {
+    /// Required when migrating from a version that stored no source.
+    pub randomness_source: Option<RandomnessSource>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintEggDragon {
     pub id: String,
     pub egg_id: String,
+    /// sha256 of a secret to reveal in `FulfilHatch`.
+    pub commitment: Option<Binary>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -74,18 +43,33 @@ pub struct ClaimMessage {
 pub enum ExecuteMsg {
     MintEgg {},
     GenesisHatch(MintEggDragon),
+    /// Mints the dragon of a hatch once the beacon round it waits for is
+    /// published. Anyone can fulfil a hatch made without a commitment.
+    FulfilHatch {
+        egg_id: String,
+        secret: Option<Binary>,
+    },
     DragonBirth {
         id: String,
         owner: String,
     },
     EditState {
-        new_owner: String,
         base_price: Uint128,
-        random_key: i32,
         hatch_price: Uint128,
         egg_sale_size: Uint64,
         allowed_cw20: Addr,
     },
+    UpdateOwnership(OwnershipMsg),
+    EditRarityWeights {
+        weights: Vec<RarityWeight>,
+    },
+    EditRandomnessSource {
+        source: RandomnessSource,
+    },
+    /// Adds the template of a kind or replaces the one it had.
+    EditMetadataTemplate {
+        template: MetadataTemplate,
+    },
     EditContracts {
         egg: String,
         dragon: String,
@@ -98,7 +82,11 @@ pub enum ExecuteMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReceiveMsg {
-    Hatch { id: String, egg_id: String },
+    Hatch {
+        id: String,
+        egg_id: String,
+        commitment: Option<Binary>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -106,7 +94,11 @@ pub enum ReceiveMsg {
 pub enum QueryMsg {
     // GetCount returns the current count as a json-encoded number
     GetState {},
+    PendingOwner {},
     GetEggsaleOwnedCount {},
+    RarityWeights {},
+    MetadataTemplates {},
+    PendingHatch { egg_id: String },
 }
 
 // We define a custom struct for each query response
@@ -114,7 +106,6 @@ pub enum QueryMsg {
 pub struct GetStateResponse {
     pub owner: String,
     pub base_price: Uint128,
-    pub random_key: i32,
     pub hatch_price: Uint128,
     pub total_eggs: Uint64,
     pub egg_sale_size: Uint64,
@@ -130,3 +121,9 @@ pub struct GetEggsaleInfoResponse {
     pub size: Uint64,
     pub base_price: Uint128,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RarityWeightsResponse {
+    pub weights: Vec<RarityWeight>,
+    pub source: RandomnessSource,
+}
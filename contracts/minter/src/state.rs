@@ -2,14 +2,16 @@ use cosmwasm_std::{Addr, Uint128, Uint64};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
+use stake_dragons_common::random::{RandomnessRequest, RandomnessSource};
+use stake_dragons_common::templates::MetadataTemplate;
+use stake_dragons_common::Trait;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub owner: String,
     pub base_price: Uint128,
     pub hatch_price: Uint128,
-    pub random_key: i32,
     pub egg_sale_size: Uint64,
     pub allowed_cw20: Addr,
 }
@@ -25,3 +27,72 @@ pub const STATE: Item<State> = Item::new("state");
 pub const CONTRACTS: Item<ContractAddressList> = Item::new("contract_list");
 pub const EGG_SALE_COUNT: Item<Uint64> = Item::new("eggsale_size");
 pub const TOTAL_EGGS: Item<Uint64> = Item::new("total_eggs");
+
+/// Chance of hatching a given dragon kind, in basis points of 10000.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RarityWeight {
+    pub kind: String,
+    pub weight: u64,
+}
+
+pub const RARITY_WEIGHTS: Item<Vec<RarityWeight>> = Item::new("rarity_weights");
+pub const RANDOMNESS_SOURCE: Item<RandomnessSource> = Item::new("randomness_source");
+
+/// Egg burned for a hatch whose dragon is minted once its draw is fulfilled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingHatch {
+    pub owner: Addr,
+    /// rarity weights at the time of the request
+    pub weights: Vec<RarityWeight>,
+    pub request: RandomnessRequest,
+}
+
+pub const PENDING_HATCHES: Map<&str, PendingHatch> = Map::new("pending_hatches");
+
+pub const DRAGON_KINDS: [&str; 5] = ["common", "uncommon", "rare", "epic", "legendary"];
+
+/// Same odds as the legacy character table: 28 of 36 characters hatched a
+/// common dragon and each other kind had 2 characters.
+pub fn default_rarity_weights() -> Vec<RarityWeight> {
+    [
+        ("common", 7778),
+        ("uncommon", 555),
+        ("rare", 555),
+        ("epic", 556),
+        ("legendary", 556),
+    ]
+    .iter()
+    .map(|(kind, weight)| RarityWeight {
+        kind: kind.to_string(),
+        weight: *weight,
+    })
+    .collect()
+}
+
+/// The art and traits the minter gave each kind before templates were stored.
+pub fn default_metadata_templates() -> Vec<MetadataTemplate> {
+    [
+        ("common", "60", "0.5"),
+        ("uncommon", "45", "1"),
+        ("rare", "30", "2"),
+        ("epic", "20", "5"),
+        ("legendary", "15", "10"),
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, (kind, ovulation_period, daily_income))| MetadataTemplate {
+        kind: kind.to_string(),
+        name: format!("{}_stake_dragon", kind),
+        description: format!(
+            "This is the {} type of the stake dragon. It has a {} day ovulation period and {} DRGN daily income when staked",
+            kind, ovulation_period, daily_income
+        ),
+        image_base_uri: "https://bafybeiacxf7hsoqkyhg6fqa6ktnxvtoni32tyuqd6w2mgpk7vosxg4y2ve.ipfs.nftstorage.link".to_string(),
+        image: Some(format!("{}.png", i + 1)),
+        traits: vec![
+            Trait::new("ovulation_period", ovulation_period),
+            Trait::new("daily_income", daily_income),
+        ],
+    })
+    .collect()
+}
@@ -1,14 +1,25 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, SubMsg, Uint128,
+    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult, Storage,
+    SubMsg, Timestamp, Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, GetStateResponse, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{
+    DistributedResponse, ExecuteMsg, GetStateResponse, InstantiateMsg, MigrateMsg, QueryMsg,
+    RecipientDistributedResponse, SourceMsg, SourceResponse, SourcesResponse,
+};
+use crate::state::{
+    EpochUsage, RewardSource, State, DISTRIBUTED, EPOCH_USAGE, LEGACY_STATE,
+    RECIPIENT_DISTRIBUTED, SOURCES, STATE, TOTAL_DISTRIBUTED,
+};
 use cw20::{Cw20Contract, Cw20ExecuteMsg};
+use stake_dragons_common::migrate::{check_migration, Version};
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
+use stake_dragons_common::reward::SECONDS_PER_DAY;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:stake-reward";
@@ -16,19 +27,20 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     let state = State {
         owner: info.sender.into_string(),
-        dragon_contract: msg.dragon_contract,
-        cw20_contract: msg.cw20_contract,
         admin: msg.admin,
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    for source in msg.sources {
+        save_source(deps.branch(), &env, source)?;
+    }
 
     Ok(Response::new().add_attribute("method", "instantiate"))
 }
@@ -36,25 +48,24 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Claim { recipient, amount } => {
-            execute_distribute_reward(deps, info, recipient, amount)
+            execute_distribute_reward(deps, env, info, recipient, amount)
         }
-        ExecuteMsg::EditState {
-            admin,
-            dragon_contract,
-            cw20_contract,
-            owner,
-        } => execute_edit_state(deps, info, admin, dragon_contract, owner, cw20_contract),
+        ExecuteMsg::EditState { admin } => execute_edit_state(deps, info, admin),
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, env, info, msg),
+        ExecuteMsg::SetSource(source) => execute_set_source(deps, env, info, source),
+        ExecuteMsg::RemoveSource { contract } => execute_remove_source(deps, info, contract),
     }
 }
 
 pub fn execute_distribute_reward(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     recipient: String,
     amount: Uint128,
@@ -62,40 +73,142 @@ pub fn execute_distribute_reward(
     let state = STATE.load(deps.storage)?;
     let owner = state.admin;
 
-    //Only owner and dragon contract can execute this message
-    if info.sender != state.owner && info.sender != state.dragon_contract {
-        return Err(ContractError::Unauthorized {});
+    //Only registered reward sources can execute this message
+    let source = SOURCES
+        .may_load(deps.storage, &info.sender)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let mut usage = epoch_usage(deps.storage, &info.sender, &source, env.block.time)?;
+    let remaining = source.epoch_cap.saturating_sub(usage.distributed);
+    if amount > remaining {
+        return Err(ContractError::EmissionCapExceeded {
+            requested: amount,
+            remaining,
+        });
     }
+    usage.distributed += amount;
+    EPOCH_USAGE.save(deps.storage, &info.sender, &usage)?;
+    TOTAL_DISTRIBUTED.update(deps.storage, &info.sender, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_add(amount)?)
+    })?;
+    DISTRIBUTED.update(
+        deps.storage,
+        (&info.sender, &recipient_addr),
+        |paid| -> StdResult<_> { Ok(paid.unwrap_or_default().checked_add(amount)?) },
+    )?;
+    RECIPIENT_DISTRIBUTED.update(deps.storage, &recipient_addr, |paid| -> StdResult<_> {
+        Ok(paid.unwrap_or_default().checked_add(amount)?)
+    })?;
 
     let cw20_execute_send = Cw20ExecuteMsg::TransferFrom { owner,recipient, amount };
-    let reward_send_msg = Cw20Contract(state.cw20_contract)
+    let reward_send_msg = Cw20Contract(source.reward_token)
         .call(cw20_execute_send)
         .map_err(ContractError::Std)?;
 
     Ok(Response::new()
         .add_submessages(vec![SubMsg::new(reward_send_msg)])
-        .add_attribute("method", "reset"))
+        .add_attribute("method", "reset")
+        .add_attribute("source", info.sender)
+        .add_attribute("epoch", usage.epoch.to_string())
+        .add_attribute("epoch_distributed", usage.distributed))
+}
+
+/// What `contract` paid out during the epoch `now` falls in.
+fn epoch_usage(
+    storage: &dyn Storage,
+    contract: &Addr,
+    source: &RewardSource,
+    now: Timestamp,
+) -> StdResult<EpochUsage> {
+    let epoch = now.seconds().saturating_sub(source.start) / source.epoch_length;
+    Ok(EPOCH_USAGE
+        .may_load(storage, contract)?
+        .filter(|usage| usage.epoch == epoch)
+        .unwrap_or(EpochUsage {
+            epoch,
+            distributed: Uint128::zero(),
+        }))
+}
+
+/// Registers or updates a source. An existing source keeps its epoch
+/// schedule unless the epoch length changes.
+fn save_source(deps: DepsMut, env: &Env, msg: SourceMsg) -> Result<Addr, ContractError> {
+    if msg.epoch_length.is_zero() {
+        return Err(ContractError::InvalidEpochLength {});
+    }
+    let contract = deps.api.addr_validate(&msg.contract)?;
+    let existing = SOURCES.may_load(deps.storage, &contract)?;
+    let start = match existing {
+        Some(source) if source.epoch_length == msg.epoch_length.u64() => source.start,
+        _ => {
+            EPOCH_USAGE.remove(deps.storage, &contract);
+            env.block.time.seconds()
+        }
+    };
+    let source = RewardSource {
+        reward_token: deps.api.addr_validate(&msg.reward_token)?,
+        start,
+        epoch_length: msg.epoch_length.u64(),
+        epoch_cap: msg.epoch_cap,
+    };
+    SOURCES.save(deps.storage, &contract, &source)?;
+    Ok(contract)
+}
+
+pub fn execute_set_source(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: SourceMsg,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let epoch_cap = msg.epoch_cap;
+    let contract = save_source(deps, &env, msg)?;
+    Ok(Response::new()
+        .add_attribute("method", "set_source")
+        .add_attribute("source", contract)
+        .add_attribute("epoch_cap", epoch_cap))
+}
+
+pub fn execute_remove_source(
+    deps: DepsMut,
+    info: MessageInfo,
+    contract: String,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let addr = deps.api.addr_validate(&contract)?;
+    if !SOURCES.has(deps.storage, &addr) {
+        return Err(ContractError::UnknownSource { contract });
+    }
+    // totals are kept so past payouts stay queryable
+    SOURCES.remove(deps.storage, &addr);
+    EPOCH_USAGE.remove(deps.storage, &addr);
+    Ok(Response::new()
+        .add_attribute("method", "remove_source")
+        .add_attribute("source", addr))
 }
 
 pub fn execute_edit_state(
     deps: DepsMut,
     info: MessageInfo,
     admin: String,
-    dragon_contract: String,
-    owner: String,
-    cw20_contract: Addr,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
 
-    //Only owner and dragon contract can execute this message
+    //Only owner and admin can execute this message
     if info.sender != state.owner && info.sender != state.admin {
         return Err(ContractError::Unauthorized {});
     }
 
     let new_state = State {
-        owner,
-        dragon_contract,
-        cw20_contract,
+        owner: state.owner,
         admin,
     };
     STATE.save(deps.storage, &new_state)?;
@@ -103,10 +216,80 @@ pub fn execute_edit_state(
     Ok(Response::new().add_attribute("method", "reset"))
 }
 
+pub fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
+    STATE.save(deps.storage, &state)?;
+    Ok(res)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let from = check_migration(
+        &stored.contract,
+        &stored.version,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+    if from < Version::new(0, 2, 0) {
+        migrate_v0_2(deps.storage, &env)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// 0.2.0 keeps a registry of reward sources. The old dragon contract
+/// becomes the only source, with daily epochs and no cap until the owner
+/// sets one.
+fn migrate_v0_2(storage: &mut dyn Storage, env: &Env) -> StdResult<()> {
+    let legacy = LEGACY_STATE.load(storage)?;
+    let state = State {
+        owner: legacy.owner,
+        admin: legacy.admin,
+    };
+    STATE.save(storage, &state)?;
+    let source = RewardSource {
+        reward_token: legacy.cw20_contract,
+        start: env.block.time.seconds(),
+        epoch_length: SECONDS_PER_DAY,
+        epoch_cap: Uint128::MAX,
+    };
+    SOURCES.save(storage, &Addr::unchecked(legacy.dragon_contract), &source)?;
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetState {} => to_binary(&query_state(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
+        QueryMsg::Source { contract } => to_binary(&query_source(deps, env, contract)?),
+        QueryMsg::Sources { start_after, limit } => {
+            to_binary(&query_sources(deps, env, start_after, limit)?)
+        }
+        QueryMsg::Distributed { source, recipient } => {
+            to_binary(&query_distributed(deps, source, recipient)?)
+        }
+        QueryMsg::RecipientDistributed { recipient } => {
+            to_binary(&query_recipient_distributed(deps, recipient)?)
+        }
     }
 }
 
@@ -115,7 +298,87 @@ fn query_state(deps: Deps) -> StdResult<GetStateResponse> {
     Ok(GetStateResponse {
         owner: state.owner,
         admin: state.admin,
-        dragon_contract: state.dragon_contract,
-        cw20_contract: state.cw20_contract,
     })
 }
+
+fn source_response(
+    deps: Deps,
+    env: &Env,
+    contract: Addr,
+    source: RewardSource,
+) -> StdResult<SourceResponse> {
+    let usage = epoch_usage(deps.storage, &contract, &source, env.block.time)?;
+    Ok(SourceResponse {
+        total_distributed: TOTAL_DISTRIBUTED
+            .may_load(deps.storage, &contract)?
+            .unwrap_or_default(),
+        contract,
+        reward_token: source.reward_token,
+        epoch: usage.epoch,
+        epoch_start: source.start + usage.epoch * source.epoch_length,
+        epoch_length: source.epoch_length,
+        epoch_cap: source.epoch_cap,
+        epoch_distributed: usage.distributed,
+        remaining: source.epoch_cap.saturating_sub(usage.distributed),
+    })
+}
+
+fn query_source(deps: Deps, env: Env, contract: String) -> StdResult<SourceResponse> {
+    let addr = deps.api.addr_validate(&contract)?;
+    let source = SOURCES.load(deps.storage, &addr)?;
+    source_response(deps, &env, addr, source)
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+fn query_sources(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SourcesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let sources = SOURCES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (contract, source) = item?;
+            source_response(deps, &env, contract, source)
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(SourcesResponse { sources })
+}
+
+fn query_distributed(
+    deps: Deps,
+    source: String,
+    recipient: String,
+) -> StdResult<DistributedResponse> {
+    let source_addr = deps.api.addr_validate(&source)?;
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let amount = DISTRIBUTED
+        .may_load(deps.storage, (&source_addr, &recipient_addr))?
+        .unwrap_or_default();
+    Ok(DistributedResponse {
+        source,
+        recipient,
+        amount,
+    })
+}
+
+fn query_recipient_distributed(
+    deps: Deps,
+    recipient: String,
+) -> StdResult<RecipientDistributedResponse> {
+    let recipient_addr = deps.api.addr_validate(&recipient)?;
+    let amount = RECIPIENT_DISTRIBUTED
+        .may_load(deps.storage, &recipient_addr)?
+        .unwrap_or_default();
+    Ok(RecipientDistributedResponse { recipient, amount })
+}
@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{execute, instantiate, migrate, query};
+    use crate::msg::{
+        DistributedResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+        RecipientDistributedResponse, SourceMsg, SourceResponse, SourcesResponse,
+    };
+    use crate::state::{LegacyState, LEGACY_STATE};
+    use crate::ContractError;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+    use cosmwasm_std::{
+        from_binary, Addr, CosmosMsg, DepsMut, Env, MemoryStorage, OwnedDeps, Uint128, Uint64,
+        WasmMsg,
+    };
+    use cw2::set_contract_version;
+
+    const DAY: u64 = 86_400;
+
+    type MockDeps = OwnedDeps<MemoryStorage, MockApi, MockQuerier>;
+
+    fn source_msg(contract: &str, reward_token: &str, epoch_cap: u128) -> SourceMsg {
+        SourceMsg {
+            contract: contract.to_string(),
+            reward_token: reward_token.to_string(),
+            epoch_length: Uint64::new(DAY),
+            epoch_cap: Uint128::new(epoch_cap),
+        }
+    }
+
+    fn instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            admin: "treasury".to_string(),
+            sources: vec![
+                source_msg("dragons", "drgn", 1_000),
+                source_msg("cosmics", "cosmic_token", 100),
+            ],
+        }
+    }
+
+    fn claim(
+        deps: DepsMut,
+        env: Env,
+        source: &str,
+        recipient: &str,
+        amount: u128,
+    ) -> Result<String, ContractError> {
+        let msg = ExecuteMsg::Claim {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        };
+        let res = execute(deps, env, mock_info(source, &[]), msg)?;
+        // the token the transfer was sent to
+        match &res.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => Ok(contract_addr.clone()),
+            msg => panic!("unexpected message {:?}", msg),
+        }
+    }
+
+    fn query_source(deps: &MockDeps, env: Env, contract: &str) -> SourceResponse {
+        let msg = QueryMsg::Source {
+            contract: contract.to_string(),
+        };
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn sources_have_separate_budgets() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+
+        let token = claim(deps.as_mut(), env.clone(), "dragons", "alice", 600).unwrap();
+        assert_eq!(token, "drgn");
+        let token = claim(deps.as_mut(), env.clone(), "cosmics", "alice", 100).unwrap();
+        assert_eq!(token, "cosmic_token");
+
+        let err = claim(deps.as_mut(), env.clone(), "cosmics", "bob", 1).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::EmissionCapExceeded { requested, remaining }
+                if requested == Uint128::new(1) && remaining.is_zero()
+        ));
+        // the dragon budget is untouched by cosmic claims
+        claim(deps.as_mut(), env.clone(), "dragons", "bob", 400).unwrap();
+        let err = claim(deps.as_mut(), env.clone(), "stranger", "bob", 1).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        // the allowance comes back with the next epoch
+        env.block.time = env.block.time.plus_seconds(DAY);
+        claim(deps.as_mut(), env.clone(), "dragons", "alice", 200).unwrap();
+
+        let dragons = query_source(&deps, env.clone(), "dragons");
+        assert_eq!(dragons.epoch, 1);
+        assert_eq!(dragons.total_distributed, Uint128::new(1_200));
+        assert_eq!(dragons.epoch_distributed, Uint128::new(200));
+        assert_eq!(dragons.remaining, Uint128::new(800));
+        let cosmics = query_source(&deps, env.clone(), "cosmics");
+        assert_eq!(cosmics.total_distributed, Uint128::new(100));
+        assert_eq!(cosmics.remaining, Uint128::new(100));
+
+        let msg = QueryMsg::Distributed {
+            source: "dragons".to_string(),
+            recipient: "alice".to_string(),
+        };
+        let paid: DistributedResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(paid.amount, Uint128::new(800));
+        let msg = QueryMsg::RecipientDistributed {
+            recipient: "alice".to_string(),
+        };
+        let paid: RecipientDistributedResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(paid.amount, Uint128::new(900));
+
+        let msg = QueryMsg::Sources {
+            start_after: None,
+            limit: None,
+        };
+        let res: SourcesResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let contracts: Vec<_> = res.sources.into_iter().map(|s| s.contract).collect();
+        assert_eq!(
+            contracts,
+            vec![Addr::unchecked("cosmics"), Addr::unchecked("dragons")]
+        );
+    }
+
+    #[test]
+    fn only_owner_manages_sources() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            instantiate_msg(),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::SetSource(source_msg("updated_dragons", "drgn", 50));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("dragons", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+        let err = claim(deps.as_mut(), env.clone(), "updated_dragons", "alice", 51).unwrap_err();
+        assert!(matches!(err, ContractError::EmissionCapExceeded { .. }));
+        claim(deps.as_mut(), env.clone(), "updated_dragons", "alice", 50).unwrap();
+
+        let msg = ExecuteMsg::RemoveSource {
+            contract: "updated_dragons".to_string(),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("owner", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = claim(deps.as_mut(), env.clone(), "updated_dragons", "alice", 1).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), env, mock_info("owner", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownSource { .. }));
+    }
+
+    #[test]
+    fn migrate_registers_dragon_contract_as_source() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let legacy = LegacyState {
+            owner: "owner".to_string(),
+            dragon_contract: "dragons".to_string(),
+            cw20_contract: Addr::unchecked("drgn"),
+            admin: "treasury".to_string(),
+        };
+        LEGACY_STATE.save(deps.as_mut().storage, &legacy).unwrap();
+        set_contract_version(deps.as_mut().storage, "crates.io:stake-reward", "0.1.0").unwrap();
+
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+        let dragons = query_source(&deps, env.clone(), "dragons");
+        assert_eq!(dragons.reward_token, Addr::unchecked("drgn"));
+        assert_eq!(dragons.epoch_cap, Uint128::MAX);
+        let token = claim(deps.as_mut(), env, "dragons", "alice", 10).unwrap();
+        assert_eq!(token, "drgn");
+    }
+}
@@ -1,4 +1,6 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::ownership::OwnershipError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,11 +8,29 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
     #[error("Custom Error val: {val:?}")]
     CustomError { val: String },
+
+    #[error("Epoch length must be greater than zero")]
+    InvalidEpochLength {},
+
+    #[error("{contract} is not a reward source")]
+    UnknownSource { contract: String },
+
+    #[error("Claim of {requested} exceeds the {remaining} left in this epoch")]
+    EmissionCapExceeded {
+        requested: Uint128,
+        remaining: Uint128,
+    },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
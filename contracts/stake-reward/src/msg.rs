@@ -1,14 +1,29 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Uint128, Uint64};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::ownership::OwnershipMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub admin: String,
-    pub dragon_contract: String,
-    pub cw20_contract: Addr,
+    pub sources: Vec<SourceMsg>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SourceMsg {
+    /// contract allowed to send `Claim`
+    pub contract: String,
+    /// cw20 paid out for this source
+    pub reward_token: String,
+    /// length of an emission epoch, in seconds
+    pub epoch_length: Uint64,
+    /// most that can be claimed through this source during one epoch
+    pub epoch_cap: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -18,9 +33,12 @@ pub enum ExecuteMsg {
     },
     EditState {
         admin: String,
-        dragon_contract: String,
-        cw20_contract: Addr,
-        owner: String,
+    },
+    UpdateOwnership(OwnershipMsg),
+    /// Registers a source, or updates the token and budget of an existing one.
+    SetSource(SourceMsg),
+    RemoveSource {
+        contract: String,
     },
 }
 
@@ -28,6 +46,22 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     GetState {},
+    PendingOwner {},
+    Source {
+        contract: String,
+    },
+    Sources {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Distributed {
+        source: String,
+        recipient: String,
+    },
+    /// What `recipient` was paid by all sources together.
+    RecipientDistributed {
+        recipient: String,
+    },
 }
 
 // We define a custom struct for each query response
@@ -35,6 +69,35 @@ pub enum QueryMsg {
 pub struct GetStateResponse {
     pub owner: String,
     pub admin: String,
-    pub dragon_contract: String,
-    pub cw20_contract: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SourceResponse {
+    pub contract: Addr,
+    pub reward_token: Addr,
+    pub total_distributed: Uint128,
+    pub epoch: u64,
+    pub epoch_start: u64,
+    pub epoch_length: u64,
+    pub epoch_cap: Uint128,
+    pub epoch_distributed: Uint128,
+    pub remaining: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SourcesResponse {
+    pub sources: Vec<SourceResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DistributedResponse {
+    pub source: String,
+    pub recipient: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecipientDistributedResponse {
+    pub recipient: String,
+    pub amount: Uint128,
 }
@@ -1,15 +1,51 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    pub owner: String,
+    pub admin: String,
+}
+
+pub const STATE: Item<State> = Item::new("state");
+
+/// Layout of `state` before 0.2.0, when only one contract could claim.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
     pub owner: String,
     pub dragon_contract: String,
     pub cw20_contract: Addr,
     pub admin: String,
 }
 
-pub const STATE: Item<State> = Item::new("state");
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
+
+/// A contract allowed to pay out rewards, together with the token it pays
+/// in and its budget. Epochs are `epoch_length` seconds long, counted from
+/// `start`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardSource {
+    pub reward_token: Addr,
+    pub start: u64,
+    pub epoch_length: u64,
+    pub epoch_cap: Uint128,
+}
+
+/// Rewards paid during `epoch`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochUsage {
+    pub epoch: u64,
+    pub distributed: Uint128,
+}
+
+/// Reward sources, keyed by the calling contract.
+pub const SOURCES: Map<&Addr, RewardSource> = Map::new("sources");
+pub const EPOCH_USAGE: Map<&Addr, EpochUsage> = Map::new("epoch_usage");
+pub const TOTAL_DISTRIBUTED: Map<&Addr, Uint128> = Map::new("total_distributed");
+/// Paid out per (source, recipient).
+pub const DISTRIBUTED: Map<(&Addr, &Addr), Uint128> = Map::new("distributed");
+/// Paid out per recipient, summed over every source.
+pub const RECIPIENT_DISTRIBUTED: Map<&Addr, Uint128> = Map::new("recipient_distributed");
@@ -22,6 +22,7 @@ pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Extension>;
 use crate::helper::generate_dragon_birth_msg;
 use cw_storage_plus::Bound;
 use cw_utils::Expiration;
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:dragon-mint";
@@ -68,7 +69,7 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateOwner { new_owner } => execute_update_owner(deps, info, new_owner),
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, env, info, msg),
         ExecuteMsg::UpdateRewardContractAddress { new_address } => {
             execute_update_reward_contract_address(deps, info, new_address)
         }
@@ -107,18 +108,24 @@ pub fn execute(
     }
 }
 
-fn execute_update_owner(
+fn execute_update_ownership(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    new_owner: String,
+    msg: OwnershipMsg,
 ) -> Result<Response, ContractError> {
     let mut state = STATE.load(deps.storage)?;
-    if info.sender.to_string() != state.owner {
-        return Err(ContractError::Unauthorized {});
-    }
-    state.owner = new_owner;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
     STATE.save(deps.storage, &state)?;
-    Ok(Response::default().add_attribute("new_owner", state.owner))
+    Ok(res)
 }
 
 fn execute_update_reward_contract_address(
@@ -618,6 +625,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             to_binary(&query_calculate_reward(deps, env, token_id)?)
         }
         QueryMsg::State {} => to_binary(&query_state(deps)?),
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
         _ => Cw721Contract::default().query(deps, env, msg.into()),
     }
 }
@@ -1,10 +1,11 @@
 use cosmwasm_std::{Binary, Uint128, Uint64};
 use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use stake_dragons_common::ownership::OwnershipMsg;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, Trait};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -14,34 +15,6 @@ pub struct InstantiateMsg {
     pub reward_contract_address: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
-pub struct Metadata {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub external_url: Option<String>,
-    pub attributes: Vec<Trait>,
-    ///////////////////////////////////
-    pub image_data: Option<String>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
-
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub extension: Vec<Trait>,
-}
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ClaimMessage {
     pub claim: Claim,
@@ -59,9 +32,7 @@ pub enum ExecuteMsg {
     UpdateMinStakeTime {
         time: Uint64,
     },
-    UpdateOwner {
-        new_owner: String,
-    },
+    UpdateOwnership(OwnershipMsg),
     UpdateRewardContractAddress {
         new_address: String,
     },
@@ -160,6 +131,7 @@ pub enum QueryMsg {
         token_id: Uint64,
     },
     State {},
+    PendingOwner {},
 }
 
 impl From<QueryMsg> for Cw721QueryMsg {
@@ -1 +1,63 @@
+use crate::contract::{execute, instantiate, query};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse};
+use crate::ContractError;
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{from_binary, Addr, MemoryStorage, OwnedDeps, Uint64};
+use cw721_base::msg::InstantiateMsg as Cw721InstantiateMsg;
+use stake_dragons_common::ownership::{OwnershipError, OwnershipMsg, PendingOwnerResponse};
 
+fn setup() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        base: Cw721InstantiateMsg {
+            name: "dragons".to_string(),
+            symbol: "DRAGON".to_string(),
+            minter: "minter".to_string(),
+        },
+        size: Uint64::new(100),
+        base_price: Uint64::new(1),
+        reward_contract_address: "reward".to_string(),
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    deps
+}
+
+#[test]
+fn ownership_moves_on_accept() {
+    let mut deps = setup();
+    let msg = ExecuteMsg::UpdateOwnership(OwnershipMsg::ProposeOwner {
+        new_owner: "next".to_string(),
+        expires: None,
+    });
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("user", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        ContractError::Ownership(OwnershipError::NotOwner {})
+    ));
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+    let pending: PendingOwnerResponse = from_binary(&res).unwrap();
+    assert_eq!(pending.pending_owner, Some(Addr::unchecked("next")));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.owner, "owner");
+
+    let accept = ExecuteMsg::UpdateOwnership(OwnershipMsg::AcceptOwnership {});
+    execute(deps.as_mut(), mock_env(), mock_info("next", &[]), accept).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.owner, "next");
+
+    let msg = ExecuteMsg::UpdateMinStakeTime {
+        time: Uint64::new(1),
+    };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Unauthorized {}));
+}
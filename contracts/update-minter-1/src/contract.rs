@@ -5,24 +5,30 @@ use cosmwasm_std::{
     from_slice, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QueryRequest,
     Response, StdError, StdResult, SubMsg, Uint128, Uint64, WasmQuery,
 };
+use stake_dragons_common::burns::{BurnExecuteMsg, BurnReason};
+use stake_dragons_common::migrate::{check_migration, Version};
+use stake_dragons_common::ownership::{query_pending_owner, update_ownership, OwnershipMsg};
+use stake_dragons_common::random::{
+    fulfil_randomness, request_randomness, validate_source, RandomnessSource,
+};
 use std::ops::Add;
 
 use crate::msg::{
     DragonInfoMsg, DragonResponse, ExecuteMsg, GetDragonInfoMsg, GetStateResponse, InstantiateMsg,
-    MinterDragonBirth, MinterEditContracts, MinterEditState, QueryMsg, ReceiveMsg,
+    MigrateMsg, MinterDragonBirth, MinterEditContracts, MinterEditState, QueryMsg, ReceiveMsg,
 };
 use crate::state::{
-    Contracts, RequiredMinMax, State, UpdatedStats, CONTRACTS, REQUIRED_MIN_MAX, STATE,
-    UPDATED_DRAGON_COUNT, UPDATED_STATS,
+    Contracts, PendingUpgrade, RequiredMinMax, State, UpdatedStats, UpgradeRecord, BASIS_POINTS,
+    CONTRACTS, DEFAULT_SUCCESS_CHANCE, LEGACY_UPDATED_STATS, PENDING_UPGRADES, RANDOMNESS_SOURCE,
+    REQUIRED_MIN_MAX, STATE, UPDATED_DRAGON_COUNT, UPDATED_STATS, UPGRADE_COUNT, UPGRADE_HISTORY,
 };
 
 use crate::error::ContractError;
-use crate::helper::{generate_updated_dragon_mint_msg, Metadata};
+use crate::helper::generate_updated_dragon_mint_msg;
 
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
-pub type Cw721ExecuteMsg = cw721_base::ExecuteMsg<Metadata>;
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:update-minter-1";
@@ -40,7 +46,6 @@ pub fn instantiate(
         drgn_contract: msg.drgn_contract,
         allowed_cw20: msg.allowed_cw20,
         allowed_operators: msg.allowed_operators,
-        random_key: msg.random_key,
         drgn_rac: msg.drgn_rac,
         season: msg.season,
     };
@@ -52,6 +57,7 @@ pub fn instantiate(
         drgn_recipient: msg.drgn_recipient,
         cw20_recipient: msg.cw20_recipient,
     };
+    validate_source(deps.api, &msg.randomness_source)?;
 
     let stats = UpdatedStats {
         common_reward: Uint64::new(2),
@@ -64,6 +70,11 @@ pub fn instantiate(
         epic_ovulation: Uint64::new(7),
         legendary_reward: Uint64::new(15),
         legendary_ovulation: Uint64::new(5),
+        common_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        uncommon_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        rare_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        epic_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        legendary_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
     };
 
     let values = RequiredMinMax {
@@ -85,6 +96,8 @@ pub fn instantiate(
     UPDATED_DRAGON_COUNT.save(deps.storage, &Uint64::new(0))?;
     UPDATED_STATS.save(deps.storage, &stats)?;
     REQUIRED_MIN_MAX.save(deps.storage, &values)?;
+    RANDOMNESS_SOURCE.save(deps.storage, &msg.randomness_source)?;
+    UPGRADE_COUNT.save(deps.storage, &0)?;
 
     Ok(Response::new().add_attribute("method", "instantiate"))
 }
@@ -97,22 +110,19 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
+        ExecuteMsg::UpdateOwnership(msg) => execute_update_ownership(deps, env, info, msg),
         ExecuteMsg::EditState {
-            owner,
             drgn_contract,
             allowed_cw20,
             allowed_operators,
-            random_key,
             drgn_rac,
             season,
         } => execute_edit_state(
             deps,
             info,
-            owner,
             drgn_contract,
             allowed_cw20,
             allowed_operators,
-            random_key,
             drgn_rac,
             season,
         ),
@@ -142,6 +152,11 @@ pub fn execute(
             epic_ovulation,
             legendary_reward,
             legendary_ovulation,
+            common_success,
+            uncommon_success,
+            rare_success,
+            epic_success,
+            legendary_success,
         } => execute_edit_stats(
             deps,
             info,
@@ -155,7 +170,18 @@ pub fn execute(
             epic_ovulation,
             legendary_reward,
             legendary_ovulation,
+            common_success,
+            uncommon_success,
+            rare_success,
+            epic_success,
+            legendary_success,
         ),
+        ExecuteMsg::EditRandomnessSource { source } => {
+            execute_edit_randomness_source(deps, info, source)
+        }
+        ExecuteMsg::FulfilUpgrade { upgrade_id, secret } => {
+            execute_fulfil_upgrade(deps, env, upgrade_id, secret)
+        }
         ExecuteMsg::UpgradeAdmin {
             owner,
             id_1,
@@ -211,25 +237,29 @@ pub fn execute_receive(
             id_2,
             id_3,
             rarity,
-            res,
+            commitment,
         } => execute_update_nft_cw20(
-            deps, _env, info, amount, owner, id_1, id_2, id_3, &rarity, res,
+            deps,
+            info,
+            amount,
+            owner,
+            [id_1, id_2, id_3],
+            &rarity,
+            commitment,
         ),
     }
 }
 
 pub fn execute_update_nft_cw20(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
     amount: Uint128,
     owner: String,
-    id_1: Uint64,
-    id_2: Uint64,
-    id_3: Uint64,
+    ids: [Uint64; 3],
     rarity: &str,
-    res: String,
+    commitment: Option<Binary>,
 ) -> Result<Response, ContractError> {
+    let [id_1, id_2, id_3] = ids;
     let state = STATE.load(deps.storage)?;
     let contracts = CONTRACTS.load(deps.storage)?;
     let stats = UPDATED_STATS.load(deps.storage)?;
@@ -245,40 +275,28 @@ pub fn execute_update_nft_cw20(
 
     let mut min_amount: Uint128;
     let mut max_amount: Uint128;
-    let ovulation: String;
-    let daily_reward: String;
 
     //check rarity
     match rarity {
         "common" => {
             min_amount = min_max.common_min;
             max_amount = min_max.common_max;
-            ovulation = stats.common_ovulation.to_string();
-            daily_reward = stats.common_reward.to_string()
         }
         "uncommon" => {
             min_amount = min_max.uncommon_min;
             max_amount = min_max.uncommon_max;
-            ovulation = stats.uncommon_ovulation.to_string();
-            daily_reward = stats.uncommon_reward.to_string()
         }
         "rare" => {
             min_amount = min_max.rare_min;
             max_amount = min_max.rare_max;
-            ovulation = stats.rare_ovulation.to_string();
-            daily_reward = stats.rare_reward.to_string()
         }
         "epic" => {
             min_amount = min_max.epic_min;
             max_amount = min_max.epic_max;
-            ovulation = stats.epic_ovulation.to_string();
-            daily_reward = stats.epic_reward.to_string()
         }
         "legendary" => {
             min_amount = min_max.legendary_min;
             max_amount = min_max.legendary_max;
-            ovulation = stats.legendary_ovulation.to_string();
-            daily_reward = stats.legendary_reward.to_string()
         }
         _ => return Err(ContractError::RarityNotSupported {}),
     }
@@ -348,14 +366,17 @@ pub fn execute_update_nft_cw20(
     });
 
     //Burn all given dragons
-    let burn_msg_1 = Cw721ExecuteMsg::Burn {
+    let burn_msg_1 = BurnExecuteMsg::Burn {
         token_id: id_1.to_string(),
+        reason: Some(BurnReason::Upgrade),
     };
-    let burn_msg_2 = Cw721ExecuteMsg::Burn {
+    let burn_msg_2 = BurnExecuteMsg::Burn {
         token_id: id_2.to_string(),
+        reason: Some(BurnReason::Upgrade),
     };
-    let burn_msg_3 = Cw721ExecuteMsg::Burn {
+    let burn_msg_3 = BurnExecuteMsg::Burn {
         token_id: id_3.to_string(),
+        reason: Some(BurnReason::Upgrade),
     };
 
     let execute_burn_1 = CosmosMsg::Wasm(Execute {
@@ -374,59 +395,135 @@ pub fn execute_update_nft_cw20(
         funds: vec![],
     });
 
-    let mut submessages = vec![
+    let submessages = vec![
         SubMsg::new(execute_burn_1),
         SubMsg::new(execute_burn_2),
         SubMsg::new(execute_burn_3),
         SubMsg::new(execute_payment_msg),
     ];
 
-    // Pick random index from given array for success/fail
-    let key1 = state.random_key / 10000;
-    let key2 = state.random_key % 100;
-    let secret = key1 + key2;
-    let type_id = res.chars().nth(secret as usize).unwrap();
-
-    let success: bool;
-    match type_id {
-        'j' => success = false,
-        '2' => success = false,
-        '8' => success = false,
-        'H' => success = false,
-        _ => success = true,
+    // Commit to the roll before anyone can know it; the dragons are burned
+    // and paid for right away, so the upgrade can't be taken back.
+    let owner = deps.api.addr_validate(&owner)?;
+    let success_chance = stats
+        .success_chance(rarity)
+        .ok_or(ContractError::RarityNotSupported {})?;
+    let source = RANDOMNESS_SOURCE.load(deps.storage)?;
+    let nonce = format!("{}:{}:{}", id_1, id_2, id_3);
+    let request = request_randomness(deps.as_ref(), &source, nonce.as_bytes(), commitment)?;
+    let upgrade_id = UPGRADE_COUNT.update::<_, StdError>(deps.storage, |count| Ok(count + 1))?;
+    let pending = PendingUpgrade {
+        owner,
+        rarity: rarity.to_string(),
+        burned: vec![id_1, id_2, id_3],
+        success_chance,
+        request: request.clone(),
     };
+    PENDING_UPGRADES.save(deps.storage, upgrade_id, &pending)?;
+
+    Ok(Response::new()
+        .add_submessages(submessages)
+        .add_attribute("upgrade_id", upgrade_id.to_string())
+        .add_attribute("success_chance", success_chance.to_string())
+        .add_attributes(request.attributes()))
+}
+
+/// Rolls a pending upgrade against the success chance it was requested with
+/// and mints the upgraded dragon on success.
+pub fn execute_fulfil_upgrade(
+    deps: DepsMut,
+    env: Env,
+    upgrade_id: u64,
+    secret: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_UPGRADES.load(deps.storage, upgrade_id)?;
+    let randomness = fulfil_randomness(deps.as_ref(), &env, &pending.request, secret)?;
+    let roll = randomness.roll(BASIS_POINTS);
+    let success = roll < pending.success_chance.u64();
+    PENDING_UPGRADES.remove(deps.storage, upgrade_id);
+
+    let mut res = Response::new();
+    let mut minted = None;
+    if success {
+        let state = STATE.load(deps.storage)?;
+        let contracts = CONTRACTS.load(deps.storage)?;
+        let stats = UPDATED_STATS.load(deps.storage)?;
+        let (daily_reward, ovulation) = stats
+            .traits(&pending.rarity)
+            .ok_or(ContractError::RarityNotSupported {})?;
 
-    if success == true {
         //Mint msg
-        UPDATED_DRAGON_COUNT
+        let id = UPDATED_DRAGON_COUNT
             .update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint64::new(1))))?;
-        let id = UPDATED_DRAGON_COUNT.load(deps.storage)?;
-
         let mint_msg = generate_updated_dragon_mint_msg(
             id,
-            owner,
-            nft_info_1.kind,
-            ovulation,
-            daily_reward,
+            pending.owner.to_string(),
+            pending.rarity.clone(),
+            ovulation.to_string(),
+            daily_reward.to_string(),
             state.season,
         )?;
-        let execute_mint_msg = CosmosMsg::Wasm(Execute {
+        res = res.add_message(CosmosMsg::Wasm(Execute {
             contract_addr: contracts.updated_dragon,
             msg: to_binary(&mint_msg)?,
             funds: vec![],
-        });
-
-        submessages.push(SubMsg::new(execute_mint_msg))
+        }));
+        minted = Some(id);
     }
 
-    Ok(Response::new()
-        .add_submessages(submessages)
-        .add_attribute("success", success.to_string()))
+    record_upgrade(
+        deps,
+        &env,
+        upgrade_id,
+        pending.owner,
+        &pending.rarity,
+        pending.burned,
+        success,
+        Some(roll),
+        pending.success_chance,
+        minted,
+    )?;
+
+    Ok(res
+        .add_attribute("action", "fulfil_upgrade")
+        .add_attribute("upgrade_id", upgrade_id.to_string())
+        .add_attribute("success", success.to_string())
+        .add_attribute("roll", roll.to_string())
+        .add_attribute("success_chance", pending.success_chance.to_string())
+        .add_attributes(randomness.attributes()))
+}
+
+/// Appends upgrade `id` to its owner's history.
+#[allow(clippy::too_many_arguments)]
+fn record_upgrade(
+    deps: DepsMut,
+    env: &Env,
+    id: u64,
+    owner: Addr,
+    rarity: &str,
+    burned: Vec<Uint64>,
+    success: bool,
+    roll: Option<u64>,
+    success_chance: Uint64,
+    minted: Option<Uint64>,
+) -> StdResult<()> {
+    let record = UpgradeRecord {
+        id,
+        owner: owner.clone(),
+        rarity: rarity.to_string(),
+        burned,
+        success,
+        roll,
+        success_chance,
+        minted,
+        height: env.block.height,
+    };
+    UPGRADE_HISTORY.save(deps.storage, (&owner, id), &record)
 }
 
 pub fn execute_update_nft_admin(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     owner: String,
     id_1: Uint64,
@@ -437,13 +534,14 @@ pub fn execute_update_nft_admin(
     let state = STATE.load(deps.storage)?;
     let stats = UPDATED_STATS.load(deps.storage)?;
 
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "Only allowed operators can execute this message".to_string(),
         });
     }
 
     let contracts = CONTRACTS.load(deps.storage)?;
+    let owner_addr = deps.api.addr_validate(&owner)?;
 
     //Check if rarity is correct and dragons are not staked
     let msg_1 = GetDragonInfoMsg {
@@ -477,14 +575,17 @@ pub fn execute_update_nft_admin(
     }
 
     //Burn all given dragons
-    let burn_msg_1 = Cw721ExecuteMsg::Burn {
+    let burn_msg_1 = BurnExecuteMsg::Burn {
         token_id: id_1.to_string(),
+        reason: Some(BurnReason::Upgrade),
     };
-    let burn_msg_2 = Cw721ExecuteMsg::Burn {
+    let burn_msg_2 = BurnExecuteMsg::Burn {
         token_id: id_2.to_string(),
+        reason: Some(BurnReason::Upgrade),
     };
-    let burn_msg_3 = Cw721ExecuteMsg::Burn {
+    let burn_msg_3 = BurnExecuteMsg::Burn {
         token_id: id_3.to_string(),
+        reason: Some(BurnReason::Upgrade),
     };
 
     let execute_burn_1 = CosmosMsg::Wasm(Execute {
@@ -506,40 +607,19 @@ pub fn execute_update_nft_admin(
     //Mint msg
     UPDATED_DRAGON_COUNT.update::<_, StdError>(deps.storage, |id| Ok(id.add(Uint64::new(1))))?;
     let id = UPDATED_DRAGON_COUNT.load(deps.storage)?;
-    let ovulation: String;
-    let daily_reward: String;
-
-    //check rarity
-    match rarity {
-        "common" => {
-            ovulation = stats.common_ovulation.to_string();
-            daily_reward = stats.common_reward.to_string()
-        }
-        "uncommon" => {
-            ovulation = stats.uncommon_ovulation.to_string();
-            daily_reward = stats.uncommon_reward.to_string()
-        }
-        "rare" => {
-            ovulation = stats.rare_ovulation.to_string();
-            daily_reward = stats.rare_reward.to_string()
-        }
-        "epic" => {
-            ovulation = stats.epic_ovulation.to_string();
-            daily_reward = stats.epic_reward.to_string()
-        }
-        "legendary" => {
-            ovulation = stats.legendary_ovulation.to_string();
-            daily_reward = stats.legendary_reward.to_string()
-        }
-        _ => return Err(ContractError::RarityNotSupported {}),
-    }
+    let success_chance = stats
+        .success_chance(rarity)
+        .ok_or(ContractError::RarityNotSupported {})?;
+    let (daily_reward, ovulation) = stats
+        .traits(rarity)
+        .ok_or(ContractError::RarityNotSupported {})?;
 
     let mint_msg = generate_updated_dragon_mint_msg(
         id,
         owner,
         nft_info_1.kind,
-        ovulation,
-        daily_reward,
+        ovulation.to_string(),
+        daily_reward.to_string(),
         state.season,
     )?;
     let execute_mint_msg = CosmosMsg::Wasm(Execute {
@@ -555,31 +635,66 @@ pub fn execute_update_nft_admin(
         SubMsg::new(execute_mint_msg),
     ];
 
-    Ok(Response::new().add_submessages(submessages))
+    let upgrade_id = UPGRADE_COUNT.update::<_, StdError>(deps.storage, |count| Ok(count + 1))?;
+    record_upgrade(
+        deps,
+        &env,
+        upgrade_id,
+        owner_addr,
+        rarity,
+        vec![id_1, id_2, id_3],
+        true,
+        None,
+        success_chance,
+        Some(id),
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(submessages)
+        .add_attribute("upgrade_id", upgrade_id.to_string())
+        .add_attribute("success", "true"))
 }
+
+pub fn execute_update_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    let (owner, res) = update_ownership(
+        deps.storage,
+        deps.api,
+        &env.block,
+        &info.sender,
+        &state.owner,
+        msg,
+    )?;
+    state.owner = owner;
+    STATE.save(deps.storage, &state)?;
+    Ok(res)
+}
+
 pub fn execute_edit_state(
     deps: DepsMut,
     info: MessageInfo,
-    owner: String,
     drgn_contract: Addr,
     allowed_cw20: Addr,
     allowed_operators: Vec<String>,
-    random_key: i32,
     drgn_rac: Uint128,
     season: String,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "state can only be edited by the owner".to_string(),
         });
     }
     let new = State {
-        owner,
+        owner: state.owner,
         drgn_contract,
         allowed_cw20,
         allowed_operators,
-        random_key,
         drgn_rac,
         season,
     };
@@ -602,7 +717,7 @@ pub fn execute_edit_min_max(
     legendary_max: Uint128,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "state can only be edited by the owner".to_string(),
         });
@@ -638,9 +753,14 @@ pub fn execute_edit_stats(
     epic_ovulation: Uint64,
     legendary_reward: Uint64,
     legendary_ovulation: Uint64,
+    common_success: Uint64,
+    uncommon_success: Uint64,
+    rare_success: Uint64,
+    epic_success: Uint64,
+    legendary_success: Uint64,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "state can only be edited by the owner".to_string(),
         });
@@ -657,12 +777,44 @@ pub fn execute_edit_stats(
         epic_ovulation,
         legendary_reward,
         legendary_ovulation,
+        common_success,
+        uncommon_success,
+        rare_success,
+        epic_success,
+        legendary_success,
     };
+    for chance in [
+        common_success,
+        uncommon_success,
+        rare_success,
+        epic_success,
+        legendary_success,
+    ] {
+        if chance.u64() > BASIS_POINTS {
+            return Err(ContractError::InvalidSuccessChance { chance });
+        }
+    }
 
     UPDATED_STATS.save(deps.storage, &stats)?;
     Ok(Response::new())
 }
 
+pub fn execute_edit_randomness_source(
+    deps: DepsMut,
+    info: MessageInfo,
+    source: RandomnessSource,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
+        return Err(ContractError::Unauthorized {
+            msg: "randomness source can only be edited by the owner".to_string(),
+        });
+    }
+    validate_source(deps.api, &source)?;
+    RANDOMNESS_SOURCE.save(deps.storage, &source)?;
+    Ok(Response::new().add_attribute("action", "edit_randomness_source"))
+}
+
 pub fn execute_edit_contracts(
     deps: DepsMut,
     info: MessageInfo,
@@ -673,7 +825,7 @@ pub fn execute_edit_contracts(
     cw20_recipient: String,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "state can only be edited by the owner".to_string(),
         });
@@ -698,7 +850,7 @@ pub fn execute_edit_old_minter_state(
     let state = STATE.load(deps.storage)?;
     let contracts = CONTRACTS.load(deps.storage)?;
 
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "state can only be edited by the owner".to_string(),
         });
@@ -721,7 +873,7 @@ pub fn execute_edit_old_minter_contracts(
     let state = STATE.load(deps.storage)?;
     let contracts = CONTRACTS.load(deps.storage)?;
 
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "state can only be edited by the owner".to_string(),
         });
@@ -744,7 +896,7 @@ pub fn execute_old_minter_dragon_birth(
     let state = STATE.load(deps.storage)?;
     let contracts = CONTRACTS.load(deps.storage)?;
 
-    if state.allowed_operators.contains(&info.sender.to_string()) == false {
+    if !state.allowed_operators.contains(&info.sender.to_string()) {
         return Err(ContractError::Unauthorized {
             msg: "state can only be edited by the owner".to_string(),
         });
@@ -759,18 +911,89 @@ pub fn execute_old_minter_dragon_birth(
     Ok(Response::new().add_message(msg))
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    let from = check_migration(
+        &stored.contract,
+        &stored.version,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+    if from < Version::new(0, 2, 0) {
+        migrate_v0_2(deps.branch(), msg.randomness_source)?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+/// 0.2.0 dropped `random_key` from `State`, rolls upgrades against per-rarity
+/// success chances drawn from a randomness beacon and records every upgrade.
+fn migrate_v0_2(deps: DepsMut, source: Option<RandomnessSource>) -> Result<(), ContractError> {
+    let storage = deps.storage;
+    let state = STATE.load(storage)?;
+    STATE.save(storage, &state)?;
+    let legacy = LEGACY_UPDATED_STATS.load(storage)?;
+    let stats = UpdatedStats {
+        common_reward: legacy.common_reward,
+        common_ovulation: legacy.common_ovulation,
+        uncommon_reward: legacy.uncommon_reward,
+        uncommon_ovulation: legacy.uncommon_ovulation,
+        rare_reward: legacy.rare_reward,
+        rare_ovulation: legacy.rare_ovulation,
+        epic_reward: legacy.epic_reward,
+        epic_ovulation: legacy.epic_ovulation,
+        legendary_reward: legacy.legendary_reward,
+        legendary_ovulation: legacy.legendary_ovulation,
+        common_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        uncommon_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        rare_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        epic_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+        legendary_success: Uint64::new(DEFAULT_SUCCESS_CHANCE),
+    };
+    UPDATED_STATS.save(storage, &stats)?;
+    if let Some(source) = source {
+        validate_source(deps.api, &source)?;
+        RANDOMNESS_SOURCE.save(storage, &source)?;
+    } else if RANDOMNESS_SOURCE.may_load(storage)?.is_none() {
+        return Err(ContractError::MissingRandomnessSource {});
+    }
+    if UPGRADE_COUNT.may_load(storage)?.is_none() {
+        UPGRADE_COUNT.save(storage, &0)?;
+    }
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetState {} => to_binary(&query::state(deps)?),
         QueryMsg::GetStats {} => to_binary(&query::stats(deps)?),
         QueryMsg::GetMinMax {} => to_binary(&query::token_amount(deps)?),
+        QueryMsg::UpgradeHistory {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query::upgrade_history(deps, owner, start_after, limit)?),
+        QueryMsg::PendingUpgrade { upgrade_id } => {
+            to_binary(&PENDING_UPGRADES.load(deps.storage, upgrade_id)?)
+        }
+        QueryMsg::PendingOwner {} => to_binary(&query_pending_owner(deps.storage)?),
     }
 }
 
 pub mod query {
     use super::*;
-    use crate::msg::{GetMinMaxResponse, GetStatsResponse};
+    use crate::msg::{GetMinMaxResponse, GetStatsResponse, UpgradeHistoryResponse};
+    use cosmwasm_std::Order;
+    use cw_storage_plus::Bound;
+
+    // settings for pagination
+    const MAX_LIMIT: u32 = 30;
+    const DEFAULT_LIMIT: u32 = 10;
 
     pub fn state(deps: Deps) -> StdResult<GetStateResponse> {
         let state = STATE.load(deps.storage)?;
@@ -780,9 +1003,9 @@ pub mod query {
             drgn_contract: state.drgn_contract,
             allowed_cw20: state.allowed_cw20,
             allowed_operators: state.allowed_operators,
-            random_key: state.random_key,
             drgn_rac: state.drgn_rac,
             season: state.season,
+            randomness_source: RANDOMNESS_SOURCE.load(deps.storage)?,
             dragon: contract.dragon,
             updated_dragon: contract.updated_dragon,
             egg_minter: contract.egg_minter,
@@ -804,6 +1027,31 @@ pub mod query {
             epic_ovulation: stats.epic_ovulation,
             legendary_reward: stats.legendary_reward,
             legendary_ovulation: stats.legendary_ovulation,
+            common_success: stats.common_success,
+            uncommon_success: stats.uncommon_success,
+            rare_success: stats.rare_success,
+            epic_success: stats.epic_success,
+            legendary_success: stats.legendary_success,
+        })
+    }
+
+    pub fn upgrade_history(
+        deps: Deps,
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<UpgradeHistoryResponse> {
+        let owner = deps.api.addr_validate(&owner)?;
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let upgrades: StdResult<Vec<_>> = UPGRADE_HISTORY
+            .prefix(&owner)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, record)| record))
+            .collect();
+        Ok(UpgradeHistoryResponse {
+            upgrades: upgrades?,
         })
     }
 
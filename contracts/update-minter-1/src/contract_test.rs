@@ -0,0 +1,656 @@
+#[cfg(test)]
+mod tests {
+    use crate::contract::{execute, instantiate, migrate, query};
+    use crate::msg::{
+        DragonResponse, ExecuteMsg, GetStateResponse, GetStatsResponse, InstantiateMsg, MigrateMsg,
+        QueryMsg, ReceiveMsg, UpgradeHistoryResponse,
+    };
+    use crate::state::{
+        LegacyUpdatedStats, PendingUpgrade, LEGACY_UPDATED_STATS, RANDOMNESS_SOURCE, STATE,
+        UPGRADE_COUNT,
+    };
+    use crate::ContractError;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+    use cosmwasm_std::{
+        from_binary, from_slice, to_binary, Addr, Binary, ContractResult, DepsMut, Env,
+        MemoryStorage, MessageInfo, OwnedDeps, QuerierResult, Response, SystemResult, Uint128,
+        Uint64, WasmQuery,
+    };
+    use cw2::set_contract_version;
+    use cw20::{Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use cw20_base::msg::InstantiateMsg as Cw20InstantiateMsg;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use cw_storage_plus::Item;
+    use dragon_mint::msg::{
+        ExecuteMsg as DragonExecuteMsg, InstantiateMsg as DragonInstantiateMsg,
+        QueryMsg as DragonQueryMsg,
+    };
+    use dragon_mint::state::Dragon;
+    use serde::{Deserialize, Serialize};
+    use stake_dragons_common::burns::{BurnReason, BurnedTokensResponse};
+    use stake_dragons_common::ownership::{OwnershipMsg, PendingOwnerResponse};
+    use stake_dragons_common::random::mock::{
+        beacon_execute, beacon_instantiate, beacon_query, mock_beacon, PublishRound,
+    };
+    use stake_dragons_common::random::{RandomnessError, RandomnessSource};
+    use stake_dragons_common::{CustomMintMsg, MintMsg, Trait};
+
+    fn instantiate_msg() -> InstantiateMsg {
+        InstantiateMsg {
+            drgn_contract: Addr::unchecked("drgn"),
+            allowed_cw20: Addr::unchecked("cw20"),
+            allowed_operators: vec!["owner".to_string()],
+            drgn_rac: Uint128::new(100),
+            season: "s1".to_string(),
+            dragon: Addr::unchecked("dragon"),
+            updated_dragon: "updated_dragon".to_string(),
+            egg_minter: "egg_minter".to_string(),
+            drgn_recipient: "drgn_recipient".to_string(),
+            cw20_recipient: "cw20_recipient".to_string(),
+            randomness_source: beacon(),
+        }
+    }
+
+    fn beacon() -> RandomnessSource {
+        RandomnessSource::Beacon {
+            contract: "beacon".to_string(),
+        }
+    }
+
+    /// Answers beacon queries as if `round` was the latest round and every
+    /// dragon query with an unstaked dragon of `rarity`.
+    fn querier(rarity: &'static str, round: u64) -> impl Fn(&WasmQuery) -> QuerierResult {
+        let beacon = mock_beacon("beacon", round, vec![5u8; 32]);
+        move |query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "beacon" => beacon(query),
+            _ => SystemResult::Ok(ContractResult::Ok(
+                to_binary(&DragonResponse {
+                    owner: "owner".to_string(),
+                    token_id: "1".to_string(),
+                    kind: rarity.to_string(),
+                    ovulation_period: 0,
+                    hatch: Uint64::zero(),
+                    daily_income: "1".to_string(),
+                    is_staked: false,
+                    stake_start_time: Uint64::zero(),
+                    reward_start_time: Uint64::zero(),
+                    unstaking_start_time: Uint64::zero(),
+                    unstaking_process: false,
+                    reward_end_time: Uint64::zero(),
+                })
+                .unwrap(),
+            )),
+        }
+    }
+
+    fn setup(rarity: &'static str) -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(querier(rarity, 1));
+        let msg = instantiate_msg();
+        instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        deps
+    }
+
+    /// Publishes the round upgrade `upgrade_id` waits for and fulfils it.
+    fn fulfil(
+        deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        rarity: &'static str,
+        upgrade_id: u64,
+    ) -> Response {
+        let msg = QueryMsg::PendingUpgrade { upgrade_id };
+        let pending: PendingUpgrade =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        deps.querier
+            .update_wasm(querier(rarity, pending.request.round));
+        let msg = ExecuteMsg::FulfilUpgrade {
+            upgrade_id,
+            secret: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap()
+    }
+
+    fn edit_chances(common: u64, legendary: u64) -> ExecuteMsg {
+        ExecuteMsg::EditStats {
+            common_reward: Uint64::new(2),
+            common_ovulation: Uint64::new(20),
+            uncommon_reward: Uint64::new(4),
+            uncommon_ovulation: Uint64::new(15),
+            rare_reward: Uint64::new(8),
+            rare_ovulation: Uint64::new(10),
+            epic_reward: Uint64::new(20),
+            epic_ovulation: Uint64::new(7),
+            legendary_reward: Uint64::new(15),
+            legendary_ovulation: Uint64::new(5),
+            common_success: Uint64::new(common),
+            uncommon_success: Uint64::new(9000),
+            rare_success: Uint64::new(9000),
+            epic_success: Uint64::new(9000),
+            legendary_success: Uint64::new(legendary),
+        }
+    }
+
+    fn upgrade(rarity: &str, amount: u128) -> ExecuteMsg {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "user".to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(&ReceiveMsg::Upgrade {
+                owner: "user".to_string(),
+                id_1: Uint64::new(1),
+                id_2: Uint64::new(2),
+                id_3: Uint64::new(3),
+                rarity: rarity.to_string(),
+                commitment: None,
+            })
+            .unwrap(),
+        })
+    }
+
+    fn attribute(res: &Response, key: &str) -> String {
+        res.attributes
+            .iter()
+            .find(|a| a.key == key)
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    #[test]
+    fn upgrade_outcome_follows_success_chance() {
+        let mut deps = setup("legendary");
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            edit_chances(9000, 10000),
+        )
+        .unwrap();
+        for upgrade_id in 1..=3 {
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("drgn", &[]),
+                upgrade("legendary", 900_000_000),
+            )
+            .unwrap();
+            assert_eq!(attribute(&res, "upgrade_id"), upgrade_id.to_string());
+            assert_eq!(attribute(&res, "success_chance"), "10000");
+            // three burns and the payment
+            assert_eq!(res.messages.len(), 4);
+            let res = fulfil(&mut deps, "legendary", upgrade_id);
+            assert_eq!(attribute(&res, "success"), "true");
+            // the mint
+            assert_eq!(res.messages.len(), 1);
+        }
+
+        let mut deps = setup("common");
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            edit_chances(0, 10000),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("drgn", &[]),
+            upgrade("common", 45_000_000),
+        )
+        .unwrap();
+        let res = fulfil(&mut deps, "common", 1);
+        assert_eq!(attribute(&res, "success"), "false");
+        assert!(res.messages.is_empty());
+        assert!(attribute(&res, "roll").parse::<u64>().unwrap() < 10000);
+    }
+
+    #[test]
+    fn upgrade_waits_for_the_next_round() {
+        let mut deps = setup("legendary");
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            edit_chances(9000, 10000),
+        )
+        .unwrap();
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("drgn", &[]),
+            upgrade("legendary", 900_000_000),
+        )
+        .unwrap();
+        assert_eq!(attribute(&res, "randomness_round"), "2");
+
+        let msg = ExecuteMsg::FulfilUpgrade {
+            upgrade_id: 1,
+            secret: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Randomness(RandomnessError::RoundNotPublished { round: 2 })
+        ));
+
+        // the chance the upgrade was requested with still applies
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            edit_chances(9000, 0),
+        )
+        .unwrap();
+        let res = fulfil(&mut deps, "legendary", 1);
+        assert_eq!(attribute(&res, "success"), "true");
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+    }
+
+    #[test]
+    fn success_chance_is_validated() {
+        let mut deps = setup("common");
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            edit_chances(10001, 10000),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidSuccessChance { .. }));
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            edit_chances(5000, 5000),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+    }
+
+    #[test]
+    fn upgrades_are_recorded_per_owner() {
+        let mut deps = setup("common");
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            edit_chances(0, 10000),
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("drgn", &[]),
+            upgrade("common", 45_000_000),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::UpgradeAdmin {
+            owner: "user".to_string(),
+            id_1: Uint64::new(4),
+            id_2: Uint64::new(5),
+            id_3: Uint64::new(6),
+            rarity: "common".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        fulfil(&mut deps, "common", 1);
+
+        let history = |owner: &str, start_after| {
+            let res = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::UpgradeHistory {
+                    owner: owner.to_string(),
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap();
+            from_binary::<UpgradeHistoryResponse>(&res)
+                .unwrap()
+                .upgrades
+        };
+
+        let upgrades = history("user", None);
+        assert_eq!(upgrades.len(), 2);
+        assert!(!upgrades[0].success);
+        assert!(upgrades[0].roll.is_some());
+        assert_eq!(upgrades[0].minted, None);
+        assert!(upgrades[1].success);
+        assert_eq!(upgrades[1].roll, None);
+        assert_eq!(upgrades[1].minted, Some(Uint64::new(1)));
+        assert_eq!(
+            upgrades[1].burned,
+            vec![Uint64::new(4), Uint64::new(5), Uint64::new(6)]
+        );
+
+        assert_eq!(history("user", Some(1)).len(), 1);
+        assert!(history("someone", None).is_empty());
+    }
+
+    /// `State` as stored by 0.1.0, which still had the upgrade `random_key`.
+    #[derive(Serialize, Deserialize)]
+    struct LegacyState {
+        owner: String,
+        drgn_contract: Addr,
+        allowed_cw20: Addr,
+        allowed_operators: Vec<String>,
+        random_key: i32,
+        drgn_rac: Uint128,
+        season: String,
+    }
+
+    fn instantiate_v0_1(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> Result<Response, ContractError> {
+        let res = instantiate(deps.branch(), env, info, msg)?;
+        let state = STATE.load(deps.storage)?;
+        let legacy = LegacyState {
+            owner: state.owner,
+            drgn_contract: state.drgn_contract,
+            allowed_cw20: state.allowed_cw20,
+            allowed_operators: state.allowed_operators,
+            random_key: 7,
+            drgn_rac: state.drgn_rac,
+            season: state.season,
+        };
+        Item::new("state").save(deps.storage, &legacy)?;
+        let stats = LegacyUpdatedStats {
+            common_reward: Uint64::new(3),
+            common_ovulation: Uint64::new(20),
+            uncommon_reward: Uint64::new(4),
+            uncommon_ovulation: Uint64::new(15),
+            rare_reward: Uint64::new(8),
+            rare_ovulation: Uint64::new(10),
+            epic_reward: Uint64::new(20),
+            epic_ovulation: Uint64::new(7),
+            legendary_reward: Uint64::new(15),
+            legendary_ovulation: Uint64::new(5),
+        };
+        LEGACY_UPDATED_STATS.save(deps.storage, &stats)?;
+        RANDOMNESS_SOURCE.remove(deps.storage);
+        UPGRADE_COUNT.remove(deps.storage);
+        set_contract_version(deps.storage, "crates.io:update-minter-1", "0.1.0")?;
+        Ok(res)
+    }
+
+    #[test]
+    fn migrate_upgrades_v0_1_layout() {
+        let mut app = App::default();
+        let legacy = app.store_code(Box::new(ContractWrapper::new(
+            execute,
+            instantiate_v0_1,
+            query,
+        )));
+        let current = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_migrate(migrate),
+        ));
+        let admin = Addr::unchecked("admin");
+        let addr = app
+            .instantiate_contract(
+                legacy,
+                admin.clone(),
+                &instantiate_msg(),
+                &[],
+                "update-minter",
+                Some(admin.to_string()),
+            )
+            .unwrap();
+        let err = app
+            .migrate_contract(
+                admin.clone(),
+                addr.clone(),
+                &MigrateMsg {
+                    randomness_source: None,
+                },
+                current,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap().to_string(),
+            ContractError::MissingRandomnessSource {}.to_string()
+        );
+        let msg = MigrateMsg {
+            randomness_source: Some(beacon()),
+        };
+        app.migrate_contract(admin, addr.clone(), &msg, current)
+            .unwrap();
+
+        let stats: GetStatsResponse = app
+            .wrap()
+            .query_wasm_smart(&addr, &QueryMsg::GetStats {})
+            .unwrap();
+        assert_eq!(stats.common_reward, Uint64::new(3));
+        assert_eq!(stats.common_success, Uint64::new(9000));
+        assert_eq!(stats.legendary_success, Uint64::new(9000));
+        let history: UpgradeHistoryResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &addr,
+                &QueryMsg::UpgradeHistory {
+                    owner: "user".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(history.upgrades.is_empty());
+    }
+
+    #[test]
+    fn migrate_refuses_downgrade() {
+        let mut deps = setup("common");
+        set_contract_version(deps.as_mut().storage, "crates.io:update-minter-1", "1.0.0").unwrap();
+        let msg = MigrateMsg {
+            randomness_source: None,
+        };
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Migrate(_)));
+    }
+
+    #[test]
+    fn ownership_moves_on_accept() {
+        let mut deps = setup("common");
+        let edit = ExecuteMsg::EditState {
+            drgn_contract: Addr::unchecked("drgn"),
+            allowed_cw20: Addr::unchecked("cw20"),
+            allowed_operators: vec!["owner".to_string(), "operator".to_string()],
+            drgn_rac: Uint128::new(100),
+            season: "s2".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), edit).unwrap();
+        let msg = ExecuteMsg::UpdateOwnership(OwnershipMsg::ProposeOwner {
+            new_owner: "next".to_string(),
+            expires: None,
+        });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("operator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Ownership(_)));
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingOwner {}).unwrap();
+        let pending: PendingOwnerResponse = from_binary(&res).unwrap();
+        assert_eq!(pending.pending_owner, Some(Addr::unchecked("next")));
+
+        let accept = ExecuteMsg::UpdateOwnership(OwnershipMsg::AcceptOwnership {});
+        execute(deps.as_mut(), mock_env(), mock_info("next", &[]), accept).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetState {}).unwrap();
+        let state: GetStateResponse = from_binary(&res).unwrap();
+        assert_eq!(state.owner, "next");
+        assert_eq!(state.season, "s2");
+    }
+
+    /// Instantiates the dragon collection, which builds on a newer cw721-base
+    /// than this crate.
+    fn dragon_instantiate_msg() -> DragonInstantiateMsg {
+        from_slice(
+            br#"{
+                "base": {"name": "dragons", "symbol": "DRAGON", "minter": "egg_minter"},
+                "size": "100",
+                "base_price": "1",
+                "reward_contract_address": "reward"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn mint_dragon(app: &mut App, dragon: &Addr) {
+        let traits = [
+            ("kind", "common"),
+            ("ovulation_period", "1"),
+            ("daily_income", "1"),
+        ];
+        let msg = DragonExecuteMsg::Mint(CustomMintMsg {
+            base: MintMsg {
+                token_id: "".to_string(),
+                owner: "user".to_string(),
+                token_uri: None,
+                extension: None,
+            },
+            extension: traits
+                .iter()
+                .map(|(trait_type, value)| Trait {
+                    display_type: None,
+                    trait_type: trait_type.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+        });
+        let minter = Addr::unchecked("egg_minter");
+        app.execute_contract(minter, dragon.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn fused_dragons_are_burned_for_the_upgrade() {
+        let mut app = App::default();
+        let owner = Addr::unchecked("owner");
+        let user = Addr::unchecked("user");
+        let dragon_code = app.store_code(Box::new(ContractWrapper::new(
+            dragon_mint::contract::execute,
+            dragon_mint::contract::instantiate,
+            dragon_mint::contract::query,
+        )));
+        let dragon = app
+            .instantiate_contract(
+                dragon_code,
+                owner.clone(),
+                &dragon_instantiate_msg(),
+                &[],
+                "dragons",
+                None,
+            )
+            .unwrap();
+        for _ in 0..3 {
+            mint_dragon(&mut app, &dragon);
+        }
+        let drgn_code = app.store_code(Box::new(ContractWrapper::new(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        )));
+        let msg = Cw20InstantiateMsg {
+            name: "dragon token".to_string(),
+            symbol: "DRGN".to_string(),
+            decimals: 6,
+            initial_balances: vec![Cw20Coin {
+                address: user.to_string(),
+                amount: Uint128::new(100_000_000),
+            }],
+            mint: None,
+            marketing: None,
+        };
+        let drgn = app
+            .instantiate_contract(drgn_code, owner.clone(), &msg, &[], "drgn", None)
+            .unwrap();
+        let beacon_code = app.store_code(Box::new(ContractWrapper::new(
+            beacon_execute,
+            beacon_instantiate,
+            beacon_query,
+        )));
+        let round = PublishRound {
+            round: 1,
+            randomness: Binary::from(vec![5u8; 32]),
+        };
+        let beacon = app
+            .instantiate_contract(beacon_code, owner.clone(), &round, &[], "beacon", None)
+            .unwrap();
+        let minter_code =
+            app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+        let msg = InstantiateMsg {
+            drgn_contract: drgn.clone(),
+            dragon: dragon.clone(),
+            randomness_source: RandomnessSource::Beacon {
+                contract: beacon.to_string(),
+            },
+            ..instantiate_msg()
+        };
+        let minter = app
+            .instantiate_contract(minter_code, owner.clone(), &msg, &[], "minter", None)
+            .unwrap();
+
+        let msg = DragonExecuteMsg::ApproveAll {
+            operator: minter.to_string(),
+            expires: None,
+        };
+        app.execute_contract(user.clone(), dragon.clone(), &msg, &[])
+            .unwrap();
+        let fuse = Cw20ExecuteMsg::Send {
+            contract: minter.to_string(),
+            amount: Uint128::new(45_000_000),
+            msg: to_binary(&ReceiveMsg::Upgrade {
+                owner: user.to_string(),
+                id_1: Uint64::new(1),
+                id_2: Uint64::new(2),
+                id_3: Uint64::new(3),
+                rarity: "common".to_string(),
+                commitment: None,
+            })
+            .unwrap(),
+        };
+        // only the egg minter and configured burners may burn for upgrades
+        let err = app
+            .execute_contract(user.clone(), drgn.clone(), &fuse, &[])
+            .unwrap_err();
+        assert_eq!(err.root_cause().to_string(), "Unauthorized");
+        let msg = DragonExecuteMsg::UpdateBurners {
+            burners: vec![minter.to_string()],
+        };
+        app.execute_contract(owner, dragon.clone(), &msg, &[])
+            .unwrap();
+        app.execute_contract(user, drgn, &fuse, &[]).unwrap();
+
+        let msg = DragonQueryMsg::BurnedTokens {
+            start_after: None,
+            limit: None,
+        };
+        let burned: BurnedTokensResponse<Dragon> =
+            app.wrap().query_wasm_smart(&dragon, &msg).unwrap();
+        let reasons: Vec<_> = burned
+            .tokens
+            .iter()
+            .map(|b| (b.token.token_id.as_str(), b.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                ("1", BurnReason::Upgrade),
+                ("2", BurnReason::Upgrade),
+                ("3", BurnReason::Upgrade)
+            ]
+        );
+    }
+}
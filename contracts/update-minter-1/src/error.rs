@@ -1,4 +1,7 @@
-use cosmwasm_std::{DivideByZeroError, OverflowError, StdError, Uint128};
+use cosmwasm_std::{DivideByZeroError, OverflowError, StdError, Uint128, Uint64};
+use stake_dragons_common::migrate::MigrateError;
+use stake_dragons_common::ownership::OwnershipError;
+use stake_dragons_common::random::RandomnessError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +9,15 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("{0}")]
+    Ownership(#[from] OwnershipError),
+
+    #[error("{0}")]
+    Randomness(#[from] RandomnessError),
+
     #[error("Unauthorized {msg}")]
     Unauthorized { msg: String },
 
@@ -27,6 +39,12 @@ pub enum ContractError {
         min: Uint128,
         sent: Uint128,
     },
+    #[error("Invalid success chance: {chance} (max: 10000)")]
+    InvalidSuccessChance { chance: Uint64 },
+
+    #[error("A randomness source is required to migrate")]
+    MissingRandomnessSource {},
+
     #[error("{0}")]
     Overflow(#[from] OverflowError),
 
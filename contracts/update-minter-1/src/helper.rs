@@ -1,41 +1,7 @@
 use crate::ContractError;
-use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Uint64;
-use cw721_base::msg::MintMsg as Cw721MintMsg;
-
-#[cw_serde]
-pub struct DragonMintMsg {
-    pub mint: CustomMintMsg,
-}
-
-#[cw_serde]
-pub struct CustomMintMsg {
-    pub base: Cw721MintMsg<Extension>,
-    pub extension: Vec<Trait>,
-}
-
-#[cw_serde]
-pub struct Trait {
-    pub display_type: Option<String>,
-    pub trait_type: String,
-    pub value: String,
-}
-//Change some to mandatory some to optional
-// see: https://docs.opensea.io/docs/metadata-standards
-#[cw_serde]
-pub struct Metadata {
-    pub name: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-    pub external_url: Option<String>,
-    pub attributes: Vec<Trait>,
-    pub image_data: Option<String>,
-    pub background_color: Option<String>,
-    pub animation_url: Option<String>,
-    pub youtube_url: Option<String>,
-}
-
-pub type Extension = Option<Metadata>;
+use stake_dragons_common::MintMsg as Cw721MintMsg;
+pub use stake_dragons_common::{CustomMintMsg, Extension, Metadata, MintExecuteMsg, Trait};
 
 pub fn generate_updated_dragon_mint_msg(
     id: Uint64,
@@ -44,7 +10,7 @@ pub fn generate_updated_dragon_mint_msg(
     ovulation: String,
     daily_reward: String,
     season: String,
-) -> Result<DragonMintMsg, ContractError> {
+) -> Result<MintExecuteMsg, ContractError> {
     let attributes = vec![
         Trait {
             display_type: None,
@@ -67,22 +33,20 @@ pub fn generate_updated_dragon_mint_msg(
         description: Option::from("Updated stake dragon".to_string()),
         image: Option::from("".to_string()),
         external_url: Option::from("".to_string()),
-        attributes: attributes.clone(),
+        attributes: Some(attributes.clone()),
         image_data: Option::from("".to_string()),
         background_color: Option::from("".to_string()),
         animation_url: Option::from("".to_string()),
         youtube_url: Option::from("".to_string()),
     };
-    let msg = DragonMintMsg {
-        mint: CustomMintMsg {
-            base: Cw721MintMsg {
-                token_id: id.to_string(),
-                owner: owner.to_string(),
-                token_uri: None,
-                extension: Extension::from(metadata),
-            },
-            extension: attributes,
+    let msg = MintExecuteMsg::Mint(CustomMintMsg {
+        base: Cw721MintMsg {
+            token_id: id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: Extension::from(metadata),
         },
-    };
+        extension: attributes,
+    });
     Ok(msg)
 }
@@ -1,13 +1,18 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Uint128, Uint64};
+use cosmwasm_std::{Addr, Binary, Uint128, Uint64};
 use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use stake_dragons_common::ownership::{OwnershipMsg, PendingOwnerResponse};
+use stake_dragons_common::random::RandomnessSource;
+
+use crate::state::{PendingUpgrade, UpgradeRecord};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub drgn_contract: Addr,
     pub allowed_cw20: Addr,
     pub allowed_operators: Vec<String>,
-    pub random_key: i32,
     pub drgn_rac: Uint128,
     pub season: String,
     pub dragon: Addr,
@@ -15,17 +20,23 @@ pub struct InstantiateMsg {
     pub egg_minter: String,
     pub drgn_recipient: String,
     pub cw20_recipient: String,
+    pub randomness_source: RandomnessSource,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Required when migrating from a version that stored no source.
+    pub randomness_source: Option<RandomnessSource>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
+    UpdateOwnership(OwnershipMsg),
     EditState {
-        owner: String,
         drgn_contract: Addr,
         allowed_cw20: Addr,
         allowed_operators: Vec<String>,
-        random_key: i32,
         drgn_rac: Uint128,
         season: String,
     },
@@ -47,6 +58,14 @@ pub enum ExecuteMsg {
         epic_ovulation: Uint64,
         legendary_reward: Uint64,
         legendary_ovulation: Uint64,
+        common_success: Uint64,
+        uncommon_success: Uint64,
+        rare_success: Uint64,
+        epic_success: Uint64,
+        legendary_success: Uint64,
+    },
+    EditRandomnessSource {
+        source: RandomnessSource,
     },
     EditMinMax {
         common_min: Uint128,
@@ -60,6 +79,12 @@ pub enum ExecuteMsg {
         legendary_min: Uint128,
         legendary_max: Uint128,
     },
+    /// Rolls an upgrade once the beacon round it waits for is published.
+    /// Anyone can fulfil an upgrade made without a commitment.
+    FulfilUpgrade {
+        upgrade_id: u64,
+        secret: Option<Binary>,
+    },
     UpgradeAdmin {
         owner: String,
         id_1: Uint64,
@@ -80,7 +105,8 @@ pub enum ReceiveMsg {
         id_2: Uint64,
         id_3: Uint64,
         rarity: String,
-        res: String,
+        /// sha256 of a secret to reveal in `FulfilUpgrade`.
+        commitment: Option<Binary>,
     },
 }
 
@@ -93,6 +119,16 @@ pub enum QueryMsg {
     GetStats {},
     #[returns(GetMinMaxResponse)]
     GetMinMax {},
+    #[returns(UpgradeHistoryResponse)]
+    UpgradeHistory {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(PendingUpgrade)]
+    PendingUpgrade { upgrade_id: u64 },
+    #[returns(PendingOwnerResponse)]
+    PendingOwner {},
 }
 
 #[cw_serde]
@@ -101,9 +137,9 @@ pub struct GetStateResponse {
     pub drgn_contract: Addr,
     pub allowed_cw20: Addr,
     pub allowed_operators: Vec<String>,
-    pub random_key: i32,
     pub drgn_rac: Uint128,
     pub season: String,
+    pub randomness_source: RandomnessSource,
     pub dragon: Addr,
     pub updated_dragon: String,
     pub egg_minter: String,
@@ -123,6 +159,16 @@ pub struct GetStatsResponse {
     pub epic_ovulation: Uint64,
     pub legendary_reward: Uint64,
     pub legendary_ovulation: Uint64,
+    pub common_success: Uint64,
+    pub uncommon_success: Uint64,
+    pub rare_success: Uint64,
+    pub epic_success: Uint64,
+    pub legendary_success: Uint64,
+}
+
+#[cw_serde]
+pub struct UpgradeHistoryResponse {
+    pub upgrades: Vec<UpgradeRecord>,
 }
 
 #[cw_serde]
@@ -151,7 +197,9 @@ pub struct GetDragonInfoMsg {
     pub DragonInfo: DragonInfoMsg,
 }
 
-#[cw_serde]
+/// The fields of the dragon contract's `DragonInfo` answer the upgrade reads.
+/// Not `cw_serde`, so the fields the dragon contract added since still parse.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct DragonResponse {
     pub owner: String,
     pub token_id: String,
@@ -189,7 +237,6 @@ pub struct MinterEditState {
 pub struct MinterEditStateMsg {
     new_owner: String,
     base_price: Uint128,
-    random_key: i32,
     hatch_price: Uint128,
     egg_sale_size: Uint64,
     allowed_cw20: Addr,
@@ -2,7 +2,8 @@ use cosmwasm_std::{Addr, Uint128, Uint64};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::Item;
+use cw_storage_plus::{Item, Map};
+use stake_dragons_common::random::{RandomnessRequest, RandomnessSource};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
@@ -10,7 +11,6 @@ pub struct State {
     pub drgn_contract: Addr,
     pub allowed_cw20: Addr,
     pub allowed_operators: Vec<String>,
-    pub random_key: i32,
     pub drgn_rac: Uint128,
     pub season: String,
 }
@@ -36,6 +36,79 @@ pub struct UpdatedStats {
     pub epic_ovulation: Uint64,
     pub legendary_reward: Uint64,
     pub legendary_ovulation: Uint64,
+    /// chance of a successful upgrade, in basis points of 10000
+    pub common_success: Uint64,
+    pub uncommon_success: Uint64,
+    pub rare_success: Uint64,
+    pub epic_success: Uint64,
+    pub legendary_success: Uint64,
+}
+
+impl UpdatedStats {
+    /// Success chance for upgrading three dragons of `rarity`, in basis points.
+    pub fn success_chance(&self, rarity: &str) -> Option<Uint64> {
+        match rarity {
+            "common" => Some(self.common_success),
+            "uncommon" => Some(self.uncommon_success),
+            "rare" => Some(self.rare_success),
+            "epic" => Some(self.epic_success),
+            "legendary" => Some(self.legendary_success),
+            _ => None,
+        }
+    }
+
+    /// Daily reward and ovulation period of an upgraded dragon of `rarity`.
+    pub fn traits(&self, rarity: &str) -> Option<(Uint64, Uint64)> {
+        match rarity {
+            "common" => Some((self.common_reward, self.common_ovulation)),
+            "uncommon" => Some((self.uncommon_reward, self.uncommon_ovulation)),
+            "rare" => Some((self.rare_reward, self.rare_ovulation)),
+            "epic" => Some((self.epic_reward, self.epic_ovulation)),
+            "legendary" => Some((self.legendary_reward, self.legendary_ovulation)),
+            _ => None,
+        }
+    }
+}
+
+/// `UpdatedStats` as stored before upgrades could fail.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyUpdatedStats {
+    pub common_reward: Uint64,
+    pub common_ovulation: Uint64,
+    pub uncommon_reward: Uint64,
+    pub uncommon_ovulation: Uint64,
+    pub rare_reward: Uint64,
+    pub rare_ovulation: Uint64,
+    pub epic_reward: Uint64,
+    pub epic_ovulation: Uint64,
+    pub legendary_reward: Uint64,
+    pub legendary_ovulation: Uint64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UpgradeRecord {
+    pub id: u64,
+    pub owner: Addr,
+    pub rarity: String,
+    pub burned: Vec<Uint64>,
+    pub success: bool,
+    /// `None` for operator upgrades, which always succeed
+    pub roll: Option<u64>,
+    pub success_chance: Uint64,
+    pub minted: Option<Uint64>,
+    pub height: u64,
+}
+
+/// Upgrade whose dragons were burned and paid for, rolled once its draw is
+/// fulfilled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingUpgrade {
+    pub owner: Addr,
+    pub rarity: String,
+    pub burned: Vec<Uint64>,
+    /// chance at the time of the request, in basis points of 10000
+    pub success_chance: Uint64,
+    pub request: RandomnessRequest,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -55,5 +128,13 @@ pub struct RequiredMinMax {
 pub const STATE: Item<State> = Item::new("state");
 pub const CONTRACTS: Item<Contracts> = Item::new("contracts");
 pub const UPDATED_STATS: Item<UpdatedStats> = Item::new("stats");
+pub const LEGACY_UPDATED_STATS: Item<LegacyUpdatedStats> = Item::new("stats");
 pub const REQUIRED_MIN_MAX: Item<RequiredMinMax> = Item::new("min_max");
 pub const UPDATED_DRAGON_COUNT: Item<Uint64> = Item::new("updated_dragon_count");
+pub const RANDOMNESS_SOURCE: Item<RandomnessSource> = Item::new("randomness_source");
+pub const UPGRADE_COUNT: Item<u64> = Item::new("upgrade_count");
+pub const UPGRADE_HISTORY: Map<(&Addr, u64), UpgradeRecord> = Map::new("upgrade_history");
+pub const PENDING_UPGRADES: Map<u64, PendingUpgrade> = Map::new("pending_upgrades");
+
+pub const BASIS_POINTS: u64 = 10000;
+pub const DEFAULT_SUCCESS_CHANCE: u64 = 9000;
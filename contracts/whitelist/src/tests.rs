@@ -101,6 +101,7 @@ mod tests {
                 token_uri: Some(token_uri.clone()),
                 extension: None,
             },
+            extension: vec![],
         };
         let msg = ExecuteMsg::Mint(mint_msg);
         execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
@@ -116,6 +117,7 @@ mod tests {
                 token_uri: Some(token_uri.clone()),
                 extension: None,
             },
+            extension: vec![],
         };
         let msg = ExecuteMsg::Mint(mint_msg);
         execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
@@ -131,6 +133,7 @@ mod tests {
                 token_uri: Some(token_uri.clone()),
                 extension: None,
             },
+            extension: vec![],
         };
         let msg = ExecuteMsg::Mint(mint_msg);
         execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
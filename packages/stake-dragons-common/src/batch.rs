@@ -0,0 +1,64 @@
+use cosmwasm_std::Uint64;
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Most tokens a single batch message may touch.
+pub const MAX_BATCH_SIZE: usize = 30;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum BatchError {
+    #[error("Batch must contain at least one token")]
+    Empty {},
+
+    #[error("Batch exceeds the maximum of {max} tokens")]
+    TooLarge { max: usize },
+
+    #[error("Token {token_id} appears more than once in the batch")]
+    Duplicate { token_id: Uint64 },
+}
+
+/// Rejects empty, oversized and repeating batches before any token is touched.
+pub fn check_batch(token_ids: &[Uint64]) -> Result<(), BatchError> {
+    if token_ids.is_empty() {
+        return Err(BatchError::Empty {});
+    }
+    if token_ids.len() > MAX_BATCH_SIZE {
+        return Err(BatchError::TooLarge {
+            max: MAX_BATCH_SIZE,
+        });
+    }
+    let mut seen = HashSet::new();
+    for token_id in token_ids {
+        if !seen.insert(token_id.u64()) {
+            return Err(BatchError::Duplicate {
+                token_id: *token_id,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_bad_batches() {
+        let ids = |ids: &[u64]| ids.iter().map(|id| Uint64::new(*id)).collect::<Vec<_>>();
+        assert_eq!(check_batch(&[]), Err(BatchError::Empty {}));
+        assert_eq!(
+            check_batch(&ids(&[1, 2, 1])),
+            Err(BatchError::Duplicate {
+                token_id: Uint64::new(1)
+            })
+        );
+        let too_many: Vec<_> = (0..=MAX_BATCH_SIZE as u64).collect();
+        assert_eq!(
+            check_batch(&ids(&too_many)),
+            Err(BatchError::TooLarge {
+                max: MAX_BATCH_SIZE
+            })
+        );
+        assert!(check_batch(&ids(&[3, 1, 2])).is_ok());
+    }
+}
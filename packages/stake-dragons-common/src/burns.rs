@@ -0,0 +1,87 @@
+use cosmwasm_std::{Order, StdResult, Storage, Uint64};
+use cw_storage_plus::{Bound, Map};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnReason {
+    /// Consumed to upgrade another token.
+    Upgrade,
+    /// Consumed with others to forge a new token.
+    Forge,
+    /// Burned by its owner.
+    User,
+}
+
+/// Last record of a token, moved out of the live records when it was burned.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BurnedToken<T> {
+    pub token: T,
+    pub reason: BurnReason,
+    /// Zero for tokens burned before their records were kept.
+    pub burned_at: Uint64,
+}
+
+/// The `burn` variant of the NFT contracts' `ExecuteMsg`, used by the minters
+/// to burn the tokens an upgrade or forge consumes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnExecuteMsg {
+    Burn {
+        token_id: String,
+        reason: Option<BurnReason>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BurnedTokensResponse<T> {
+    pub tokens: Vec<BurnedToken<T>>,
+}
+
+/// Up to `limit` burned tokens of `burned`, by id, after `start_after`.
+pub fn query_burned_tokens<T: Serialize + DeserializeOwned>(
+    storage: &dyn Storage,
+    burned: &Map<u64, BurnedToken<T>>,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<BurnedTokensResponse<T>> {
+    let tokens = burned
+        .range(
+            storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, token)| token))
+        .collect::<StdResult<_>>()?;
+    Ok(BurnedTokensResponse { tokens })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    const BURNED: Map<u64, BurnedToken<String>> = Map::new("burned_tokens");
+
+    #[test]
+    fn burned_tokens_page_by_id() {
+        let mut storage = MockStorage::new();
+        for id in 1..=3 {
+            let token = BurnedToken {
+                token: id.to_string(),
+                reason: BurnReason::User,
+                burned_at: Uint64::new(id * 10),
+            };
+            BURNED.save(&mut storage, id, &token).unwrap();
+        }
+        let page = query_burned_tokens(&storage, &BURNED, Some(1), 1).unwrap();
+        let ids: Vec<_> = page.tokens.iter().map(|t| t.token.as_str()).collect();
+        assert_eq!(ids, vec!["2"]);
+        let page = query_burned_tokens(&storage, &BURNED, Some(2), 10).unwrap();
+        assert_eq!(page.tokens[0].burned_at, Uint64::new(30));
+    }
+}
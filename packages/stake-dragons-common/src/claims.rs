@@ -0,0 +1,129 @@
+use cosmwasm_std::{Addr, Api, Response, StdResult, Storage};
+use cw_storage_plus::Map;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Where each owner's rewards are paid, if not to the owner.
+pub const REWARD_RECIPIENTS: Map<&str, Addr> = Map::new("reward_recipients");
+/// Address each owner lets claim on their behalf. A delegate can only claim;
+/// transfers and unstaking stay with the owner.
+pub const CLAIM_DELEGATES: Map<&str, Addr> = Map::new("claim_delegates");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimSettingsMsg {
+    SetRewardRecipient { recipient: String },
+    ClearRewardRecipient {},
+    SetClaimDelegate { delegate: String },
+    ClearClaimDelegate {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimSettingsResponse {
+    pub owner: String,
+    pub reward_recipient: Addr,
+    pub claim_delegate: Option<Addr>,
+}
+
+/// Applies `msg` to the claim settings of `sender`, who is always the owner
+/// being configured.
+pub fn update_claim_settings(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    sender: &Addr,
+    msg: ClaimSettingsMsg,
+) -> StdResult<Response> {
+    let owner = sender.as_str();
+    let res = Response::new()
+        .add_attribute("action", "update_claim_settings")
+        .add_attribute("owner", owner);
+    match msg {
+        ClaimSettingsMsg::SetRewardRecipient { recipient } => {
+            let recipient = api.addr_validate(&recipient)?;
+            REWARD_RECIPIENTS.save(storage, owner, &recipient)?;
+            Ok(res.add_attribute("reward_recipient", recipient))
+        }
+        ClaimSettingsMsg::ClearRewardRecipient {} => {
+            REWARD_RECIPIENTS.remove(storage, owner);
+            Ok(res.add_attribute("reward_recipient", owner))
+        }
+        ClaimSettingsMsg::SetClaimDelegate { delegate } => {
+            let delegate = api.addr_validate(&delegate)?;
+            CLAIM_DELEGATES.save(storage, owner, &delegate)?;
+            Ok(res.add_attribute("claim_delegate", delegate))
+        }
+        ClaimSettingsMsg::ClearClaimDelegate {} => {
+            CLAIM_DELEGATES.remove(storage, owner);
+            Ok(res.add_attribute("claim_delegate", ""))
+        }
+    }
+}
+
+/// Address `owner`'s rewards are paid to.
+pub fn reward_recipient(storage: &dyn Storage, owner: &str) -> StdResult<Addr> {
+    Ok(REWARD_RECIPIENTS
+        .may_load(storage, owner)?
+        .unwrap_or_else(|| Addr::unchecked(owner)))
+}
+
+/// Whether `sender` may claim for tokens of `owner`: the owner or their
+/// delegate.
+pub fn can_claim(storage: &dyn Storage, owner: &str, sender: &Addr) -> StdResult<bool> {
+    if sender.as_str() == owner {
+        return Ok(true);
+    }
+    let delegate = CLAIM_DELEGATES.may_load(storage, owner)?;
+    Ok(delegate.as_ref() == Some(sender))
+}
+
+pub fn query_claim_settings(
+    storage: &dyn Storage,
+    owner: String,
+) -> StdResult<ClaimSettingsResponse> {
+    Ok(ClaimSettingsResponse {
+        reward_recipient: reward_recipient(storage, &owner)?,
+        claim_delegate: CLAIM_DELEGATES.may_load(storage, &owner)?,
+        owner,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{MockApi, MockStorage};
+
+    #[test]
+    fn settings_are_per_owner_and_clearable() {
+        let mut storage = MockStorage::new();
+        let api = MockApi::default();
+        let owner = Addr::unchecked("cold");
+        let hot = Addr::unchecked("hot");
+        let set = |storage: &mut MockStorage, msg| {
+            update_claim_settings(storage, &api, &owner, msg).unwrap();
+        };
+
+        assert!(!can_claim(&storage, "cold", &hot).unwrap());
+        set(
+            &mut storage,
+            ClaimSettingsMsg::SetClaimDelegate {
+                delegate: "hot".to_string(),
+            },
+        );
+        set(
+            &mut storage,
+            ClaimSettingsMsg::SetRewardRecipient {
+                recipient: "vault".to_string(),
+            },
+        );
+        assert!(can_claim(&storage, "cold", &hot).unwrap());
+        assert!(!can_claim(&storage, "other", &hot).unwrap());
+        assert_eq!(reward_recipient(&storage, "cold").unwrap(), "vault");
+        assert_eq!(reward_recipient(&storage, "other").unwrap(), "other");
+
+        set(&mut storage, ClaimSettingsMsg::ClearClaimDelegate {});
+        set(&mut storage, ClaimSettingsMsg::ClearRewardRecipient {});
+        let settings = query_claim_settings(&storage, "cold".to_string()).unwrap();
+        assert_eq!(settings.reward_recipient, "cold");
+        assert_eq!(settings.claim_delegate, None);
+    }
+}
@@ -0,0 +1,14 @@
+pub mod batch;
+pub mod burns;
+pub mod claims;
+pub mod migrate;
+pub mod nft;
+pub mod ownership;
+pub mod random;
+pub mod reward;
+pub mod stats;
+pub mod supply;
+pub mod templates;
+
+pub use crate::nft::{CustomMintMsg, Extension, Metadata, MintExecuteMsg, Trait};
+pub use cw721_base::msg::MintMsg;
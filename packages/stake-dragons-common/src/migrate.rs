@@ -0,0 +1,186 @@
+use cosmwasm_std::{StdResult, Storage};
+use cw_storage_plus::Item;
+pub use semver::Version;
+use thiserror::Error;
+
+/// Id of the last token a state migration rewrote, kept while the migration
+/// still has tokens left for the next `migrate` call.
+pub const MIGRATION_CURSOR: Item<u64> = Item::new("migration_cursor");
+
+/// Tokens rewritten per `migrate` call when the message sets no limit; the
+/// rewrites of a collection are split over as many calls as they need.
+pub const DEFAULT_MIGRATION_LIMIT: u32 = 200;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum MigrateError {
+    #[error("Cannot migrate {stored} with the code of {expected}")]
+    WrongContract { stored: String, expected: String },
+
+    #[error("Cannot migrate from {stored} down to {new}")]
+    Downgrade { stored: String, new: String },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidVersion { version: String },
+
+    #[error("Migration unfinished after token {cursor}, migrate again first")]
+    Unfinished { cursor: u64 },
+}
+
+fn parse(version: &str) -> Result<Version, MigrateError> {
+    Version::parse(version).map_err(|_| MigrateError::InvalidVersion {
+        version: version.to_string(),
+    })
+}
+
+/// Checks the cw2 info stored by the running code against the code being
+/// migrated to and returns the stored version, so the caller can run every
+/// state migration newer than it. Re-running the same version is allowed.
+pub fn check_migration(
+    stored_contract: &str,
+    stored_version: &str,
+    contract: &str,
+    version: &str,
+) -> Result<Version, MigrateError> {
+    if stored_contract != contract {
+        return Err(MigrateError::WrongContract {
+            stored: stored_contract.to_string(),
+            expected: contract.to_string(),
+        });
+    }
+    let stored = parse(stored_version)?;
+    if stored > parse(version)? {
+        return Err(MigrateError::Downgrade {
+            stored: stored_version.to_string(),
+            new: version.to_string(),
+        });
+    }
+    Ok(stored)
+}
+
+/// Like [`check_migration`] for code that was deployed under another
+/// contract's cw2 name before `renamed_in`: the legacy name is taken as this
+/// contract's own, but only for the versions stored before the rename.
+pub fn check_renamed_migration(
+    stored_contract: &str,
+    stored_version: &str,
+    legacy_contract: &str,
+    renamed_in: &Version,
+    contract: &str,
+    version: &str,
+) -> Result<Version, MigrateError> {
+    if stored_contract == legacy_contract && parse(stored_version)? < *renamed_in {
+        return check_migration(contract, stored_version, contract, version);
+    }
+    check_migration(stored_contract, stored_version, contract, version)
+}
+
+/// Fails while a state migration has tokens left to rewrite, so nothing runs
+/// on a half migrated collection.
+pub fn ensure_migrated(storage: &dyn Storage) -> Result<(), MigrateError> {
+    match MIGRATION_CURSOR.may_load(storage) {
+        Ok(Some(cursor)) => Err(MigrateError::Unfinished { cursor }),
+        _ => Ok(()),
+    }
+}
+
+/// Records how far a migration got: keeps the id of the last token rewritten
+/// when there are more, and clears it once the collection is done. Returns
+/// whether it is.
+pub fn save_migration_cursor(
+    storage: &mut dyn Storage,
+    last: Option<u64>,
+    done: bool,
+) -> StdResult<bool> {
+    match last {
+        Some(last) if !done => MIGRATION_CURSOR.save(storage, &last)?,
+        _ => MIGRATION_CURSOR.remove(storage),
+    }
+    Ok(done)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn returns_stored_version() {
+        let from = check_migration("crates.io:minter", "0.1.0", "crates.io:minter", "0.2.0");
+        assert_eq!(from.unwrap(), Version::new(0, 1, 0));
+        assert!(check_migration("crates.io:minter", "0.2.0", "crates.io:minter", "0.2.0").is_ok());
+    }
+
+    #[test]
+    fn refuses_downgrades_and_other_contracts() {
+        assert_eq!(
+            check_migration("crates.io:minter", "0.3.0", "crates.io:minter", "0.2.0"),
+            Err(MigrateError::Downgrade {
+                stored: "0.3.0".to_string(),
+                new: "0.2.0".to_string()
+            })
+        );
+        assert_eq!(
+            check_migration("crates.io:box-minter", "0.1.0", "crates.io:minter", "0.2.0"),
+            Err(MigrateError::WrongContract {
+                stored: "crates.io:box-minter".to_string(),
+                expected: "crates.io:minter".to_string()
+            })
+        );
+        assert_eq!(
+            check_migration("crates.io:minter", "one", "crates.io:minter", "0.2.0"),
+            Err(MigrateError::InvalidVersion {
+                version: "one".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn accepts_legacy_name_before_rename() {
+        let renamed_in = Version::new(0, 2, 0);
+        let from = check_renamed_migration(
+            "crates.io:dragon-mint",
+            "0.1.0",
+            "crates.io:dragon-mint",
+            &renamed_in,
+            "crates.io:cosmic",
+            "0.2.0",
+        );
+        assert_eq!(from.unwrap(), Version::new(0, 1, 0));
+        assert_eq!(
+            check_renamed_migration(
+                "crates.io:dragon-mint",
+                "0.2.0",
+                "crates.io:dragon-mint",
+                &renamed_in,
+                "crates.io:cosmic",
+                "0.2.0",
+            ),
+            Err(MigrateError::WrongContract {
+                stored: "crates.io:dragon-mint".to_string(),
+                expected: "crates.io:cosmic".to_string()
+            })
+        );
+        let from = check_renamed_migration(
+            "crates.io:cosmic",
+            "0.2.0",
+            "crates.io:dragon-mint",
+            &renamed_in,
+            "crates.io:cosmic",
+            "0.2.0",
+        );
+        assert_eq!(from.unwrap(), Version::new(0, 2, 0));
+    }
+
+    #[test]
+    fn cursor_blocks_until_done() {
+        let mut storage = MockStorage::new();
+        assert_eq!(ensure_migrated(&storage), Ok(()));
+        assert!(!save_migration_cursor(&mut storage, Some(3), false).unwrap());
+        assert_eq!(
+            ensure_migrated(&storage),
+            Err(MigrateError::Unfinished { cursor: 3 })
+        );
+        assert!(save_migration_cursor(&mut storage, Some(5), true).unwrap());
+        assert_eq!(ensure_migrated(&storage), Ok(()));
+    }
+}
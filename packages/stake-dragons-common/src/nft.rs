@@ -0,0 +1,167 @@
+use cosmwasm_std::{Empty, StdResult, Storage};
+use cw721_base::msg::MintMsg as Cw721MintMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct Trait {
+    pub display_type: Option<String>,
+    pub trait_type: String,
+    pub value: String,
+}
+
+// see: https://docs.opensea.io/docs/metadata-standards
+// `attributes` is optional so tokens minted without any traits (eggs, boxes)
+// and tokens minted with them (dragons, crystals, cosmics) share one layout.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub external_url: Option<String>,
+    pub attributes: Option<Vec<Trait>>,
+    pub image_data: Option<String>,
+    pub background_color: Option<String>,
+    pub animation_url: Option<String>,
+    pub youtube_url: Option<String>,
+}
+
+impl Trait {
+    pub fn new(trait_type: &str, value: impl ToString) -> Self {
+        Trait {
+            display_type: None,
+            trait_type: trait_type.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Metadata {
+    /// Sets the value of the `trait_type` attribute, adding it if missing.
+    pub fn set_trait(&mut self, trait_type: &str, value: String) {
+        let attributes = self.attributes.get_or_insert_with(Vec::new);
+        match attributes.iter_mut().find(|t| t.trait_type == trait_type) {
+            Some(existing) => existing.value = value,
+            None => attributes.push(Trait::new(trait_type, value)),
+        }
+    }
+}
+
+pub type Extension = Option<Metadata>;
+
+/// Presentation fields the collection owner may change after mint. Fields left
+/// out keep their value; attributes follow the contract's own records instead.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct MetadataUpdate {
+    pub token_uri: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub external_url: Option<String>,
+    pub animation_url: Option<String>,
+}
+
+/// Applies `update` to the stored cw721 info of `token_id`.
+pub fn update_metadata(
+    storage: &mut dyn Storage,
+    token_id: &str,
+    update: MetadataUpdate,
+) -> StdResult<()> {
+    let tokens = cw721_base::Cw721Contract::<Extension, Empty>::default().tokens;
+    let mut token = tokens.load(storage, token_id)?;
+    if update.token_uri.is_some() {
+        token.token_uri = update.token_uri;
+    }
+    let metadata = token.extension.get_or_insert_with(Default::default);
+    let fields = [
+        (&mut metadata.name, update.name),
+        (&mut metadata.description, update.description),
+        (&mut metadata.image, update.image),
+        (&mut metadata.external_url, update.external_url),
+        (&mut metadata.animation_url, update.animation_url),
+    ];
+    for (field, value) in fields {
+        if value.is_some() {
+            *field = value;
+        }
+    }
+    tokens.save(storage, token_id, &token)
+}
+
+/// `extension` with `live` set over its stored attributes, so NFT info queries
+/// describe the token as it is now.
+pub fn with_live_traits(extension: Extension, live: Vec<Trait>) -> Extension {
+    let mut metadata = extension.unwrap_or_default();
+    for t in live {
+        metadata.set_trait(&t.trait_type, t.value);
+    }
+    Some(metadata)
+}
+
+/// Mint payload accepted by every StakeDragons NFT contract.
+/// `extension` carries the on-chain traits (kind, daily income, ...) the
+/// contract keeps in its own storage; contracts without traits leave it empty.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CustomMintMsg {
+    pub base: Cw721MintMsg<Extension>,
+    #[serde(default)]
+    pub extension: Vec<Trait>,
+}
+
+/// The `mint` variant of the NFT contracts' `ExecuteMsg`, used by the minters
+/// to build the cross-contract call.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MintExecuteMsg {
+    Mint(CustomMintMsg),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{from_json, to_json_vec};
+
+    #[test]
+    fn mint_msg_matches_legacy_payloads() {
+        // dragons and crystals used to be minted with a bare `attributes` list
+        let legacy = br#"{"mint":{"base":{"token_id":"1","owner":"owner","token_uri":null,"extension":{"name":null,"description":null,"image":null,"external_url":null,"attributes":[{"display_type":null,"trait_type":"kind","value":"common"}],"image_data":null,"background_color":null,"animation_url":null,"youtube_url":null}},"extension":[]}}"#;
+        let MintExecuteMsg::Mint(msg) = from_json(legacy).unwrap();
+        let metadata = msg.base.extension.unwrap();
+        assert_eq!(metadata.attributes.unwrap()[0].value, "common");
+
+        // eggs and boxes were minted without the trait list
+        let legacy = br#"{"mint":{"base":{"token_id":"2","owner":"owner","token_uri":null,"extension":null}}}"#;
+        let MintExecuteMsg::Mint(msg) = from_json(legacy).unwrap();
+        assert!(msg.extension.is_empty());
+    }
+
+    #[test]
+    fn mint_msg_round_trips() {
+        let msg = MintExecuteMsg::Mint(CustomMintMsg {
+            base: Cw721MintMsg {
+                token_id: "1".to_string(),
+                owner: "owner".to_string(),
+                token_uri: None,
+                extension: Some(Metadata::default()),
+            },
+            extension: vec![Trait {
+                display_type: None,
+                trait_type: "kind".to_string(),
+                value: "fire".to_string(),
+            }],
+        });
+        let decoded: MintExecuteMsg = from_json(to_json_vec(&msg).unwrap()).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn set_trait_replaces_or_appends() {
+        let mut metadata = Metadata::default();
+        metadata.set_trait("level", "1".to_string());
+        metadata.set_trait("xp", "10".to_string());
+        metadata.set_trait("level", "2".to_string());
+        let attributes = metadata.attributes.unwrap();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes[0].value, "2");
+    }
+}
@@ -0,0 +1,240 @@
+use cosmwasm_std::{Addr, Api, BlockInfo, Response, StdError, StdResult, Storage};
+use cw_storage_plus::Item;
+use cw_utils::Expiration;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Owner proposed by the current owner, who only takes over once accepted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner: Addr,
+    pub expires: Option<Expiration>,
+}
+
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnershipMsg {
+    /// Offers ownership to `new_owner`, replacing any earlier proposal.
+    ProposeOwner {
+        new_owner: String,
+        expires: Option<Expiration>,
+    },
+    AcceptOwnership {},
+    /// Leaves the contract without an owner. Cannot be undone.
+    RenounceOwnership {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwnerResponse {
+    pub pending_owner: Option<Addr>,
+    pub expires: Option<Expiration>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum OwnershipError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Only the owner can change ownership")]
+    NotOwner {},
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("Only the pending owner can accept ownership")]
+    NotPendingOwner {},
+
+    #[error("Ownership proposal expired")]
+    ProposalExpired {},
+}
+
+/// Applies `msg` on behalf of `sender` to a contract currently owned by
+/// `owner`, and returns the owner the contract must store from now on. The
+/// owner is empty once renounced, so no sender matches it again.
+pub fn update_ownership(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    block: &BlockInfo,
+    sender: &Addr,
+    owner: &str,
+    msg: OwnershipMsg,
+) -> Result<(String, Response), OwnershipError> {
+    let res = Response::new().add_attribute("action", "update_ownership");
+    match msg {
+        OwnershipMsg::ProposeOwner { new_owner, expires } => {
+            if sender.as_str() != owner {
+                return Err(OwnershipError::NotOwner {});
+            }
+            if matches!(expires, Some(expires) if expires.is_expired(block)) {
+                return Err(OwnershipError::ProposalExpired {});
+            }
+            let new_owner = api.addr_validate(&new_owner)?;
+            PENDING_OWNER.save(
+                storage,
+                &PendingOwner {
+                    owner: new_owner.clone(),
+                    expires,
+                },
+            )?;
+            Ok((
+                owner.to_string(),
+                res.add_attribute("pending_owner", new_owner),
+            ))
+        }
+        OwnershipMsg::AcceptOwnership {} => {
+            let pending = PENDING_OWNER
+                .may_load(storage)?
+                .ok_or(OwnershipError::NoPendingOwner {})?;
+            if *sender != pending.owner {
+                return Err(OwnershipError::NotPendingOwner {});
+            }
+            if matches!(pending.expires, Some(expires) if expires.is_expired(block)) {
+                return Err(OwnershipError::ProposalExpired {});
+            }
+            PENDING_OWNER.remove(storage);
+            Ok((
+                pending.owner.to_string(),
+                res.add_attribute("new_owner", pending.owner),
+            ))
+        }
+        OwnershipMsg::RenounceOwnership {} => {
+            if sender.as_str() != owner {
+                return Err(OwnershipError::NotOwner {});
+            }
+            PENDING_OWNER.remove(storage);
+            Ok((String::new(), res.add_attribute("new_owner", "")))
+        }
+    }
+}
+
+pub fn query_pending_owner(storage: &dyn Storage) -> StdResult<PendingOwnerResponse> {
+    let pending = PENDING_OWNER.may_load(storage)?;
+    Ok(PendingOwnerResponse {
+        pending_owner: pending.as_ref().map(|p| p.owner.clone()),
+        expires: pending.and_then(|p| p.expires),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_env, MockApi, MockStorage};
+
+    fn apply(
+        storage: &mut MockStorage,
+        block: &BlockInfo,
+        sender: &str,
+        owner: &str,
+        msg: OwnershipMsg,
+    ) -> Result<String, OwnershipError> {
+        let sender = Addr::unchecked(sender);
+        update_ownership(storage, &MockApi::default(), block, &sender, owner, msg)
+            .map(|(owner, _)| owner)
+    }
+
+    fn propose(new_owner: &str, expires: Option<Expiration>) -> OwnershipMsg {
+        OwnershipMsg::ProposeOwner {
+            new_owner: new_owner.to_string(),
+            expires,
+        }
+    }
+
+    #[test]
+    fn pending_owner_takes_over_on_accept() {
+        let mut storage = MockStorage::new();
+        let block = mock_env().block;
+        let err = apply(
+            &mut storage,
+            &block,
+            "anyone",
+            "owner",
+            propose("next", None),
+        );
+        assert_eq!(err, Err(OwnershipError::NotOwner {}));
+
+        let owner = apply(
+            &mut storage,
+            &block,
+            "owner",
+            "owner",
+            propose("next", None),
+        );
+        assert_eq!(owner.unwrap(), "owner");
+        let pending = query_pending_owner(&storage).unwrap();
+        assert_eq!(pending.pending_owner, Some(Addr::unchecked("next")));
+
+        let accept = OwnershipMsg::AcceptOwnership {};
+        let err = apply(&mut storage, &block, "owner", "owner", accept.clone());
+        assert_eq!(err, Err(OwnershipError::NotPendingOwner {}));
+        let owner = apply(&mut storage, &block, "next", "owner", accept.clone());
+        assert_eq!(owner.unwrap(), "next");
+        assert_eq!(query_pending_owner(&storage).unwrap().pending_owner, None);
+        let err = apply(&mut storage, &block, "next", "next", accept);
+        assert_eq!(err, Err(OwnershipError::NoPendingOwner {}));
+    }
+
+    #[test]
+    fn proposals_expire() {
+        let mut storage = MockStorage::new();
+        let mut block = mock_env().block;
+        let past = Some(Expiration::AtHeight(block.height));
+        let err = apply(
+            &mut storage,
+            &block,
+            "owner",
+            "owner",
+            propose("next", past),
+        );
+        assert_eq!(err, Err(OwnershipError::ProposalExpired {}));
+
+        let soon = Some(Expiration::AtHeight(block.height + 10));
+        apply(
+            &mut storage,
+            &block,
+            "owner",
+            "owner",
+            propose("next", soon),
+        )
+        .unwrap();
+        block.height += 10;
+        let err = apply(
+            &mut storage,
+            &block,
+            "next",
+            "owner",
+            OwnershipMsg::AcceptOwnership {},
+        );
+        assert_eq!(err, Err(OwnershipError::ProposalExpired {}));
+    }
+
+    #[test]
+    fn renounce_clears_owner_and_proposal() {
+        let mut storage = MockStorage::new();
+        let block = mock_env().block;
+        apply(
+            &mut storage,
+            &block,
+            "owner",
+            "owner",
+            propose("next", None),
+        )
+        .unwrap();
+        let renounce = OwnershipMsg::RenounceOwnership {};
+        let owner = apply(&mut storage, &block, "owner", "owner", renounce.clone());
+        assert_eq!(owner.unwrap(), "");
+        assert_eq!(query_pending_owner(&storage).unwrap().pending_owner, None);
+        let err = apply(&mut storage, &block, "owner", "", renounce);
+        assert_eq!(err, Err(OwnershipError::NotOwner {}));
+    }
+
+    #[test]
+    fn proposed_owner_is_validated() {
+        let mut storage = MockStorage::new();
+        let block = mock_env().block;
+        let err = apply(&mut storage, &block, "owner", "owner", propose("", None));
+        assert!(matches!(err, Err(OwnershipError::Std(_))));
+    }
+}
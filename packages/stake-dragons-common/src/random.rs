@@ -0,0 +1,367 @@
+use cosmwasm_std::{
+    attr, to_json_binary, Api, Attribute, Binary, Deps, Env, QueryRequest, StdError, WasmQuery,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Where a contract takes its randomness from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RandomnessSource {
+    /// A drand style beacon contract answering `{"latest_randomness":{}}` and
+    /// `{"randomness":{"round":..}}`.
+    Beacon { contract: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BeaconQueryMsg {
+    LatestRandomness {},
+    /// The randomness of `round`, once it is published.
+    Randomness {
+        round: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LatestRandomnessResponse {
+    pub round: u64,
+    pub randomness: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RandomnessResponse {
+    pub round: u64,
+    pub randomness: Option<Binary>,
+}
+
+/// A draw committed to before anything it depends on is known: the beacon
+/// round it is taken from wasn't published yet and the requester's secret, if
+/// they committed to one, is only revealed when the draw is fulfilled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RandomnessRequest {
+    pub beacon: String,
+    pub round: u64,
+    /// sha256 of the secret that must be revealed to fulfil the draw.
+    pub commitment: Option<Binary>,
+    pub nonce: Binary,
+}
+
+impl RandomnessRequest {
+    /// Event attributes describing the request.
+    pub fn attributes(&self) -> Vec<Attribute> {
+        vec![
+            attr("randomness_beacon", &self.beacon),
+            attr("randomness_round", self.round.to_string()),
+        ]
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RandomnessError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Commitment must be a sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("Secret does not match the commitment")]
+    SecretMismatch {},
+
+    #[error("Beacon round {round} is not published yet")]
+    RoundNotPublished { round: u64 },
+}
+
+/// One draw. `round` is the beacon round it was taken from; together with
+/// `value` it is emitted in events so every outcome can be recomputed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Randomness {
+    pub round: u64,
+    pub value: [u8; 32],
+}
+
+impl Randomness {
+    /// Uniform roll in `0..modulus`.
+    pub fn roll(&self, modulus: u64) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.value[0..8]);
+        u64::from_be_bytes(bytes) % modulus
+    }
+
+    /// Picks an entry of `table` with probability proportional to `weight`.
+    /// Returns the roll that was used together with the entry.
+    pub fn pick<'a, T, F>(&self, table: &'a [T], weight: F) -> Option<(u64, &'a T)>
+    where
+        F: Fn(&T) -> u64,
+    {
+        let total: u64 = table.iter().map(&weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let roll = self.roll(total);
+        let mut cumulative = 0u64;
+        for entry in table {
+            cumulative += weight(entry);
+            if roll < cumulative {
+                return Some((roll, entry));
+            }
+        }
+        None
+    }
+
+    pub fn to_binary(&self) -> Binary {
+        Binary::from(self.value.to_vec())
+    }
+
+    /// Event attributes describing the draw.
+    pub fn attributes(&self) -> Vec<Attribute> {
+        vec![
+            attr("randomness", self.to_binary().to_base64()),
+            attr("randomness_round", self.round.to_string()),
+        ]
+    }
+}
+
+/// Errors unless `source` is a beacon at a valid address.
+pub fn validate_source(api: &dyn Api, source: &RandomnessSource) -> Result<(), RandomnessError> {
+    let RandomnessSource::Beacon { contract } = source;
+    api.addr_validate(contract)?;
+    Ok(())
+}
+
+/// Commits a draw to the first beacon round of `source` after the latest one,
+/// and to `commitment` when the requester gave one. `nonce` separates
+/// independent draws (e.g. the egg or box being opened).
+pub fn request_randomness(
+    deps: Deps,
+    source: &RandomnessSource,
+    nonce: &[u8],
+    commitment: Option<Binary>,
+) -> Result<RandomnessRequest, RandomnessError> {
+    let RandomnessSource::Beacon { contract: beacon } = source.clone();
+    if let Some(commitment) = &commitment {
+        if commitment.len() != 32 {
+            return Err(RandomnessError::InvalidCommitment {});
+        }
+    }
+    let latest: LatestRandomnessResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: beacon.clone(),
+            msg: to_json_binary(&BeaconQueryMsg::LatestRandomness {})?,
+        }))?;
+    Ok(RandomnessRequest {
+        beacon,
+        round: latest.round + 1,
+        commitment,
+        nonce: Binary::from(nonce),
+    })
+}
+
+/// Draws the randomness `request` committed to. Fails until its beacon round
+/// is published, and unless `secret` opens the request's commitment.
+pub fn fulfil_randomness(
+    deps: Deps,
+    env: &Env,
+    request: &RandomnessRequest,
+    secret: Option<Binary>,
+) -> Result<Randomness, RandomnessError> {
+    let secret = match (&request.commitment, secret) {
+        (None, None) => Binary::default(),
+        (Some(commitment), Some(secret))
+            if Sha256::digest(secret.as_slice())[..] == commitment[..] =>
+        {
+            secret
+        }
+        _ => return Err(RandomnessError::SecretMismatch {}),
+    };
+    let res: RandomnessResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: request.beacon.clone(),
+        msg: to_json_binary(&BeaconQueryMsg::Randomness {
+            round: request.round,
+        })?,
+    }))?;
+    let randomness = res.randomness.ok_or(RandomnessError::RoundNotPublished {
+        round: request.round,
+    })?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(randomness.as_slice());
+    hasher.update(secret.as_slice());
+    hasher.update(env.contract.address.as_bytes());
+    hasher.update(request.nonce.as_slice());
+    Ok(Randomness {
+        round: request.round,
+        value: hasher.finalize().into(),
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod mock {
+    use super::{BeaconQueryMsg, LatestRandomnessResponse, RandomnessResponse};
+    use cosmwasm_std::{
+        from_json, to_json_binary, Binary, ContractResult, Deps, DepsMut, Env, MessageInfo, Order,
+        QuerierResult, Response, StdError, StdResult, SystemError, SystemResult, WasmQuery,
+    };
+    use cw_storage_plus::Map;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    /// Wasm query handler for `MockQuerier::update_wasm` that answers beacon
+    /// queries sent to `beacon` as if `round` was its latest round and every
+    /// round so far gave `randomness`.
+    pub fn mock_beacon(
+        beacon: &str,
+        round: u64,
+        randomness: Vec<u8>,
+    ) -> impl Fn(&WasmQuery) -> QuerierResult {
+        let beacon = beacon.to_string();
+        move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if *contract_addr == beacon => {
+                let randomness = Binary::from(randomness.clone());
+                let res = match from_json(msg).unwrap() {
+                    BeaconQueryMsg::LatestRandomness {} => {
+                        to_json_binary(&LatestRandomnessResponse { round, randomness })
+                    }
+                    BeaconQueryMsg::Randomness { round: asked } => {
+                        to_json_binary(&RandomnessResponse {
+                            round: asked,
+                            randomness: Some(randomness).filter(|_| asked <= round),
+                        })
+                    }
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "mock beacon only answers the configured contract".to_string(),
+            }),
+        }
+    }
+
+    const ROUNDS: Map<u64, Binary> = Map::new("rounds");
+
+    /// Publishes a round of the mock beacon contract.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct PublishRound {
+        pub round: u64,
+        pub randomness: Binary,
+    }
+
+    /// Entry points of a beacon contract for multi-tests, instantiated with
+    /// its first round.
+    pub fn beacon_instantiate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: PublishRound,
+    ) -> StdResult<Response> {
+        beacon_execute(deps, env, info, msg)
+    }
+
+    pub fn beacon_execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: PublishRound,
+    ) -> StdResult<Response> {
+        ROUNDS.save(deps.storage, msg.round, &msg.randomness)?;
+        Ok(Response::new())
+    }
+
+    pub fn beacon_query(deps: Deps, _env: Env, msg: BeaconQueryMsg) -> StdResult<Binary> {
+        match msg {
+            BeaconQueryMsg::LatestRandomness {} => {
+                let (round, randomness) = ROUNDS
+                    .range(deps.storage, None, None, Order::Descending)
+                    .next()
+                    .ok_or_else(|| StdError::not_found("round"))??;
+                to_json_binary(&LatestRandomnessResponse { round, randomness })
+            }
+            BeaconQueryMsg::Randomness { round } => to_json_binary(&RandomnessResponse {
+                round,
+                randomness: ROUNDS.may_load(deps.storage, round)?,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::mock_beacon;
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+
+    #[test]
+    fn requests_wait_for_the_next_round() {
+        let mut deps = mock_dependencies();
+        deps.querier
+            .update_wasm(mock_beacon("beacon", 42, vec![7u8; 32]));
+        let source = RandomnessSource::Beacon {
+            contract: "beacon".to_string(),
+        };
+        let request = request_randomness(deps.as_ref(), &source, b"egg", None).unwrap();
+        assert_eq!(request.round, 43);
+        let err = fulfil_randomness(deps.as_ref(), &mock_env(), &request, None).unwrap_err();
+        assert_eq!(err, RandomnessError::RoundNotPublished { round: 43 });
+
+        deps.querier
+            .update_wasm(mock_beacon("beacon", 43, vec![7u8; 32]));
+        let first = fulfil_randomness(deps.as_ref(), &mock_env(), &request, None).unwrap();
+        assert_eq!(first.round, 43);
+        let again = fulfil_randomness(deps.as_ref(), &mock_env(), &request, None).unwrap();
+        assert_eq!(first, again);
+        let other = request_randomness(deps.as_ref(), &source, b"box", None).unwrap();
+        let other = fulfil_randomness(
+            deps.as_ref(),
+            &mock_env(),
+            &RandomnessRequest { round: 43, ..other },
+            None,
+        )
+        .unwrap();
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn commitment_must_be_opened() {
+        let mut deps = mock_dependencies();
+        deps.querier
+            .update_wasm(mock_beacon("beacon", 1, vec![7u8; 32]));
+        let source = RandomnessSource::Beacon {
+            contract: "beacon".to_string(),
+        };
+        let err = request_randomness(deps.as_ref(), &source, b"egg", Some(Binary::from(b"short")))
+            .unwrap_err();
+        assert_eq!(err, RandomnessError::InvalidCommitment {});
+
+        let secret = Binary::from(b"secret");
+        let commitment = Binary::from(Sha256::digest(secret.as_slice()).to_vec());
+        let request = request_randomness(deps.as_ref(), &source, b"egg", Some(commitment)).unwrap();
+        deps.querier
+            .update_wasm(mock_beacon("beacon", 2, vec![7u8; 32]));
+        for wrong in [None, Some(Binary::from(b"guess"))] {
+            let err = fulfil_randomness(deps.as_ref(), &mock_env(), &request, wrong).unwrap_err();
+            assert_eq!(err, RandomnessError::SecretMismatch {});
+        }
+        fulfil_randomness(deps.as_ref(), &mock_env(), &request, Some(secret)).unwrap();
+    }
+
+    #[test]
+    fn pick_follows_weights() {
+        let table = vec![("never", 0u64), ("always", 10_000u64)];
+        for seed in 0..32u8 {
+            let randomness = Randomness {
+                round: 0,
+                value: [seed; 32],
+            };
+            let (_, picked) = randomness.pick(&table, |entry| entry.1).unwrap();
+            assert_eq!(picked.0, "always");
+        }
+        let empty: Vec<(&str, u64)> = vec![];
+        let randomness = Randomness {
+            round: 0,
+            value: [0; 32],
+        };
+        assert_eq!(randomness.pick(&empty, |entry| entry.1), None);
+    }
+}
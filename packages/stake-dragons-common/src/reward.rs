@@ -0,0 +1,310 @@
+use cosmwasm_std::{Decimal, Order, StdError, StdResult, Storage, Uint128, Uint64};
+use cw_storage_plus::{Bound, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub const SECONDS_PER_DAY: u64 = 86400;
+/// Micro units per whole reward token.
+pub const TOKEN_UNIT: u128 = 1_000_000;
+
+/// Emission per token per day, in micro units, keyed by kind. Kinds without a
+/// rate earn the `daily_income` they were minted with.
+pub const EMISSION_RATES: Map<&str, Uint128> = Map::new("emission_rates");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionRate {
+    pub kind: String,
+    pub per_day: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionRatesResponse {
+    pub rates: Vec<EmissionRate>,
+}
+
+/// Every rate a kind has had, keyed by the time it took effect. A rate holds
+/// until the next checkpoint of its kind.
+pub const RATE_HISTORY: Map<(&str, u64), Uint128> = Map::new("rate_history");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateCheckpoint {
+    pub from: Uint64,
+    pub per_day: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateHistoryResponse {
+    pub kind: String,
+    pub checkpoints: Vec<RateCheckpoint>,
+}
+
+/// The reward window of a staked token, as kept by the NFT contracts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Accrual {
+    pub reward_start_time: Uint64,
+    pub reward_end_time: Uint64,
+    pub unstaking_process: bool,
+}
+
+impl Accrual {
+    /// Start and end of the window earned up to `now`. Rewards stop when
+    /// unstaking starts.
+    pub fn window(&self, now: u64) -> StdResult<(u64, u64)> {
+        let start = self.reward_start_time.u64();
+        let end = if self.unstaking_process {
+            self.reward_end_time.u64()
+        } else {
+            now
+        };
+        if end < start {
+            return Err(StdError::generic_err("reward window ends before it starts"));
+        }
+        Ok((start, end))
+    }
+
+    /// Seconds earned up to `now`.
+    pub fn elapsed(&self, now: u64) -> StdResult<u64> {
+        let (start, end) = self.window(now)?;
+        Ok(end - start)
+    }
+}
+
+/// Reward a token of `kind` accrued over `accrual` up to `now`, each stretch
+/// paid at the rate in force at the time. Before the first checkpoint of its
+/// kind a token earns its own `daily_income`.
+pub fn accrued_reward(
+    storage: &dyn Storage,
+    kind: &str,
+    daily_income: Decimal,
+    accrual: &Accrual,
+    now: u64,
+) -> StdResult<Uint128> {
+    let (start, end) = accrual.window(now)?;
+    let history = RATE_HISTORY.prefix(kind);
+    let mut per_day = match history
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(start)),
+            Order::Descending,
+        )
+        .next()
+    {
+        Some(item) => item?.1,
+        None => Uint128::new(TOKEN_UNIT) * daily_income,
+    };
+    // sum rate * seconds over every stretch and divide once, so splitting a
+    // window at a checkpoint never loses a rounding remainder
+    let mut from = start;
+    let mut total = Uint128::zero();
+    let changes = history.range(
+        storage,
+        Some(Bound::exclusive(start)),
+        Some(Bound::exclusive(end)),
+        Order::Ascending,
+    );
+    for item in changes {
+        let (at, rate) = item?;
+        total = total.checked_add(Uint128::from(at - from).checked_mul(per_day)?)?;
+        from = at;
+        per_day = rate;
+    }
+    total = total.checked_add(Uint128::from(end - from).checked_mul(per_day)?)?;
+    Ok(total.checked_div(Uint128::from(SECONDS_PER_DAY))?)
+}
+
+/// Daily income, in whole tokens, a token of `kind` earns at `now`: the last
+/// rate checkpointed by then, or its own `daily_income` before any.
+pub fn current_daily_income(
+    storage: &dyn Storage,
+    kind: &str,
+    daily_income: Decimal,
+    now: u64,
+) -> StdResult<Decimal> {
+    let rate = RATE_HISTORY
+        .prefix(kind)
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(now)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?;
+    Ok(match rate {
+        Some((_, per_day)) => Decimal::from_ratio(per_day, TOKEN_UNIT),
+        None => daily_income,
+    })
+}
+
+/// Sets the current rate of each kind and checkpoints it from `now` on.
+pub fn save_emission_rates(
+    storage: &mut dyn Storage,
+    rates: &[EmissionRate],
+    now: u64,
+) -> StdResult<()> {
+    for rate in rates {
+        EMISSION_RATES.save(storage, &rate.kind, &rate.per_day)?;
+        RATE_HISTORY.save(storage, (&rate.kind, now), &rate.per_day)?;
+    }
+    Ok(())
+}
+
+pub fn query_rate_history(storage: &dyn Storage, kind: String) -> StdResult<RateHistoryResponse> {
+    let checkpoints = RATE_HISTORY
+        .prefix(&kind)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(from, per_day)| RateCheckpoint {
+                from: Uint64::new(from),
+                per_day,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(RateHistoryResponse { kind, checkpoints })
+}
+
+pub fn query_emission_rates(storage: &dyn Storage) -> StdResult<EmissionRatesResponse> {
+    let rates = EMISSION_RATES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(kind, per_day)| EmissionRate { kind, per_day }))
+        .collect::<StdResult<_>>()?;
+    Ok(EmissionRatesResponse { rates })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+    use proptest::prelude::*;
+    use std::str::FromStr;
+
+    fn staked(start: u64) -> Accrual {
+        Accrual {
+            reward_start_time: Uint64::new(start),
+            reward_end_time: Uint64::zero(),
+            unstaking_process: false,
+        }
+    }
+
+    /// Storage holding `checkpoints` as the rate history of `common`.
+    fn history(checkpoints: &[(u64, u128)]) -> MockStorage {
+        let mut storage = MockStorage::new();
+        for (at, per_day) in checkpoints {
+            RATE_HISTORY
+                .save(&mut storage, ("common", *at), &Uint128::new(*per_day))
+                .unwrap();
+        }
+        storage
+    }
+
+    fn accrued(storage: &MockStorage, accrual: &Accrual, now: u64) -> Uint128 {
+        accrued_reward(storage, "common", Decimal::one(), accrual, now).unwrap()
+    }
+
+    #[test]
+    fn checkpointed_rate_wins_over_daily_income() {
+        let mut storage = MockStorage::new();
+        let half = Decimal::from_str("0.5").unwrap();
+        let day = |storage: &MockStorage, kind| {
+            accrued_reward(storage, kind, half, &staked(0), SECONDS_PER_DAY).unwrap()
+        };
+        assert_eq!(day(&storage, "common"), Uint128::new(500_000));
+
+        save_emission_rates(
+            &mut storage,
+            &[EmissionRate {
+                kind: "common".to_string(),
+                per_day: Uint128::new(400_000),
+            }],
+            0,
+        )
+        .unwrap();
+        assert_eq!(day(&storage, "common"), Uint128::new(400_000));
+        assert_eq!(day(&storage, "rare"), Uint128::new(500_000));
+        assert_eq!(query_emission_rates(&storage).unwrap().rates.len(), 1);
+    }
+
+    #[test]
+    fn unstaking_stops_rewards() {
+        let storage = MockStorage::new();
+        let accrual = Accrual {
+            reward_start_time: Uint64::new(100),
+            reward_end_time: Uint64::new(100 + SECONDS_PER_DAY),
+            unstaking_process: true,
+        };
+        assert_eq!(
+            accrued(&storage, &accrual, 10 * SECONDS_PER_DAY),
+            Uint128::new(TOKEN_UNIT)
+        );
+        let err = accrued_reward(&storage, "common", Decimal::one(), &staked(200), 100);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn rate_changes_only_apply_going_forward() {
+        let mut storage = MockStorage::new();
+        let half = Decimal::from_str("0.5").unwrap();
+        let rate = |per_day| {
+            vec![EmissionRate {
+                kind: "common".to_string(),
+                per_day: Uint128::new(per_day),
+            }]
+        };
+        let accrued = |storage: &MockStorage, now| {
+            accrued_reward(storage, "common", half, &staked(0), now * SECONDS_PER_DAY).unwrap()
+        };
+        // no checkpoint yet: the token's own income
+        assert_eq!(accrued(&storage, 2), Uint128::new(1_000_000));
+
+        save_emission_rates(&mut storage, &rate(2_000_000), 2 * SECONDS_PER_DAY).unwrap();
+        assert_eq!(accrued(&storage, 2), Uint128::new(1_000_000));
+        assert_eq!(accrued(&storage, 3), Uint128::new(3_000_000));
+
+        save_emission_rates(&mut storage, &rate(0), 3 * SECONDS_PER_DAY).unwrap();
+        assert_eq!(accrued(&storage, 10), Uint128::new(3_000_000));
+
+        let history = query_rate_history(&storage, "common".to_string()).unwrap();
+        let from: Vec<_> = history.checkpoints.iter().map(|c| c.from.u64()).collect();
+        assert_eq!(from, vec![2 * SECONDS_PER_DAY, 3 * SECONDS_PER_DAY]);
+    }
+
+    #[test]
+    fn current_income_follows_the_checkpoints() {
+        let storage = history(&[(10, 2_500_000), (20, 0)]);
+        let income = |now| current_daily_income(&storage, "common", Decimal::one(), now).unwrap();
+        assert_eq!(income(5), Decimal::one());
+        assert_eq!(income(10), Decimal::from_str("2.5").unwrap());
+        assert_eq!(income(30), Decimal::zero());
+    }
+
+    fn checkpoints() -> impl Strategy<Value = Vec<(u64, u128)>> {
+        prop::collection::vec((0u64..2_000_000, 0u128..1_000_000_000_000), 0..8)
+    }
+
+    proptest! {
+        #[test]
+        fn full_days_pay_the_daily_rate(per_day in 0u128..1_000_000_000_000, days in 0u64..3650) {
+            let storage = history(&[(0, per_day)]);
+            let reward = accrued(&storage, &staked(0), days * SECONDS_PER_DAY);
+            prop_assert_eq!(reward, Uint128::new(per_day * days as u128));
+        }
+
+        #[test]
+        fn reward_never_decreases(checkpoints in checkpoints(), start in 0u64..1_000_000, a in 0u64..1_000_000, b in 0u64..1_000_000) {
+            let storage = history(&checkpoints);
+            let (early, late) = (start + a.min(b), start + a.max(b));
+            prop_assert!(accrued(&storage, &staked(start), early) <= accrued(&storage, &staked(start), late));
+        }
+
+        #[test]
+        fn later_rate_changes_never_alter_earned_rewards(checkpoints in checkpoints(), start in 0u64..1_000_000, elapsed in 0u64..1_000_000, delay in 0u64..1_000_000, per_day in 0u128..1_000_000_000_000) {
+            let mut storage = history(&checkpoints);
+            let now = start + elapsed;
+            let earned = accrued(&storage, &staked(start), now);
+            let kind = EmissionRate { kind: "common".to_string(), per_day: Uint128::new(per_day) };
+            save_emission_rates(&mut storage, &[kind], now + delay).unwrap();
+            prop_assert_eq!(accrued(&storage, &staked(start), now), earned);
+        }
+    }
+}
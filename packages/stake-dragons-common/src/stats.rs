@@ -0,0 +1,164 @@
+use cosmwasm_std::{Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Contract-wide counters, moved along on every mint, burn, stake change and
+/// claim so they never need a scan.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct StakingStats {
+    pub minted: u64,
+    pub burned: u64,
+    pub staked: u64,
+    pub unstaking: u64,
+    pub rewards_claimed: Uint128,
+}
+
+pub const STAKING_STATS: Item<StakingStats> = Item::new("staking_stats");
+/// Staked tokens by kind. Kinds with nothing staked have no entry.
+pub const STAKED_BY_KIND: Map<&str, u64> = Map::new("staked_by_kind");
+
+/// Where a token stands in the staking cycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StakeStatus {
+    pub staked: bool,
+    pub unstaking: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KindStakedResponse {
+    pub kind: String,
+    pub staked: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingStatsResponse {
+    pub minted: u64,
+    pub burned: u64,
+    pub staked: u64,
+    pub unstaking: u64,
+    pub rewards_claimed: Uint128,
+    pub staked_by_kind: Vec<KindStakedResponse>,
+}
+
+fn load_stats(storage: &dyn Storage) -> StdResult<StakingStats> {
+    Ok(STAKING_STATS.may_load(storage)?.unwrap_or_default())
+}
+
+pub fn record_mint(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut stats = load_stats(storage)?;
+    stats.minted += 1;
+    STAKING_STATS.save(storage, &stats)
+}
+
+pub fn record_burn(storage: &mut dyn Storage) -> StdResult<()> {
+    let mut stats = load_stats(storage)?;
+    stats.burned += 1;
+    STAKING_STATS.save(storage, &stats)
+}
+
+pub fn record_claim(storage: &mut dyn Storage, amount: Uint128) -> StdResult<()> {
+    let mut stats = load_stats(storage)?;
+    stats.rewards_claimed = stats.rewards_claimed.checked_add(amount)?;
+    STAKING_STATS.save(storage, &stats)
+}
+
+/// Moves the counters along when a token of `kind` goes from `before` to
+/// `after`.
+pub fn record_status_change(
+    storage: &mut dyn Storage,
+    kind: &str,
+    before: StakeStatus,
+    after: StakeStatus,
+) -> StdResult<()> {
+    if before == after {
+        return Ok(());
+    }
+    let mut stats = load_stats(storage)?;
+    if before.staked != after.staked {
+        let count = STAKED_BY_KIND.may_load(storage, kind)?.unwrap_or_default();
+        let count = if after.staked {
+            stats.staked += 1;
+            count + 1
+        } else {
+            stats.staked = stats.staked.saturating_sub(1);
+            count.saturating_sub(1)
+        };
+        if count == 0 {
+            STAKED_BY_KIND.remove(storage, kind);
+        } else {
+            STAKED_BY_KIND.save(storage, kind, &count)?;
+        }
+    }
+    if before.unstaking != after.unstaking {
+        if after.unstaking {
+            stats.unstaking += 1;
+        } else {
+            stats.unstaking = stats.unstaking.saturating_sub(1);
+        }
+    }
+    STAKING_STATS.save(storage, &stats)
+}
+
+pub fn query_staking_stats(storage: &dyn Storage) -> StdResult<StakingStatsResponse> {
+    let stats = load_stats(storage)?;
+    let staked_by_kind = STAKED_BY_KIND
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(kind, staked)| KindStakedResponse { kind, staked }))
+        .collect::<StdResult<_>>()?;
+    Ok(StakingStatsResponse {
+        minted: stats.minted,
+        burned: stats.burned,
+        staked: stats.staked,
+        unstaking: stats.unstaking,
+        rewards_claimed: stats.rewards_claimed,
+        staked_by_kind,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    const IDLE: StakeStatus = StakeStatus {
+        staked: false,
+        unstaking: false,
+    };
+    const STAKED: StakeStatus = StakeStatus {
+        staked: true,
+        unstaking: false,
+    };
+    const UNSTAKING: StakeStatus = StakeStatus {
+        staked: true,
+        unstaking: true,
+    };
+
+    #[test]
+    fn counters_follow_the_staking_cycle() {
+        let mut storage = MockStorage::new();
+        record_mint(&mut storage).unwrap();
+        record_mint(&mut storage).unwrap();
+        record_status_change(&mut storage, "rare", IDLE, STAKED).unwrap();
+        record_status_change(&mut storage, "common", IDLE, STAKED).unwrap();
+        record_status_change(&mut storage, "rare", STAKED, UNSTAKING).unwrap();
+        record_claim(&mut storage, Uint128::new(500)).unwrap();
+
+        let stats = query_staking_stats(&storage).unwrap();
+        assert_eq!((stats.minted, stats.staked, stats.unstaking), (2, 2, 1));
+        assert_eq!(stats.rewards_claimed, Uint128::new(500));
+        assert_eq!(stats.staked_by_kind.len(), 2);
+
+        record_status_change(&mut storage, "rare", UNSTAKING, IDLE).unwrap();
+        record_burn(&mut storage).unwrap();
+        let stats = query_staking_stats(&storage).unwrap();
+        assert_eq!((stats.burned, stats.staked, stats.unstaking), (1, 1, 0));
+        assert_eq!(
+            stats.staked_by_kind,
+            vec![KindStakedResponse {
+                kind: "common".to_string(),
+                staked: 1,
+            }]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use cosmwasm_std::{StdError, StdResult, Storage, Uint64};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Set once the owner freezes the collection size; it can't be raised again.
+pub const SUPPLY_FROZEN: Item<bool> = Item::new("supply_frozen");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SupplyMsg {
+    /// Lets the collection grow to `size` tokens.
+    RaiseCap { size: Uint64 },
+    /// Keeps the collection at its current size for good.
+    FreezeCap {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SupplyResponse {
+    /// Tokens ever minted, burned ones included.
+    pub minted: Uint64,
+    pub burned: Uint64,
+    /// Tokens that can still be minted.
+    pub remaining: Uint64,
+    pub cap: Uint64,
+    pub frozen: bool,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SupplyError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("All {cap} tokens of the collection were minted")]
+    MaxSupplyReached { cap: Uint64 },
+
+    #[error("The collection size is frozen")]
+    CapFrozen {},
+
+    #[error("New size {size} must be above the current {cap}")]
+    CapNotRaised { cap: Uint64, size: Uint64 },
+}
+
+/// Errors unless one more token fits in a collection of `cap` tokens after
+/// `minted` were minted, burned ones included.
+pub fn check_supply(minted: Uint64, cap: Uint64) -> Result<(), SupplyError> {
+    if minted >= cap {
+        return Err(SupplyError::MaxSupplyReached { cap });
+    }
+    Ok(())
+}
+
+/// Applies `msg` to a collection of `cap` tokens and returns the size it must
+/// store from now on.
+pub fn update_supply(
+    storage: &mut dyn Storage,
+    cap: Uint64,
+    msg: SupplyMsg,
+) -> Result<Uint64, SupplyError> {
+    if SUPPLY_FROZEN.may_load(storage)?.unwrap_or_default() {
+        return Err(SupplyError::CapFrozen {});
+    }
+    match msg {
+        SupplyMsg::RaiseCap { size } => {
+            if size <= cap {
+                return Err(SupplyError::CapNotRaised { cap, size });
+            }
+            Ok(size)
+        }
+        SupplyMsg::FreezeCap {} => {
+            SUPPLY_FROZEN.save(storage, &true)?;
+            Ok(cap)
+        }
+    }
+}
+
+/// Supply of a collection of `cap` tokens that minted `minted` and still has
+/// `live` of them.
+pub fn query_supply(
+    storage: &dyn Storage,
+    minted: Uint64,
+    live: u64,
+    cap: Uint64,
+) -> StdResult<SupplyResponse> {
+    Ok(SupplyResponse {
+        minted,
+        burned: minted.saturating_sub(Uint64::new(live)),
+        remaining: cap.saturating_sub(minted),
+        cap,
+        frozen: SUPPLY_FROZEN.may_load(storage)?.unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn cap_can_be_raised_until_frozen() {
+        let mut storage = MockStorage::new();
+        let cap = Uint64::new(2);
+        check_supply(Uint64::new(1), cap).unwrap();
+        assert_eq!(
+            check_supply(Uint64::new(2), cap),
+            Err(SupplyError::MaxSupplyReached { cap })
+        );
+
+        let size = Uint64::new(2);
+        assert_eq!(
+            update_supply(&mut storage, cap, SupplyMsg::RaiseCap { size }),
+            Err(SupplyError::CapNotRaised { cap, size })
+        );
+        let size = Uint64::new(3);
+        let cap = update_supply(&mut storage, cap, SupplyMsg::RaiseCap { size }).unwrap();
+        assert_eq!(cap, size);
+        update_supply(&mut storage, cap, SupplyMsg::FreezeCap {}).unwrap();
+        assert_eq!(
+            update_supply(&mut storage, cap, SupplyMsg::RaiseCap { size: 4u64.into() }),
+            Err(SupplyError::CapFrozen {})
+        );
+
+        let supply = query_supply(&storage, Uint64::new(2), 1, cap).unwrap();
+        assert_eq!(supply.burned, Uint64::new(1));
+        assert_eq!(supply.remaining, Uint64::new(1));
+        assert!(supply.frozen);
+    }
+}
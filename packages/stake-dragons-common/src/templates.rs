@@ -0,0 +1,124 @@
+use crate::nft::{CustomMintMsg, Metadata, MintExecuteMsg, Trait};
+use cosmwasm_std::{Order, StdResult, Storage};
+use cw721_base::MintMsg;
+use cw_storage_plus::Map;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Art and traits a minter gives every token of one kind.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MetadataTemplate {
+    pub kind: String,
+    pub name: String,
+    pub description: String,
+    /// Where the collection's art lives; also its external URL.
+    pub image_base_uri: String,
+    /// Image of this kind, relative to `image_base_uri`.
+    pub image: Option<String>,
+    /// Traits minted besides `kind`, such as a dragon's daily income.
+    pub traits: Vec<Trait>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MetadataTemplatesResponse {
+    pub templates: Vec<MetadataTemplate>,
+}
+
+/// Templates by kind.
+pub const METADATA_TEMPLATES: Map<&str, MetadataTemplate> = Map::new("metadata_templates");
+
+impl MetadataTemplate {
+    /// Mint message for token `id` of this kind, owned by `owner`.
+    pub fn mint_msg(&self, id: &str, owner: &str) -> MintExecuteMsg {
+        let mut attributes = vec![Trait::new("kind", &self.kind)];
+        attributes.extend(self.traits.iter().cloned());
+        let base = self.image_base_uri.trim_end_matches('/');
+        let image = match &self.image {
+            Some(image) => format!("{}/{}", base, image),
+            None => base.to_string(),
+        };
+        let metadata = Metadata {
+            name: Some(self.name.clone()),
+            description: Some(self.description.clone()),
+            image: Some(image),
+            external_url: Some(base.to_string()),
+            attributes: Some(attributes.clone()),
+            image_data: Some(base.to_string()),
+            background_color: Some(String::new()),
+            animation_url: Some(String::new()),
+            youtube_url: Some(String::new()),
+        };
+        MintExecuteMsg::Mint(CustomMintMsg {
+            base: MintMsg {
+                token_id: id.to_string(),
+                owner: owner.to_string(),
+                token_uri: None,
+                extension: Some(metadata),
+            },
+            extension: attributes,
+        })
+    }
+}
+
+/// Template of `kind`, if one was set.
+pub fn metadata_template(storage: &dyn Storage, kind: &str) -> StdResult<Option<MetadataTemplate>> {
+    METADATA_TEMPLATES.may_load(storage, &kind.to_lowercase())
+}
+
+/// Adds the template of each kind or replaces the one it had.
+pub fn save_metadata_templates(
+    storage: &mut dyn Storage,
+    templates: &[MetadataTemplate],
+) -> StdResult<()> {
+    for template in templates {
+        let mut template = template.clone();
+        template.kind = template.kind.to_lowercase();
+        METADATA_TEMPLATES.save(storage, &template.kind, &template)?;
+    }
+    Ok(())
+}
+
+pub fn query_metadata_templates(storage: &dyn Storage) -> StdResult<MetadataTemplatesResponse> {
+    let templates = METADATA_TEMPLATES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, template)| template))
+        .collect::<StdResult<_>>()?;
+    Ok(MetadataTemplatesResponse { templates })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    fn template(kind: &str, image: Option<&str>) -> MetadataTemplate {
+        MetadataTemplate {
+            kind: kind.to_string(),
+            name: format!("{}_stake_dragon", kind.to_lowercase()),
+            description: String::new(),
+            image_base_uri: "ipfs://art/".to_string(),
+            image: image.map(str::to_string),
+            traits: vec![Trait::new("daily_income", "0.5")],
+        }
+    }
+
+    #[test]
+    fn templates_build_the_mint_message() {
+        let mut storage = MockStorage::new();
+        save_metadata_templates(&mut storage, &[template("Common", Some("1.png"))]).unwrap();
+        save_metadata_templates(&mut storage, &[template("common", Some("2.png"))]).unwrap();
+        assert_eq!(
+            query_metadata_templates(&storage).unwrap().templates.len(),
+            1
+        );
+        assert!(metadata_template(&storage, "rare").unwrap().is_none());
+
+        let template = metadata_template(&storage, "COMMON").unwrap().unwrap();
+        let MintExecuteMsg::Mint(msg) = template.mint_msg("7", "owner");
+        let metadata = msg.base.extension.unwrap();
+        assert_eq!(metadata.image.unwrap(), "ipfs://art/2.png");
+        assert_eq!(metadata.external_url.unwrap(), "ipfs://art");
+        let values: Vec<_> = msg.extension.iter().map(|t| t.value.as_str()).collect();
+        assert_eq!(values, vec!["common", "0.5"]);
+    }
+}